@@ -0,0 +1,347 @@
+use axum::{http::StatusCode, response::Response};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, to_bson, DateTime, Document, Regex},
+    Collection, Database,
+};
+
+use super::admin_structure::{
+    AdminAction, AuditEntry, AuditEntryView, AuditQuery, ChangeRoleRequest, ReviewDecision, ReviewRequest,
+    UserListQuery, UserPage, UserView,
+};
+use crate::{
+    session::session_model::revoke_sessions,
+    user::user_structure::{RoleStatus, User},
+    utils::{
+        db::AppState,
+        paging,
+        response::{error_response, success_response},
+    },
+};
+
+pub async fn list_pending_users(state: AppState) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<User> = db.database("disaster").collection("users");
+
+    match collection
+        .find(doc! { "role_status": RoleStatus::Pending.as_str() })
+        .sort(doc! { "_id": 1 })
+        .await
+    {
+        Ok(cursor) => match cursor.try_collect::<Vec<User>>().await {
+            Ok(users) => {
+                let users: Vec<UserView> = users.into_iter().map(UserView::from).collect();
+                success_response("Pending applicants retrieved successfully", users, StatusCode::OK)
+            }
+            Err(e) => error_response(&format!("Failed to collect users: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        },
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn review_applicant(
+    state: AppState,
+    admin_id: ObjectId,
+    user_id: ObjectId,
+    review: ReviewRequest,
+) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<User> = db.database("disaster").collection("users");
+
+    let user = match collection
+        .find_one(doc! { "_id": user_id, "role_status": RoleStatus::Pending.as_str() })
+        .await
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => return error_response("No pending applicant found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let mut set = doc! {
+        "role_reason": review.reason.clone(),
+        "role_reviewed_by": admin_id,
+        "role_reviewed_at": DateTime::now()
+    };
+    let mut update = doc! {};
+
+    match review.decision {
+        ReviewDecision::Approve => {
+            let role = match user.requested_role {
+                Some(role) => role,
+                None => return error_response("Applicant has not requested a role", StatusCode::BAD_REQUEST),
+            };
+            set.insert("role_status", RoleStatus::Approved.as_str());
+            // Approving adds the role; roles granted earlier are kept
+            update.insert("$addToSet", doc! { "roles": role.as_str() });
+        }
+        ReviewDecision::Reject => {
+            set.insert("role_status", RoleStatus::Rejected.as_str());
+        }
+    }
+    update.insert("$set", set);
+
+    if let Err(e) = collection.update_one(doc! { "_id": user_id }, update).await {
+        return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let detail = format!("{:?}: {}", review.decision, review.reason.unwrap_or_default());
+    match record_audit(&db.database("disaster"), admin_id, AdminAction::ReviewApplicant, Some(user_id), Some(detail)).await {
+        Ok(_) => match collection.find_one(doc! { "_id": user_id }).await {
+            Ok(Some(user)) => success_response("Applicant reviewed successfully", UserView::from(user), StatusCode::OK),
+            _ => success_response("Applicant reviewed successfully", user_id.to_hex(), StatusCode::OK),
+        },
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn change_roles(
+    state: AppState,
+    admin_id: ObjectId,
+    user_id: ObjectId,
+    request: ChangeRoleRequest,
+) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<User> = db.database("disaster").collection("users");
+
+    let roles: Vec<&str> = request.roles.iter().map(|role| role.as_str()).collect();
+    let detail = roles.join(",");
+
+    let update = doc! {
+        "$set": {
+            "roles": &roles,
+            "role_status": RoleStatus::Approved.as_str(),
+            "role_reason": request.reason,
+            "role_reviewed_by": admin_id,
+            "role_reviewed_at": DateTime::now()
+        },
+        "$unset": { "role": "" }
+    };
+
+    match collection.update_one(doc! { "_id": user_id }, update).await {
+        Ok(result) if result.matched_count == 0 => return error_response("User not found", StatusCode::NOT_FOUND),
+        Ok(_) => {}
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    match record_audit(&db.database("disaster"), admin_id, AdminAction::ChangeRoles, Some(user_id), Some(detail)).await {
+        Ok(_) => match collection.find_one(doc! { "_id": user_id }).await {
+            Ok(Some(user)) => success_response("Roles updated successfully", UserView::from(user), StatusCode::OK),
+            _ => success_response("Roles updated successfully", user_id.to_hex(), StatusCode::OK),
+        },
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+
+/// Appends an entry to the admin audit log
+pub async fn record_audit(
+    db: &Database,
+    admin_id: ObjectId,
+    action: AdminAction,
+    target_id: Option<ObjectId>,
+    detail: Option<String>,
+) -> mongodb::error::Result<()> {
+    let audit: Collection<AuditEntry> = db.collection("admin_audit");
+    let entry = AuditEntry {
+        id: None,
+        admin_id,
+        action,
+        target_id,
+        detail,
+        at: DateTime::now(),
+    };
+    audit.insert_one(entry).await?;
+    Ok(())
+}
+
+/// Escapes user input so it is matched literally inside a `$regex`
+pub fn escape_regex(value: &str) -> String {
+    value
+        .chars()
+        .fold(String::with_capacity(value.len()), |mut escaped, c| {
+            if "\\.+*?()|[]{}^$".contains(c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            escaped
+        })
+}
+
+pub async fn list_users(state: AppState, query: UserListQuery) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<User> = db.database("disaster").collection("users");
+
+    let mut filter = Document::new();
+    if let Some(email) = query.email.as_deref().map(str::trim).filter(|email| !email.is_empty()) {
+        filter.insert(
+            "email",
+            Regex {
+                pattern: escape_regex(email),
+                options: "i".to_string(),
+            },
+        );
+    }
+    if let Some(role) = query.role {
+        // Legacy accounts still carry a single `role`
+        filter.insert("$or", vec![doc! { "roles": role.as_str() }, doc! { "role": role.as_str() }]);
+    }
+    if let Some(role_status) = query.role_status {
+        filter.insert("role_status", role_status.as_str());
+    }
+    match query.deactivated {
+        Some(true) => {
+            filter.insert("deactivated", true);
+        }
+        Some(false) => {
+            filter.insert("deactivated", doc! { "$ne": true });
+        }
+        None => {}
+    }
+
+    let (page, per_page) = paging::page_window(query.page, query.per_page);
+
+    let total = match collection.count_documents(filter.clone()).await {
+        Ok(total) => total,
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match collection
+        .find(filter)
+        .sort(doc! { "_id": 1 })
+        .skip(paging::skip(page, per_page))
+        .limit(per_page)
+        .await
+    {
+        Ok(cursor) => match cursor.try_collect::<Vec<User>>().await {
+            Ok(users) => {
+                let users = users.into_iter().map(UserView::from).collect();
+                let page = UserPage { users, page, per_page, total };
+                success_response("Users retrieved successfully", page, StatusCode::OK)
+            }
+            Err(e) => error_response(&format!("Failed to collect users: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        },
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn get_user(state: AppState, user_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<User> = db.database("disaster").collection("users");
+
+    match collection.find_one(doc! { "_id": user_id }).await {
+        Ok(Some(user)) => success_response("User retrieved successfully", UserView::from(user), StatusCode::OK),
+        Ok(None) => error_response("User not found", StatusCode::NOT_FOUND),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Deactivating also ends every session, so the user is logged out everywhere at once
+pub async fn set_deactivated(
+    state: AppState,
+    admin_id: ObjectId,
+    user_id: ObjectId,
+    deactivated: bool,
+    reason: Option<String>,
+) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let collection: Collection<User> = database.collection("users");
+
+    let update = if deactivated {
+        doc! {
+            "$set": {
+                "deactivated": true,
+                "deactivated_at": DateTime::now(),
+                "deactivated_by": admin_id,
+                "deactivated_reason": reason.clone()
+            }
+        }
+    } else {
+        doc! {
+            "$set": { "deactivated": false },
+            "$unset": { "deactivated_at": "", "deactivated_by": "", "deactivated_reason": "" }
+        }
+    };
+
+    match collection.update_one(doc! { "_id": user_id }, update).await {
+        Ok(result) if result.matched_count == 0 => return error_response("User not found", StatusCode::NOT_FOUND),
+        Ok(_) => {}
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    if deactivated {
+        if let Err(e) = revoke_sessions(&database, doc! { "user_id": user_id, "revoked": false }).await {
+            return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    let action = if deactivated { AdminAction::DeactivateUser } else { AdminAction::ReactivateUser };
+    if let Err(e) = record_audit(&database, admin_id, action, Some(user_id), reason).await {
+        return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    match collection.find_one(doc! { "_id": user_id }).await {
+        Ok(Some(user)) => success_response(
+            if deactivated { "User deactivated successfully" } else { "User reactivated successfully" },
+            UserView::from(user),
+            StatusCode::OK,
+        ),
+        _ => success_response("User updated successfully", user_id.to_hex(), StatusCode::OK),
+    }
+}
+
+pub async fn revoke_user_sessions(state: AppState, admin_id: ObjectId, user_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    match database.collection::<User>("users").count_documents(doc! { "_id": user_id }).await {
+        Ok(0) => return error_response("User not found", StatusCode::NOT_FOUND),
+        Ok(_) => {}
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let count = match revoke_sessions(&database, doc! { "user_id": user_id, "revoked": false }).await {
+        Ok(count) => count,
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let detail = format!("{} sessions revoked", count);
+    match record_audit(&database, admin_id, AdminAction::RevokeSessions, Some(user_id), Some(detail)).await {
+        Ok(_) => success_response("Sessions revoked successfully", count, StatusCode::OK),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn list_audit_entries(state: AppState, query: AuditQuery) -> Response {
+    let db = state.db.lock().await;
+    let audit: Collection<AuditEntry> = db.database("disaster").collection("admin_audit");
+
+    let mut filter = doc! {};
+    for (field, value) in [("admin_id", &query.admin_id), ("target_id", &query.target_id)] {
+        if let Some(value) = value {
+            match ObjectId::parse_str(value) {
+                Ok(id) => {
+                    filter.insert(field, id);
+                }
+                Err(_) => return error_response(&format!("Invalid {} format", field), StatusCode::BAD_REQUEST),
+            }
+        }
+    }
+    if let Some(action) = query.action {
+        filter.insert("action", to_bson(&action).unwrap_or_default());
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT).clamp(1, 1000);
+
+    match audit.find(filter).sort(doc! { "at": -1 }).limit(limit).await {
+        Ok(cursor) => match cursor.try_collect::<Vec<AuditEntry>>().await {
+            Ok(entries) => {
+                let entries: Vec<AuditEntryView> = entries.into_iter().map(AuditEntryView::from).collect();
+                success_response("Audit log retrieved successfully", entries, StatusCode::OK)
+            }
+            Err(e) => error_response(&format!("Failed to collect audit log: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        },
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,160 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+
+use super::{
+    admin_model,
+    admin_structure::{
+        AdminAction, AuditQuery, ChangeRoleRequest, DeactivateRequest, ReviewDecision, ReviewRequest,
+        UserListQuery,
+    },
+};
+use crate::{
+    middleware::permission::AuthUser,
+    security::{security_model, security_structure::AuthEventQuery},
+    utils::{db::AppState, response::error_response},
+};
+
+pub async fn list_applicants_service(State(state): State<AppState>) -> Response {
+    admin_model::list_pending_users(state).await
+}
+
+pub async fn review_applicant_service(
+    State(state): State<AppState>,
+    admin: AuthUser,
+    Path(user_id): Path<String>,
+    Json(payload): Json<ReviewRequest>,
+) -> Response {
+    let user_id = match ObjectId::parse_str(&user_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid user ID format", StatusCode::BAD_REQUEST),
+    };
+
+    let has_reason = payload.reason.as_deref().is_some_and(|reason| !reason.trim().is_empty());
+    if payload.decision == ReviewDecision::Reject && !has_reason {
+        return error_response("A reason is required when rejecting an applicant", StatusCode::BAD_REQUEST);
+    }
+
+    admin_model::review_applicant(state, admin.id, user_id, payload).await
+}
+
+pub async fn change_roles_service(
+    State(state): State<AppState>,
+    admin: AuthUser,
+    Path(user_id): Path<String>,
+    Json(payload): Json<ChangeRoleRequest>,
+) -> Response {
+    let user_id = match ObjectId::parse_str(&user_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid user ID format", StatusCode::BAD_REQUEST),
+    };
+
+    if admin.id == user_id {
+        return error_response("Admins cannot change their own roles", StatusCode::BAD_REQUEST);
+    }
+
+    admin_model::change_roles(state, admin.id, user_id, payload).await
+}
+
+pub async fn unlock_user_service(
+    State(state): State<AppState>,
+    admin: AuthUser,
+    Path(user_id): Path<String>,
+) -> Response {
+    let user_id = match ObjectId::parse_str(&user_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid user ID format", StatusCode::BAD_REQUEST),
+    };
+
+    let response = security_model::unlock_account(state.clone(), admin.id, user_id).await;
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let db = state.db.lock().await;
+    match admin_model::record_audit(&db.database("disaster"), admin.id, AdminAction::UnlockAccount, Some(user_id), None).await {
+        Ok(_) => response,
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn list_auth_events_service(
+    State(state): State<AppState>,
+    Query(query): Query<AuthEventQuery>,
+) -> Response {
+    security_model::list_auth_events(state, query).await
+}
+
+pub async fn list_users_service(
+    State(state): State<AppState>,
+    Query(query): Query<UserListQuery>,
+) -> Response {
+    admin_model::list_users(state, query).await
+}
+
+pub async fn get_user_service(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Response {
+    let user_id = match ObjectId::parse_str(&user_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid user ID format", StatusCode::BAD_REQUEST),
+    };
+
+    admin_model::get_user(state, user_id).await
+}
+
+pub async fn deactivate_user_service(
+    State(state): State<AppState>,
+    admin: AuthUser,
+    Path(user_id): Path<String>,
+    Json(payload): Json<DeactivateRequest>,
+) -> Response {
+    let user_id = match ObjectId::parse_str(&user_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid user ID format", StatusCode::BAD_REQUEST),
+    };
+
+    if admin.id == user_id {
+        return error_response("Admins cannot deactivate their own account", StatusCode::BAD_REQUEST);
+    }
+
+    admin_model::set_deactivated(state, admin.id, user_id, true, payload.reason).await
+}
+
+pub async fn reactivate_user_service(
+    State(state): State<AppState>,
+    admin: AuthUser,
+    Path(user_id): Path<String>,
+) -> Response {
+    let user_id = match ObjectId::parse_str(&user_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid user ID format", StatusCode::BAD_REQUEST),
+    };
+
+    admin_model::set_deactivated(state, admin.id, user_id, false, None).await
+}
+
+pub async fn revoke_user_sessions_service(
+    State(state): State<AppState>,
+    admin: AuthUser,
+    Path(user_id): Path<String>,
+) -> Response {
+    let user_id = match ObjectId::parse_str(&user_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid user ID format", StatusCode::BAD_REQUEST),
+    };
+
+    admin_model::revoke_user_sessions(state, admin.id, user_id).await
+}
+
+pub async fn list_audit_service(
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    admin_model::list_audit_entries(state, query).await
+}
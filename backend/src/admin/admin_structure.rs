@@ -0,0 +1,138 @@
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::user::user_structure::{Role, RoleStatus, User};
+
+/// User as shown to admins, without credentials
+#[derive(Debug, Serialize)]
+pub struct UserView {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    pub roles: Vec<Role>,
+    pub requested_role: Option<Role>,
+    pub role_status: Option<RoleStatus>,
+    pub role_reason: Option<String>,
+    pub phone: Option<String>,
+    pub verified: bool,
+    pub totp_enabled: bool,
+    pub deactivated: bool,
+}
+
+impl From<User> for UserView {
+    fn from(user: User) -> Self {
+        UserView {
+            roles: user.granted_roles(),
+            id: user.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: user.name,
+            email: user.email,
+            requested_role: user.requested_role,
+            role_status: user.role_status,
+            role_reason: user.role_reason,
+            phone: user.phone,
+            verified: user.verified.unwrap_or(true),
+            totp_enabled: user.totp_enabled,
+            deactivated: user.deactivated,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewDecision {
+    Approve,
+    Reject,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewRequest {
+    pub decision: ReviewDecision,
+    #[serde(default)]
+    pub reason: Option<String>, // Required when rejecting
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeRoleRequest {
+    pub roles: Vec<Role>, // Replaces every role the user currently holds
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Filters for `GET /admin/users`; pages start at 1
+#[derive(Debug, Deserialize)]
+pub struct UserListQuery {
+    pub email: Option<String>, // Case-insensitive substring
+    pub role: Option<Role>,
+    pub role_status: Option<RoleStatus>,
+    pub deactivated: Option<bool>,
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserPage {
+    pub users: Vec<UserView>,
+    pub page: u64,
+    pub per_page: i64,
+    pub total: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeactivateRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    ReviewApplicant,
+    ChangeRoles,
+    UnlockAccount,
+    DeactivateUser,
+    ReactivateUser,
+    RevokeSessions,
+}
+
+/// One admin action, stored in the `admin_audit` collection
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub admin_id: ObjectId,
+    pub action: AdminAction,
+    pub target_id: Option<ObjectId>,
+    pub detail: Option<String>,
+    pub at: DateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEntryView {
+    pub id: String,
+    pub admin_id: String,
+    pub action: AdminAction,
+    pub target_id: Option<String>,
+    pub detail: Option<String>,
+    pub at: String,
+}
+
+impl From<AuditEntry> for AuditEntryView {
+    fn from(entry: AuditEntry) -> Self {
+        AuditEntryView {
+            id: entry.id.map(|id| id.to_hex()).unwrap_or_default(),
+            admin_id: entry.admin_id.to_hex(),
+            action: entry.action,
+            target_id: entry.target_id.map(|id| id.to_hex()),
+            detail: entry.detail,
+            at: entry.at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub admin_id: Option<String>,
+    pub target_id: Option<String>,
+    pub action: Option<AdminAction>,
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use axum::{
+    middleware::{from_fn, from_fn_with_state},
+    routing::{delete, get, patch, post},
+    Router,
+};
+use admin_service::{
+    change_roles_service, deactivate_user_service, get_user_service, list_applicants_service,
+    list_audit_service, list_auth_events_service, list_users_service, reactivate_user_service,
+    review_applicant_service, revoke_user_sessions_service, unlock_user_service,
+};
+
+use crate::{
+    middleware::{
+        auth::auth_middleware,
+        permission::{require_permission, Permission},
+    },
+    utils::db::AppState,
+};
+
+pub mod admin_model;
+pub mod admin_service;
+pub mod admin_structure;
+
+pub fn admin_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/applicants", get(list_applicants_service))
+        .route("/applicants/{user_id}", patch(review_applicant_service))
+        .route("/users", get(list_users_service))
+        .route("/users/{user_id}", get(get_user_service))
+        .route("/users/{user_id}/roles", patch(change_roles_service))
+        .route("/users/{user_id}/unlock", post(unlock_user_service))
+        .route("/users/{user_id}/deactivate", post(deactivate_user_service))
+        .route("/users/{user_id}/reactivate", post(reactivate_user_service))
+        .route("/users/{user_id}/sessions", delete(revoke_user_sessions_service))
+        .route("/auth_events", get(list_auth_events_service))
+        .route("/audit", get(list_audit_service))
+        .layer(from_fn_with_state(Permission::ManageUsers, require_permission))
+        .layer(from_fn(auth_middleware))
+        .with_state((*state).clone())
+}
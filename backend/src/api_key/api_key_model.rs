@@ -0,0 +1,148 @@
+use axum::{http::StatusCode, response::Response};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Collection, Database,
+};
+
+use super::api_key_structure::{ApiKey, ApiKeyView, CreateApiKeyRequest, API_KEY_PREFIX};
+use crate::{
+    middleware::permission::AuthUser,
+    organization::{organization_model::writable_org_ids, organization_structure::Organization},
+    utils::{
+        crypto::{generate_token, hash_token},
+        db::AppState,
+        response::{error_response, success_response},
+    },
+};
+
+/// Looks up an active key from the raw value presented by a client
+pub async fn find_active_key(db: &Database, raw_key: &str) -> mongodb::error::Result<Option<ApiKey>> {
+    // Keys look like `dm_<prefix>_<secret>`
+    let Some(prefix) = raw_key
+        .strip_prefix(API_KEY_PREFIX)
+        .and_then(|rest| rest.split('_').next())
+    else {
+        return Ok(None);
+    };
+
+    let collection: Collection<ApiKey> = db.collection("api_keys");
+    let now = DateTime::now();
+
+    let key = collection
+        .find_one(doc! {
+            "prefix": prefix,
+            "key_hash": hash_token(raw_key),
+            "revoked": false,
+            "$or": [
+                { "expires_at": null },
+                { "expires_at": { "$gt": now } }
+            ]
+        })
+        .await?;
+
+    if let Some(key) = &key {
+        collection
+            .update_one(doc! { "_id": key.id }, doc! { "$set": { "last_used_at": now } })
+            .await?;
+    }
+
+    Ok(key)
+}
+
+pub async fn create_api_key(state: AppState, owner: AuthUser, org_id: ObjectId, request: CreateApiKeyRequest) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let collection: Collection<ApiKey> = database.collection("api_keys");
+
+    let org = match database.collection::<Organization>("organizations").find_one(doc! { "_id": org_id }).await {
+        Ok(Some(org)) => org,
+        Ok(None) => return error_response("Organization not found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match writable_org_ids(&database, &owner).await {
+        Ok(Some(ids)) if !ids.contains(&org_id) => {
+            return error_response("You are not a member of that organization", StatusCode::FORBIDDEN);
+        }
+        Ok(_) => {}
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let secret = generate_token();
+    let prefix = secret[..8].to_string();
+    let raw_key = format!("{}{}_{}", API_KEY_PREFIX, prefix, &secret[8..]);
+    let now = DateTime::now();
+    let expires_at = match request.expires_in_days.map(|days| {
+        days.checked_mul(86_400_000)
+            .and_then(|lifetime| now.timestamp_millis().checked_add(lifetime))
+    }) {
+        Some(None) => return error_response("Expiry is too far in the future", StatusCode::BAD_REQUEST),
+        Some(Some(expires_at)) => Some(DateTime::from_millis(expires_at)),
+        None => None,
+    };
+
+    let mut key = ApiKey {
+        id: None,
+        name: request.name,
+        organization: org.name,
+        org_id: Some(org_id),
+        owner_id: owner.id,
+        prefix,
+        key_hash: hash_token(&raw_key),
+        scopes: request.scopes,
+        created_at: now,
+        expires_at,
+        last_used_at: None,
+        revoked: false,
+    };
+
+    match collection.insert_one(&key).await {
+        Ok(result) => {
+            key.id = result.inserted_id.as_object_id();
+            // The raw key is only ever shown here
+            success_response(
+                "API key created successfully. Store the key now, it cannot be shown again",
+                serde_json::json!({ "key": raw_key, "api_key": ApiKeyView::from(key) }),
+                StatusCode::CREATED,
+            )
+        }
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn list_api_keys(state: AppState, owner_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<ApiKey> = db.database("disaster").collection("api_keys");
+
+    match collection.find(doc! { "owner_id": owner_id }).sort(doc! { "created_at": -1 }).await {
+        Ok(cursor) => match cursor.try_collect::<Vec<ApiKey>>().await {
+            Ok(keys) => {
+                let keys: Vec<ApiKeyView> = keys.into_iter().map(ApiKeyView::from).collect();
+                success_response("API keys retrieved successfully", keys, StatusCode::OK)
+            }
+            Err(e) => error_response(&format!("Failed to collect API keys: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        },
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Revokes a key; `owner_id` is `None` when an admin revokes someone else's key
+pub async fn revoke_api_key(state: AppState, key_id: ObjectId, owner_id: Option<ObjectId>) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<ApiKey> = db.database("disaster").collection("api_keys");
+
+    let mut filter = doc! { "_id": key_id };
+    if let Some(owner_id) = owner_id {
+        filter.insert("owner_id", owner_id);
+    }
+
+    match collection
+        .update_one(filter, doc! { "$set": { "revoked": true, "revoked_at": DateTime::now() } })
+        .await
+    {
+        Ok(result) if result.matched_count == 0 => error_response("API key not found", StatusCode::NOT_FOUND),
+        Ok(_) => success_response("API key revoked successfully", key_id.to_hex(), StatusCode::OK),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
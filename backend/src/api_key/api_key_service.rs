@@ -0,0 +1,68 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+
+use super::{
+    api_key_model,
+    api_key_structure::{CreateApiKeyRequest, MAX_KEY_LIFETIME_DAYS},
+};
+use crate::{
+    middleware::permission::{AuthUser, Permission},
+    utils::{db::AppState, response::error_response},
+};
+
+pub async fn create_api_key_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Response {
+    if payload.name.trim().is_empty() {
+        return error_response("Name must not be empty", StatusCode::BAD_REQUEST);
+    }
+
+    let org_id = match ObjectId::parse_str(&payload.org_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid organization ID format", StatusCode::BAD_REQUEST),
+    };
+
+    if payload.scopes.is_empty() {
+        return error_response("At least one scope is required", StatusCode::BAD_REQUEST);
+    }
+
+    if payload.expires_in_days.is_some_and(|days| !(1..=MAX_KEY_LIFETIME_DAYS).contains(&days)) {
+        return error_response(
+            &format!("Expiry must be between 1 and {} days", MAX_KEY_LIFETIME_DAYS),
+            StatusCode::BAD_REQUEST,
+        );
+    }
+
+    api_key_model::create_api_key(state, user, org_id, payload).await
+}
+
+pub async fn list_api_keys_service(State(state): State<AppState>, user: AuthUser) -> Response {
+    api_key_model::list_api_keys(state, user.id).await
+}
+
+pub async fn revoke_api_key_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(key_id): Path<String>,
+) -> Response {
+    let key_id = match ObjectId::parse_str(&key_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid API key ID format", StatusCode::BAD_REQUEST),
+    };
+
+    // Admins may revoke any key, everyone else only their own
+    let owner_id = if user.has_permission(Permission::ManageUsers) {
+        None
+    } else {
+        Some(user.id)
+    };
+
+    api_key_model::revoke_api_key(state, key_id, owner_id).await
+}
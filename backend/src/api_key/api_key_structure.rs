@@ -0,0 +1,107 @@
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of every key, so leaked keys are easy to recognise and scanners can flag them
+pub const API_KEY_PREFIX: &str = "dm_";
+/// Longest lifetime a key can be created with, about ten years
+pub const MAX_KEY_LIFETIME_DAYS: i64 = 3650;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ApiScope {
+    #[serde(rename = "shelters:read")]
+    SheltersRead,
+    #[serde(rename = "shelters:write")]
+    SheltersWrite,
+    #[serde(rename = "resources:read")]
+    ResourcesRead,
+    #[serde(rename = "resources:write")]
+    ResourcesWrite,
+}
+
+impl ApiScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::SheltersRead => "shelters:read",
+            ApiScope::SheltersWrite => "shelters:write",
+            ApiScope::ResourcesRead => "resources:read",
+            ApiScope::ResourcesWrite => "resources:write",
+        }
+    }
+}
+
+/// Machine credential for partner integrations, stored in the `api_keys` collection.
+/// Requests made with a key act as its owner, limited to the key's scopes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKey {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    pub organization: String, // Organization name, kept for display
+    #[serde(default)]
+    pub org_id: Option<ObjectId>, // Organization the key writes for; older keys have none
+    pub owner_id: ObjectId, // User who created the key
+    pub prefix: String,     // Public part of the key, used to look it up
+    pub key_hash: String,   // SHA-256 of the full key
+    pub scopes: Vec<ApiScope>,
+    pub created_at: DateTime,
+    #[serde(default)]
+    pub expires_at: Option<DateTime>,
+    #[serde(default)]
+    pub last_used_at: Option<DateTime>,
+    pub revoked: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyView {
+    pub id: String,
+    pub name: String,
+    pub organization: String,
+    pub org_id: Option<String>,
+    pub prefix: String,
+    pub scopes: Vec<ApiScope>,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub revoked: bool,
+}
+
+impl From<ApiKey> for ApiKeyView {
+    fn from(key: ApiKey) -> Self {
+        ApiKeyView {
+            id: key.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: key.name,
+            organization: key.organization,
+            org_id: key.org_id.map(|id| id.to_hex()),
+            prefix: key.prefix,
+            scopes: key.scopes,
+            created_at: key.created_at.try_to_rfc3339_string().unwrap_or_default(),
+            expires_at: key.expires_at.and_then(|at| at.try_to_rfc3339_string().ok()),
+            last_used_at: key.last_used_at.and_then(|at| at.try_to_rfc3339_string().ok()),
+            revoked: key.revoked,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub org_id: String, // Organization the key acts for; the creator must be a member
+    pub scopes: Vec<ApiScope>,
+    #[serde(default)]
+    pub expires_in_days: Option<i64>, // No expiry when omitted
+}
+
+/// Scopes an API key needs on a router. Added as an `Extension` layer outside
+/// `auth_middleware`; routers without it do not accept API keys at all.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKeyRoute {
+    pub read: ApiScope,
+    pub write: ApiScope,
+}
+
+/// Scopes of the key used for the current request, inserted by `auth_middleware`
+#[derive(Debug, Clone)]
+pub struct ApiKeyAccess {
+    pub scopes: Vec<ApiScope>,
+    pub org_id: Option<ObjectId>,
+}
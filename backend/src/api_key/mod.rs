@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use axum::{
+    middleware::{from_fn, from_fn_with_state},
+    routing::{delete, get},
+    Router,
+};
+use api_key_service::{create_api_key_service, list_api_keys_service, revoke_api_key_service};
+
+use crate::{
+    middleware::{
+        auth::auth_middleware,
+        permission::{require_permission, Permission},
+    },
+    utils::db::AppState,
+};
+
+pub mod api_key_model;
+pub mod api_key_service;
+pub mod api_key_structure;
+
+pub fn api_key_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_api_keys_service).post(create_api_key_service))
+        .route("/{key_id}", delete(revoke_api_key_service))
+        .layer(from_fn_with_state(Permission::ManageApiKeys, require_permission))
+        .layer(from_fn(auth_middleware))
+        .with_state((*state).clone())
+}
@@ -0,0 +1,215 @@
+//! Phone-number login: a one-time code is sent through the configured `SmsGateway` and, once
+//! verified, exchanged for a `citizen` scoped access token bound to a session. There is no
+//! refresh token; citizens simply verify a new code when the token expires.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{http::StatusCode, Json};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::ReturnDocument,
+    Collection,
+};
+use serde_json::{json, Value};
+
+use super::citizen_structure::{Citizen, CitizenView, PhoneOtp, CITIZEN_SCOPE};
+use crate::{
+    session::{session_model, session_structure::ClientInfo},
+    utils::{
+        crypto::{constant_time_eq, hash_token},
+        db::AppState,
+        sms::SmsMessage,
+    },
+};
+
+const OTP_TTL_MINUTES: i64 = 5;
+const OTP_MAX_ATTEMPTS: u32 = 5;
+/// Codes a single number may request per hour, to bound SMS cost and abuse
+const OTP_REQUESTS_PER_HOUR: u64 = 5;
+/// Codes a single address may request per hour across all numbers, so one client cannot
+/// pump SMS to many numbers; higher since many users may share a NAT
+const OTP_REQUESTS_PER_IP_PER_HOUR: u64 = 20;
+const CITIZEN_TOKEN_TTL_HOURS: i64 = 24;
+
+fn db_error<E>(_: E) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+}
+
+/// Canonical form used as the lookup key: spaces and dashes removed, leading `+` kept
+pub fn normalize_phone(phone: &str) -> String {
+    phone.trim().chars().filter(|c| *c != ' ' && *c != '-').collect()
+}
+
+fn generate_code() -> String {
+    format!("{:06}", OsRng.next_u32() % 1_000_000)
+}
+
+pub async fn request_otp(state: AppState, phone: &str, client: ClientInfo) -> Result<Json<Value>, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let otps: Collection<PhoneOtp> = db.database("disaster").collection("phone_otps");
+    let now = DateTime::now();
+    let hour_ago = DateTime::from_millis(now.timestamp_millis() - 3_600_000);
+
+    let from_address = otps
+        .count_documents(doc! { "ip": &client.ip, "created_at": { "$gt": hour_ago } })
+        .await
+        .map_err(db_error)?;
+    if from_address >= OTP_REQUESTS_PER_IP_PER_HOUR {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many codes requested from this address, try again later".to_string(),
+        ));
+    }
+
+    let recent = otps
+        .count_documents(doc! { "phone": phone, "created_at": { "$gt": hour_ago } })
+        .await
+        .map_err(db_error)?;
+    if recent >= OTP_REQUESTS_PER_HOUR {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many codes requested for this number, try again later".to_string(),
+        ));
+    }
+
+    // Only the newest code is valid
+    otps.update_many(doc! { "phone": phone, "used": false }, doc! { "$set": { "used": true } })
+        .await
+        .map_err(db_error)?;
+
+    let code = generate_code();
+    let otp = PhoneOtp {
+        id: None,
+        phone: phone.to_string(),
+        code_hash: hash_token(&code),
+        ip: Some(client.ip),
+        created_at: now,
+        expires_at: DateTime::from_millis(now.timestamp_millis() + OTP_TTL_MINUTES * 60_000),
+        attempts: 0,
+        used: false,
+    };
+    otps.insert_one(otp).await.map_err(db_error)?;
+    drop(db);
+
+    let body = format!(
+        "Your Disaster Management verification code is {}. It expires in {} minutes.",
+        code, OTP_TTL_MINUTES
+    );
+    state
+        .sms
+        .send(SmsMessage::new(phone, body))
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e))?;
+
+    Ok(Json(json!({
+        "message": "Verification code sent",
+        "expires_in": OTP_TTL_MINUTES * 60
+    })))
+}
+
+pub async fn verify_otp(
+    state: AppState,
+    phone: &str,
+    code: &str,
+    client: ClientInfo,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let otps: Collection<PhoneOtp> = database.collection("phone_otps");
+    let citizens: Collection<Citizen> = database.collection("citizens");
+    let invalid = || (StatusCode::UNAUTHORIZED, "Invalid or expired code".to_string());
+
+    // Count the attempt in the same operation that reads the code, so guesses cannot race the limit
+    let otp = otps
+        .find_one_and_update(
+            doc! {
+                "phone": phone,
+                "used": false,
+                "expires_at": { "$gt": DateTime::now() },
+                "attempts": { "$lt": OTP_MAX_ATTEMPTS }
+            },
+            doc! { "$inc": { "attempts": 1 } },
+        )
+        .sort(doc! { "created_at": -1 })
+        .await
+        .map_err(db_error)?
+        .ok_or_else(invalid)?;
+
+    if !constant_time_eq(hash_token(code.trim()).as_bytes(), otp.code_hash.as_bytes()) {
+        return Err(invalid());
+    }
+
+    let consumed = otps
+        .update_one(doc! { "_id": otp.id, "used": false }, doc! { "$set": { "used": true } })
+        .await
+        .map_err(db_error)?;
+    if consumed.modified_count == 0 {
+        return Err(invalid());
+    }
+
+    let now = DateTime::now();
+    let citizen = citizens
+        .find_one_and_update(
+            doc! { "phone": phone },
+            doc! {
+                "$set": { "last_login_at": now },
+                "$setOnInsert": { "phone": phone, "created_at": now, "deactivated": false }
+            },
+        )
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| db_error(()))?;
+
+    if citizen.deactivated {
+        return Err((StatusCode::FORBIDDEN, "This number has been blocked".to_string()));
+    }
+    let citizen_id = citizen.id.ok_or_else(|| db_error(()))?;
+
+    let session_id = session_model::create_session(&database, citizen_id, client)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to create session".to_string(),
+            )
+        })?;
+
+    let token = state
+        .jwt_keys
+        .sign(phone, &session_id, CITIZEN_TOKEN_TTL_HOURS * 3600, Some(CITIZEN_SCOPE))
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to generate token".to_string(),
+            )
+        })?;
+
+    Ok(Json(json!({
+        "message": "Login successful",
+        "token": token,
+        "scope": CITIZEN_SCOPE,
+        "expires_in": CITIZEN_TOKEN_TTL_HOURS * 3600
+    })))
+}
+
+pub async fn get_citizen(state: AppState, citizen_id: ObjectId) -> Result<Json<CitizenView>, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let citizens: Collection<Citizen> = db.database("disaster").collection("citizens");
+
+    citizens
+        .find_one(doc! { "_id": citizen_id })
+        .await
+        .map_err(db_error)?
+        .map(|citizen| Json(CitizenView::from(citizen)))
+        .ok_or((StatusCode::NOT_FOUND, "Citizen not found".to_string()))
+}
+
+pub async fn logout(state: AppState, session_id: ObjectId) -> Result<Json<Value>, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    session_model::revoke_sessions(&db.database("disaster"), doc! { "_id": session_id })
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(json!({ "message": "Logged out" })))
+}
@@ -0,0 +1,63 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+
+use super::{
+    citizen_model,
+    citizen_structure::{CurrentCitizen, RequestOtpRequest, VerifyOtpRequest},
+};
+use crate::{
+    session::{session_service::client_info, session_structure::CurrentSession},
+    user::user_service::is_valid_phone,
+    utils::db::AppState,
+};
+
+fn validated_phone(phone: &str) -> Result<String, (StatusCode, String)> {
+    if !is_valid_phone(phone) {
+        return Err((StatusCode::BAD_REQUEST, "Invalid phone number".to_string()));
+    }
+    Ok(citizen_model::normalize_phone(phone))
+}
+
+pub async fn request_otp_service(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestOtpRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let phone = validated_phone(&payload.phone)?;
+    citizen_model::request_otp(state, &phone, client_info(&headers, addr)).await
+}
+
+pub async fn verify_otp_service(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<VerifyOtpRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let phone = validated_phone(&payload.phone)?;
+    if payload.code.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Code is required".to_string()));
+    }
+    citizen_model::verify_otp(state, &phone, &payload.code, client_info(&headers, addr)).await
+}
+
+pub async fn me_service(
+    State(state): State<AppState>,
+    citizen: CurrentCitizen,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    citizen_model::get_citizen(state, citizen.id).await
+}
+
+pub async fn logout_service(
+    State(state): State<AppState>,
+    _citizen: CurrentCitizen,
+    Extension(session): Extension<CurrentSession>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    citizen_model::logout(state, session.id).await
+}
@@ -0,0 +1,87 @@
+use axum::{extract::FromRequestParts, http::{request::Parts, StatusCode}, response::Response};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::response::error_response;
+
+/// `scope` claim of access tokens issued through phone login
+pub const CITIZEN_SCOPE: &str = "citizen";
+
+/// Lightweight identity for affected people reporting from the field, keyed by a verified phone
+/// number and stored in the `citizens` collection, apart from full user accounts
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Citizen {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub phone: String, // Normalized: digits with an optional leading `+`
+    pub created_at: DateTime,
+    pub last_login_at: DateTime,
+    #[serde(default)]
+    pub deactivated: bool,
+}
+
+/// One-time code sent by SMS, stored hashed in the `phone_otps` collection
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PhoneOtp {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub phone: String,
+    pub code_hash: String,
+    #[serde(default)]
+    pub ip: Option<String>, // Address that requested the code, for the per-address limit
+    pub created_at: DateTime,
+    pub expires_at: DateTime,
+    pub attempts: u32, // Wrong guesses so far; the code is burned once the limit is reached
+    pub used: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequestOtpRequest {
+    pub phone: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyOtpRequest {
+    pub phone: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CitizenView {
+    pub id: String,
+    pub phone: String,
+    pub created_at: String,
+}
+
+impl From<Citizen> for CitizenView {
+    fn from(citizen: Citizen) -> Self {
+        CitizenView {
+            id: citizen.id.map(|id| id.to_hex()).unwrap_or_default(),
+            phone: citizen.phone,
+            created_at: citizen.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+/// Marks a router as accepting citizen tokens. Added as an `Extension` layer outside
+/// `auth_middleware`; routers without it refuse them, so citizens cannot reach account routes.
+#[derive(Debug, Clone, Copy)]
+pub struct CitizenRoute;
+
+/// Citizen making the current request, inserted by `auth_middleware` next to the `AuthUser`
+#[derive(Debug, Clone)]
+pub struct CurrentCitizen {
+    pub id: ObjectId,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for CurrentCitizen {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<CurrentCitizen>()
+            .cloned()
+            .ok_or_else(|| error_response("Forbidden: a citizen token is required", StatusCode::FORBIDDEN))
+    }
+}
@@ -0,0 +1,22 @@
+use std::sync::Arc;
+
+use axum::{middleware::from_fn, routing::{get, post}, Extension, Router};
+use citizen_structure::CitizenRoute;
+
+use crate::{middleware::auth::auth_middleware, utils::db::AppState};
+
+pub mod citizen_model;
+pub mod citizen_service;
+pub mod citizen_structure;
+
+/// Phone OTP login for citizens. `/me` and `/logout` only serve citizen tokens.
+pub fn citizen_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/me", get(citizen_service::me_service))
+        .route("/logout", post(citizen_service::logout_service))
+        .layer(from_fn(auth_middleware))
+        .layer(Extension(CitizenRoute))
+        .route("/request_otp", post(citizen_service::request_otp_service))
+        .route("/verify_otp", post(citizen_service::verify_otp_service))
+        .with_state((*state).clone())
+}
@@ -1,9 +1,9 @@
-use axum::{extract::{Path, State}, http::StatusCode, response::IntoResponse, Json};
-use futures::{StreamExt, TryStreamExt};
+use axum::{extract::{Path, Query, State}, http::{header::CONTENT_LANGUAGE, HeaderValue, StatusCode}, response::IntoResponse, Json};
+use futures::TryStreamExt;
 use serde_json::json;
-use crate::{disaster::disaster_structure::{DisasterGuide, DisasterRecord, GuideItem}, utils::{db::AppState, response::{error_response, success_response}}};
+use crate::{admin::admin_model::escape_regex, disaster::disaster_structure::{DisasterGuide, DisasterListQuery, DisasterPage, DisasterRecord, DisasterSort, EditGuideItemRequest, GuideItem, GuideItemPage, GuideItemView, GuideKind, GuideReport, GuideStatus, GuideVote, ModerationRequest, PendingQuery, ReportRequest, UpdateDisasterRequest, VoteValue}, middleware::permission::{AuthUser, Permission}, revision::{revision_model, revision_structure::{RevisionAction, RevisionChange, RevisionEntity, RevisionTarget}}, hazard::hazard_model, translation::translation_model, utils::{db::AppState, locale::default_locale, paging, response::{error_response, success_response}}};
 use mongodb::{
-    bson::{doc, from_document, oid::ObjectId, to_bson, Bson}, options::{ClientOptions, FindOptions, IndexOptions}, Client, Collection, IndexModel
+    bson::{doc, from_document, oid::ObjectId, to_bson, to_document, Bson, DateTime, Document, Regex}, Collection, Database
 };
 
 
@@ -43,6 +43,7 @@ use mongodb::{
 
 pub async fn add_disaster(
     State(state): State<AppState>,
+    user: AuthUser,
     Json(req_record): Json<DisasterRecord>,
 ) -> impl IntoResponse {
     // Debug: log incoming request record
@@ -51,6 +52,14 @@ pub async fn add_disaster(
     let db = state.db.lock().await;
     let dr_collection: Collection<DisasterRecord> = db.database("disaster").collection("disaster_record");
 
+    let hazard = match req_record.hazard.as_deref() {
+        Some(hazard) => match known_hazard(&db.database("disaster"), hazard).await {
+            Ok(hazard) => Some(hazard),
+            Err((status, message)) => return error_response(&message, status),
+        },
+        None => None,
+    };
+
     // Create a new disaster record
     let new_disaster_record = DisasterRecord {
         id: None,  // Let MongoDB generate `_id`
@@ -58,12 +67,15 @@ pub async fn add_disaster(
         effects: req_record.effects,
         short_description: req_record.short_description,
         youtube_link: req_record.youtube_link,
+        revision: 1,
+        hazard,
+        severity: req_record.severity,
     };
 
     // Insert the new disaster record into the collection
     let dr_bson_id = match dr_collection.insert_one(&new_disaster_record).await {
         Ok(insert_result) => insert_result.inserted_id,
-        Err(e) => return error_response("Failed to insert record", StatusCode::INTERNAL_SERVER_ERROR),
+        Err(_) => return error_response("Failed to insert record", StatusCode::INTERNAL_SERVER_ERROR),
     };
 
     // Extract the ObjectId from the inserted record
@@ -82,72 +94,115 @@ pub async fn add_disaster(
     };
 
     // Insert the disaster guide entry
-    let dg_bson_id = match dg_collection.insert_one(&dg_entry).await {
-        Ok(insert_result) => insert_result.inserted_id,
-        Err(e) => return error_response("Failed to insert guide record", StatusCode::INTERNAL_SERVER_ERROR)
-    };
+    if dg_collection.insert_one(&dg_entry).await.is_err() {
+        return error_response("Failed to insert guide record", StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let target = RevisionTarget { entity: RevisionEntity::DisasterRecord, entity_id: dr_id, disaster_id: dr_id, kind: None };
+    if let Err((status, message)) = record_creation(&db.database("disaster"), &target, user.id, &new_disaster_record).await {
+        return error_response(&message, status);
+    }
 
     // Respond with success message
     success_response("New disaster record created successfully.", dr_id.to_string() , StatusCode::OK)
 }
 
-pub async fn add_dos(State(state): State<AppState>,
-Path(dr_id): Path<ObjectId>,  // Extract `dr_id` from the URL
-Json(req_message): Json<serde_json::Value>) -> impl IntoResponse{
+/// Normalized code of an existing taxonomy node
+async fn known_hazard(database: &Database, hazard: &str) -> Result<String, (StatusCode, String)> {
+    let hazard = hazard.trim().to_lowercase();
+    match hazard_model::hazard_exists(database, &hazard).await {
+        Ok(true) => Ok(hazard),
+        Ok(false) => Err((StatusCode::BAD_REQUEST, format!("Unknown hazard '{}'", hazard))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to fetch hazard taxonomy: {}", e))),
+    }
+}
 
+/// Records version 1 of a newly inserted record or item
+async fn record_creation<T: serde::Serialize>(
+    database: &Database,
+    target: &RevisionTarget,
+    author_id: ObjectId,
+    created: &T,
+) -> Result<(), (StatusCode, String)> {
+    let after = to_document(created)
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to serialize revision".to_string()))?;
+    let change = RevisionChange { action: RevisionAction::Create, author_id, before: None, after: &after, reverted_to: None };
+
+    revision_model::record_revision(database, target, 1, change)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to record revision".to_string()))
+}
+
+/// Appends a submission to the guide of `dr_id`. Every submission starts as `Pending`
+/// and only shows up in public reads once a moderator accepts it.
+async fn add_guide_item(
+    state: AppState,
+    dr_id: ObjectId,
+    kind: GuideKind,
+    user_id: ObjectId,
+    message: &str,
+) -> Result<ObjectId, (StatusCode, String)> {
     let db = state.db.lock().await;
     let dg_collection: Collection<DisasterGuide> = db.database("disaster").collection("disaster_guide");
 
-    //println!("Updating disaster guide for ID: {:?} with data: {:?}", dr_id, req_message);
-
-    let do_message = req_message.get("message").and_then(|m| m.as_str()).unwrap(); // We assume it's always present.
-
-    let id_str: &str = "67b17ff47acc96908fe325d8";  // Placeholder user_id for demonstration
-    let _do = GuideItem {
+    let item = GuideItem {
         id: Some(ObjectId::new()),
-        user_id: match ObjectId::parse_str(id_str) {
-            Ok(object_id) => object_id,
-            Err(e) => {
-                return error_response("Failed to parse User ID", StatusCode::INTERNAL_SERVER_ERROR)
-            }
-        },
-        status: String::from("Accepted"),
-        message: String::from(do_message),
+        user_id,
+        status: GuideStatus::Pending.as_str().to_string(),
+        message: message.to_string(),
+        submitted_at: Some(DateTime::now()),
+        reason: None,
+        moderated_by: None,
+        moderated_at: None,
+        revision: 1,
+        published: None,
+        upvotes: 0,
+        downvotes: 0,
+        helpfulness: 0.0,
+        flags: 0,
     };
 
-    let do_bson = match to_bson(&_do) {
-        Ok(bson) => bson,
-        Err(e) => {
-            return error_response("Failed to serialize", StatusCode::INTERNAL_SERVER_ERROR)
+    let item_bson = to_bson(&item).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to serialize GuideItem: {}", e))
+    })?;
+
+    match dg_collection
+        .update_one(doc! { "disaster_id": dr_id }, doc! { "$push": { kind.field(): item_bson } })
+        .await
+    {
+        Ok(result) if result.matched_count == 0 => {
+            Err((StatusCode::NOT_FOUND, "No record found with the given ID".to_string()))
         }
-    };
+        Ok(_) => {
+            let item_id = item.id.unwrap_or_default();
+            let target = RevisionTarget { entity: RevisionEntity::GuideItem, entity_id: item_id, disaster_id: dr_id, kind: Some(kind) };
+            record_creation(&db.database("disaster"), &target, user_id, &item).await?;
+            Ok(item_id)
+        }
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to update record: {}", e))),
+    }
+}
 
-    let update_result = dg_collection
-        .update_one(
-            doc! { "disaster_id": dr_id },
-            doc! { "$push": { "do_s": do_bson } }
-        )
-        .await;
+pub async fn add_dos(State(state): State<AppState>,
+Path(dr_id): Path<ObjectId>,  // Extract `dr_id` from the URL
+user: AuthUser,
+Json(req_message): Json<serde_json::Value>) -> impl IntoResponse{
 
-        match update_result {
-            Ok(update_result) => {
-                if update_result.matched_count == 0 {
-                    return error_response("No record found", StatusCode::NOT_FOUND);
-                }
-                success_response(
-                    "Successfully added to do_s array",
-                    json!({
-                        "disaster_id": dr_id,
-                        "new_do": do_message
-                    }),
-                    StatusCode::OK
-                )
-            }
-            Err(_) => {
-                return error_response("Failed to update record", StatusCode::INTERNAL_SERVER_ERROR);
-            }
-        }
-   
+    let do_message = req_message.get("message").and_then(|m| m.as_str()).unwrap_or_default(); // Validated by add_dos_service
+
+    match add_guide_item(state, dr_id, GuideKind::Do, user.id, do_message).await {
+        Ok(item_id) => success_response(
+            "Successfully submitted to do_s array for review",
+            json!({
+                "disaster_id": dr_id,
+                "item_id": item_id,
+                "new_do": do_message,
+                "status": GuideStatus::Pending.as_str()
+            }),
+            StatusCode::OK
+        ),
+        Err((status, message)) => error_response(&message, status),
+    }
 }
 
 
@@ -155,69 +210,24 @@ Json(req_message): Json<serde_json::Value>) -> impl IntoResponse{
 pub async fn add_donts(
     State(state): State<AppState>,
     Path(dr_id): Path<ObjectId>,
+    user: AuthUser,
     Json(req_message): Json<serde_json::Value>,
 ) -> impl IntoResponse {
-    let db = state.db.lock().await;
-    let dg_collection: Collection<DisasterGuide> = db.database("disaster").collection("disaster_guide");
-
-    //println!("Updating disaster guide for ID: {:?} with data: {:?}", dr_id, req_message);
-
-    // Directly access the message field since it's already validated by add_donts_service
-    let dont_message = req_message.get("message").and_then(|m| m.as_str()).unwrap(); // We assume it's always present.
-
-    let id_str: &str = "67b17ff47acc96908fe325d8";  // Placeholder user_id for demonstration
-    let _dont = GuideItem {
-        id: Some(ObjectId::new()),
-        user_id: match ObjectId::parse_str(id_str) {
-            Ok(object_id) => object_id,
-            Err(e) => {
-                return error_response(
-                    &format!("Failed to parse user ID: {}", e),
-                    StatusCode::BAD_REQUEST,
-                );
-            }
-        },
-        status: String::from("Accepted"),
-        message: String::from(dont_message),
-    };
-
-    let dont_bson = match to_bson(&_dont) {
-        Ok(bson) => bson,
-        Err(e) => {
-            return error_response(
-                &format!("Failed to serialize GuideItem: {}", e),
-                StatusCode::INTERNAL_SERVER_ERROR,
-            );
-        }
-    };
-
-    let update_result = dg_collection
-        .update_one(
-            doc! { "disaster_id": dr_id },
-            doc! { "$push": { "dont_s": dont_bson } }
-        )
-        .await;
-
-    match update_result {
-        Ok(update_result) => {
-            if update_result.matched_count == 0 {
-                return error_response("No record found with the given ID", StatusCode::NOT_FOUND);
-            }
-            success_response(
-                "Successfully added to dont_s array",
-                json!({
-                    "disaster_id": dr_id,
-                    "new_dont": dont_message
-                }),
-                StatusCode::OK,
-            )
-        }
-        Err(e) => {
-            error_response(
-                &format!("Failed to update record: {}", e),
-                StatusCode::INTERNAL_SERVER_ERROR,
-            )
-        }
+    // The message field is already validated by add_donts_service
+    let dont_message = req_message.get("message").and_then(|m| m.as_str()).unwrap_or_default();
+
+    match add_guide_item(state, dr_id, GuideKind::Dont, user.id, dont_message).await {
+        Ok(item_id) => success_response(
+            "Successfully submitted to dont_s array for review",
+            json!({
+                "disaster_id": dr_id,
+                "item_id": item_id,
+                "new_dont": dont_message,
+                "status": GuideStatus::Pending.as_str()
+            }),
+            StatusCode::OK,
+        ),
+        Err((status, message)) => error_response(&message, status),
     }
 }
 
@@ -301,9 +311,49 @@ pub async fn add_donts(
 // }
 
 
+/// Items with a published revision, each showing its published text, most helpful first.
+/// An accepted item that was edited stays visible with its last accepted message until the edit
+/// is reviewed; items accepted before versioning have no `published` text and are served as stored.
+/// `$sortArray` needs MongoDB 5.2 or later.
+pub fn published_items(field: &str) -> Document {
+    let has_published = doc! { "$gt": ["$$item.published", Bson::Null] };
+    let published = doc! {
+        "$map": {
+            "input": {
+                "$filter": {
+                    "input": { "$ifNull": [field, []] },
+                    "as": "item",
+                    "cond": { "$or": [{ "$eq": ["$$item.status", "Accepted"] }, has_published.clone()] }
+                }
+            },
+            "as": "item",
+            "in": {
+                "$cond": [
+                    has_published,
+                    {
+                        "$mergeObjects": [
+                            "$$item",
+                            {
+                                "status": "Accepted",
+                                "message": "$$item.published.message",
+                                "revision": "$$item.published.revision"
+                            }
+                        ]
+                    },
+                    "$$item"
+                ]
+            }
+        }
+    };
+
+    // Ties keep submission order, which `_id` follows
+    doc! { "$sortArray": { "input": published, "sortBy": { "helpfulness": -1, "_id": 1 } } }
+}
+
 pub async fn get_disaster_record(
     State(state): State<AppState>,
     Path(dr_id): Path<ObjectId>,
+    locales: Vec<String>,
 ) -> impl IntoResponse {
     let db = state.db.lock().await;
     let dg_collection: Collection<DisasterGuide> = db.database("disaster").collection("disaster_guide");
@@ -319,24 +369,8 @@ pub async fn get_disaster_record(
             "_id": 1,                             // Include _id from disaster_guide
             "disaster_id": 1,                     // Include disaster_id from disaster_guide
             "disaster_record": 1,                 // Include full disaster_record data
-            "do_s": {
-                "$filter": {
-                    "input": "$do_s",          // Filter the do_s array
-                    "as": "item",               // Alias for each element in do_s
-                    "cond": {                   // Condition to filter only accepted items
-                        "$eq": ["$$item.status", "Accepted"]
-                    }
-                }
-            },
-            "dont_s": {
-                "$filter": {
-                    "input": "$dont_s",         // Filter the dont_s array
-                    "as": "item",               // Alias for each element in dont_s
-                    "cond": {                   // Condition to filter only accepted items
-                        "$eq": ["$$item.status", "Accepted"]
-                    }
-                }
-            }
+            "do_s": published_items("$do_s"),
+            "dont_s": published_items("$dont_s")
         }
     },
 
@@ -345,7 +379,7 @@ pub async fn get_disaster_record(
 ];
 
 
-    let dg_data = match dg_collection.aggregate(dg_pipeline).await {
+    let mut dg_data = match dg_collection.aggregate(dg_pipeline).await {
         Ok(mut cursor) => {
             let mut dg_data: Vec<DisasterGuide> = Vec::new();
 
@@ -370,7 +404,7 @@ pub async fn get_disaster_record(
 
     let filter = doc! { "_id": dr_id };  // Filter by ObjectId
 
-    let dr_data = match dr_collection.find(filter).await {
+    let mut dr_data = match dr_collection.find(filter).await {
         Ok(cursor) => {
             // Collect the documents matching the filter (in this case, it should be one or none)
             let disaster_records: Vec<DisasterRecord> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
@@ -382,23 +416,37 @@ pub async fn get_disaster_record(
         }
     };
 
+    // An unavailable translation must not hide the record itself, so fall back to the original
+    let served = translation_model::localize(&db.database("disaster"), dr_id, &locales, &mut dr_data, &mut dg_data)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Error fetching translations: {:?}", e);
+            vec![default_locale()]
+        });
+
     let response_data = json!({
         "disaster_record": dr_data,
-        "disaster_guide": dg_data
-        
+        "disaster_guide": dg_data,
+        "locale": served.first()
     });
 
-    success_response(
+    let mut response = success_response(
                 "Successfully fetched disaster record with accepted dos and donts",
                 response_data,
                 StatusCode::OK,
-            )
+            );
+    if let Ok(content_language) = HeaderValue::from_str(&served.join(", ")) {
+        response.headers_mut().insert(CONTENT_LANGUAGE, content_language);
+    }
+    response
 }
 
 
 
 
 
+/// One record with its full guide, pending and rejected items included (unlike `get_disaster_record`),
+/// so it is reserved for moderators. Despite the name it does not list records; that is `list_disasters`.
 pub async fn get_all_disaster_record(
     State(state): State<AppState>,
     Path(dr_id): Path<ObjectId>,
@@ -481,184 +529,53 @@ pub async fn get_all_disaster_record(
 
 
 
-pub async fn update_dos(
-    State(state): State<AppState>,
-    Path((dr_id, gi_id)): Path<(ObjectId, ObjectId)>,
-    Json(payload): Json<serde_json::Value>,
-) -> impl IntoResponse {
-    let db = state.db.lock().await;
-    let dg_collection: Collection<DisasterGuide> = db.database("disaster").collection("disaster_guide");
-
-    //println!("Updating do_s entry for disaster_id: {:?}, do_id: {:?} and body is {:?}", dr_id, gi_id, payload);
-
-    // Extract the status directly since it's already validated in the service
-    let status = payload.get("status").and_then(|s| s.as_str()).unwrap();
-
-    //println!("Updating do_s entry for disaster_id: {:?}, do_id: {:?} with status {:?}", dr_id, gi_id, status);
-
-    let update_result = match status {
-        "Accepted" => {
-            dg_collection
-                .update_one(
-                    doc! { "disaster_id": &dr_id, "do_s._id": &gi_id },
-                    doc! { "$set": { "do_s.$.status": "Accepted" } }
-                )
-                .await
-        }
-        "Rejected" => {
-            dg_collection
-                .update_one(
-                    doc! { "disaster_id": &dr_id },
-                    doc! { "$pull": { "do_s": { "_id": &gi_id } } }  // Remove matching element
-                )
-                .await
-        }
-        _ => {
-            return error_response("Invalid status provided", StatusCode::BAD_REQUEST);
-        }
+/// Records a moderation decision on one item. Returns `None` when the item does not exist.
+async fn set_item_status(
+    database: &Database,
+    target: &RevisionTarget,
+    decision: &ModerationRequest,
+    moderator_id: ObjectId,
+) -> mongodb::error::Result<Option<Document>> {
+    let reason = decision.reason.as_deref().map(str::trim).filter(|reason| !reason.is_empty());
+    let mut changes = doc! {
+        "status": decision.status.as_str(),
+        "reason": reason.map_or(Bson::Null, Bson::from),
+        "moderated_by": moderator_id,
+        "moderated_at": DateTime::now(),
     };
 
-    match update_result {
-        Ok(res) => {
-            if res.matched_count == 0 {
-                return error_response("No matching record found", StatusCode::NOT_FOUND);
-            }
-
-            if status == "Rejected" {
-                return success_response(
-                    "Item removed successfully",
-                    json!({
-                        "item_id": gi_id,
-                        "matched_count": res.matched_count
-                    }),
-                    StatusCode::OK,
-                );
-            }
-
-            // Fetch the updated item for "Accepted" case
-            let pipeline = vec![
-                doc! { "$match": { "disaster_id": &dr_id } },
-                doc! { "$unwind": "$do_s" },
-                doc! { "$match": { "do_s._id": &gi_id } },
-                doc! { "$project": { "_id": 0, "do_s": 1 } },
-            ];
-
-            let mut cursor = dg_collection.aggregate(pipeline).await.unwrap();
-            if let Some(doc) = cursor.next().await {
-                if let Ok(updated_doc) = doc {
-                    return success_response(
-                        "Status updated successfully",
-                        json!({
-                            "item_id": gi_id,
-                            "matched_count": res.matched_count,
-                            "updated_item": updated_doc.get("do_s")
-                        }),
-                        StatusCode::OK,
-                    );
-                }
-            }
-
-            success_response(
-                "Status updated successfully, but no updated item found",
-                json!({
-                    "item_id": gi_id,
-                    "matched_count": res.matched_count,
-                    "updated_item": null
-                }),
-                StatusCode::OK,
-            )
-        }
-        Err(e) => error_response(&format!("Update failed: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    // Accepting an item again settles the reports that sent it back to review
+    if decision.status == GuideStatus::Accepted {
+        changes.insert("flags", 0);
+        database
+            .collection::<GuideReport>("guide_reports")
+            .update_many(doc! { "item_id": target.entity_id, "resolved": false }, doc! { "$set": { "resolved": true } })
+            .await?;
     }
-}
-
 
+    revision_model::apply_item_change(database, target, changes, RevisionAction::Moderate, moderator_id, None).await
+}
 
-pub async fn update_donts(
+/// Accepts or rejects one item of a guide; shared by the `do` and `don't` routes
+pub async fn moderate_item(
     State(state): State<AppState>,
-    Path((dr_id, gi_id)): Path<(ObjectId, ObjectId)>,
-    Json(payload): Json<serde_json::Value>,
+    Path((dr_id, kind, gi_id)): Path<(ObjectId, GuideKind, ObjectId)>,
+    moderator: AuthUser,
+    Json(decision): Json<ModerationRequest>,
 ) -> impl IntoResponse {
     let db = state.db.lock().await;
-    let dg_collection: Collection<DisasterGuide> = db.database("disaster").collection("disaster_guide");
-
-    // Extract status directly, assuming it's already validated in the service
-    let status = payload.get("status").and_then(|s| s.as_str()).unwrap();
-
-   // println!("Updating dont_s entry for disaster_id: {:?}, dont_id: {:?} with status {:?}", dr_id, gi_id, status);
-
-    let update_result = match status {
-        "Accepted" => {
-            dg_collection
-                .update_one(
-                    doc! { "disaster_id": &dr_id, "dont_s._id": &gi_id },
-                    doc! { "$set": { "dont_s.$.status": "Accepted" } }
-                )
-                .await
-        }
-        "Rejected" => {
-            dg_collection
-                .update_one(
-                    doc! { "disaster_id": &dr_id },
-                    doc! { "$pull": { "dont_s": { "_id": &gi_id } } },  // Remove matching element
-                )
-                .await
-        }
-        _ => {
-            return error_response("Invalid status provided", StatusCode::BAD_REQUEST);
-        }
-    };
-
-    match update_result {
-        Ok(res) => {
-            if res.matched_count == 0 {
-                return error_response("No matching record found", StatusCode::NOT_FOUND);
-            }
-
-            if status == "Rejected" {
-                return success_response(
-                    "Item removed successfully",
-                    json!({
-                        "item_id": gi_id,
-                        "matched_count": res.matched_count
-                    }),
-                    StatusCode::OK,
-                );
-            }
-
-            // Fetch the updated item for "Accepted" case
-            let pipeline = vec![
-                doc! { "$match": { "disaster_id": &dr_id } },
-                doc! { "$unwind": "$dont_s" },
-                doc! { "$match": { "dont_s._id": &gi_id } },
-                doc! { "$project": { "_id": 0, "dont_s": 1 } },
-            ];
-
-            let mut cursor = dg_collection.aggregate(pipeline).await.unwrap();
-            if let Some(doc) = cursor.next().await {
-                if let Ok(updated_doc) = doc {
-                    return success_response(
-                        "Status updated successfully",
-                        json!({
-                            "item_id": gi_id,
-                            "matched_count": res.matched_count,
-                            "updated_item": updated_doc.get("dont_s")
-                        }),
-                        StatusCode::OK,
-                    );
-                }
-            }
-
-            success_response(
-                "Status updated successfully, but no updated item found",
-                json!({
-                    "item_id": gi_id,
-                    "matched_count": res.matched_count,
-                    "updated_item": null
-                }),
-                StatusCode::OK,
-            )
-        }
+    let target = RevisionTarget { entity: RevisionEntity::GuideItem, entity_id: gi_id, disaster_id: dr_id, kind: Some(kind) };
+
+    match set_item_status(&db.database("disaster"), &target, &decision, moderator.id).await {
+        Ok(Some(item)) => success_response(
+            if decision.status == GuideStatus::Rejected { "Item rejected successfully" } else { "Status updated successfully" },
+            json!({
+                "item_id": gi_id,
+                "updated_item": item
+            }),
+            StatusCode::OK,
+        ),
+        Ok(None) => error_response("No matching record found", StatusCode::NOT_FOUND),
         Err(e) => error_response(&format!("Update failed: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
@@ -782,4 +699,642 @@ pub async fn update_donts(
 //         }
 //         Err(e) => error_response(&format!("Update failed: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
 //     }
-// }
\ No newline at end of file
+// }
+
+
+pub async fn list_disasters(
+    State(state): State<AppState>,
+    Query(query): Query<DisasterListQuery>,
+) -> impl IntoResponse {
+    let db = state.db.lock().await;
+    let dr_collection: Collection<DisasterRecord> = db.database("disaster").collection("disaster_record");
+
+    let mut filter = Document::new();
+    if let Some(name) = query.name.as_deref().map(str::trim).filter(|name| !name.is_empty()) {
+        filter.insert(
+            "name",
+            Regex {
+                pattern: escape_regex(name),
+                options: "i".to_string(),
+            },
+        );
+    }
+    if let Some(hazard) = query.hazard.as_deref() {
+        match hazard_model::hazard_filter(&db.database("disaster"), hazard).await {
+            Ok(Some(codes)) => {
+                filter.insert("hazard", doc! { "$in": codes });
+            }
+            Ok(None) => return error_response("Hazard not found", StatusCode::NOT_FOUND),
+            Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+    if let Some(min_severity) = query.min_severity {
+        filter.insert("severity", doc! { "$in": min_severity.at_least() });
+    }
+
+    // ObjectIds grow with insertion time, so `_id` doubles as the creation order
+    let sort = match query.sort {
+        DisasterSort::Name => doc! { "name": 1, "_id": 1 },
+        DisasterSort::NameDesc => doc! { "name": -1, "_id": 1 },
+        DisasterSort::Newest => doc! { "_id": -1 },
+        DisasterSort::Oldest => doc! { "_id": 1 },
+    };
+
+    let (page, per_page) = paging::page_window(query.page, query.per_page);
+
+    let total = match dr_collection.count_documents(filter.clone()).await {
+        Ok(total) => total,
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match dr_collection
+        .find(filter)
+        .sort(sort)
+        .skip(paging::skip(page, per_page))
+        .limit(per_page)
+        .await
+    {
+        Ok(cursor) => match cursor.try_collect::<Vec<DisasterRecord>>().await {
+            Ok(disasters) => success_response(
+                "Disaster records retrieved successfully",
+                DisasterPage { disasters, page, per_page, total },
+                StatusCode::OK,
+            ),
+            Err(e) => error_response(&format!("Failed to collect disaster records: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        },
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+
+
+pub async fn update_disaster(
+    State(state): State<AppState>,
+    Path(dr_id): Path<ObjectId>,
+    user: AuthUser,
+    Json(update): Json<UpdateDisasterRequest>,
+) -> impl IntoResponse {
+    let mut changes = Document::new();
+    if let Some(name) = update.name {
+        changes.insert("name", name.trim());
+    }
+    if let Some(effects) = update.effects {
+        changes.insert("effects", effects);
+    }
+    if let Some(short_description) = update.short_description {
+        changes.insert("short_description", short_description);
+    }
+    if let Some(youtube_link) = update.youtube_link {
+        changes.insert("youtube_link", youtube_link);
+    }
+    if let Some(severity) = update.severity {
+        changes.insert("severity", severity.as_str());
+    }
+
+    if changes.is_empty() && update.hazard.is_none() {
+        return error_response("Nothing to update", StatusCode::BAD_REQUEST);
+    }
+
+    let db = state.db.lock().await;
+
+    if let Some(hazard) = update.hazard.as_deref() {
+        match known_hazard(&db.database("disaster"), hazard).await {
+            Ok(hazard) => {
+                changes.insert("hazard", hazard);
+            }
+            Err((status, message)) => return error_response(&message, status),
+        }
+    }
+
+    match revision_model::apply_record_change(&db.database("disaster"), dr_id, changes, RevisionAction::Update, user.id, None).await {
+        Ok(Some(record)) => match from_document::<DisasterRecord>(record) {
+            Ok(record) => success_response("Disaster record updated successfully", record, StatusCode::OK),
+            Err(e) => error_response(&format!("Failed to read updated record: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        },
+        Ok(None) => error_response("No record found with the given ID", StatusCode::NOT_FOUND),
+        Err(e) => error_response(&format!("Update failed: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+
+
+/// Deletes the record together with its guide
+pub async fn delete_disaster(
+    State(state): State<AppState>,
+    Path(dr_id): Path<ObjectId>,
+    user: AuthUser,
+) -> impl IntoResponse {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let dr_collection: Collection<Document> = database.collection("disaster_record");
+    let dg_collection: Collection<DisasterGuide> = database.collection("disaster_guide");
+
+    // Incidents rely on the record for their guidance
+    match database.collection::<Document>("incidents").count_documents(doc! { "disaster_id": dr_id }).await {
+        Ok(0) => {}
+        Ok(incidents) => {
+            return error_response(&format!("{} incidents link to this record; relink them first", incidents), StatusCode::CONFLICT)
+        }
+        Err(e) => return error_response(&format!("Delete failed: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let record = match dr_collection.find_one_and_delete(doc! { "_id": dr_id }).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return error_response("No record found with the given ID", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Delete failed: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    // The history outlives the record, ending with its final content
+    let target = RevisionTarget { entity: RevisionEntity::DisasterRecord, entity_id: dr_id, disaster_id: dr_id, kind: None };
+    let recorded = match revision_model::next_version(&database, dr_id).await {
+        Ok(version) => {
+            let change = RevisionChange { action: RevisionAction::Delete, author_id: user.id, before: Some(&record), after: &record, reverted_to: None };
+            revision_model::record_revision(&database, &target, version, change).await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = recorded {
+        return error_response(&format!("Record deleted but its revision could not be saved: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    // Votes, reports and translations of the record and its guide items go with it
+    let cascade = async {
+        let guides = dg_collection.delete_many(doc! { "disaster_id": dr_id }).await?;
+        for collection in ["guide_votes", "guide_reports", "translations"] {
+            database.collection::<Document>(collection).delete_many(doc! { "disaster_id": dr_id }).await?;
+        }
+        mongodb::error::Result::Ok(guides.deleted_count)
+    };
+
+    match cascade.await {
+        Ok(deleted_guides) => success_response(
+            "Disaster record deleted successfully",
+            json!({
+                "disaster_id": dr_id,
+                "deleted_guides": deleted_guides
+            }),
+            StatusCode::OK,
+        ),
+        Err(e) => error_response(&format!("Record deleted but its guide could not be removed: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+
+
+/// Flattens every guide into one document per item (`kind`, `item`, `disaster_id`),
+/// with the disaster name joined in, so the queue can be filtered and paged across all disasters
+fn flattened_items_pipeline(item_filter: Document, page: u64, per_page: i64, sort: Document) -> Vec<Document> {
+    let tagged = |field: &str, kind: &str| {
+        doc! {
+            "$map": {
+                "input": { "$ifNull": [format!("${}", field), []] },
+                "as": "item",
+                "in": { "kind": kind, "item": "$$item" }
+            }
+        }
+    };
+
+    vec![
+        doc! {
+            "$project": {
+                "disaster_id": 1,
+                "entries": { "$concatArrays": [tagged("do_s", "do"), tagged("dont_s", "dont")] }
+            }
+        },
+        doc! { "$unwind": "$entries" },
+        doc! {
+            "$project": {
+                "_id": 0,
+                "disaster_id": 1,
+                "kind": "$entries.kind",
+                "item": "$entries.item"
+            }
+        },
+        doc! { "$match": item_filter },
+        doc! { "$sort": sort },
+        doc! {
+            "$facet": {
+                "items": [
+                    { "$skip": i64::try_from(paging::skip(page, per_page)).unwrap_or(i64::MAX) },
+                    { "$limit": per_page },
+                    {
+                        "$lookup": {
+                            "from": "disaster_record",
+                            "localField": "disaster_id",
+                            "foreignField": "_id",
+                            "as": "disaster"
+                        }
+                    }
+                ],
+                "total": [{ "$count": "count" }]
+            }
+        },
+    ]
+}
+
+/// Reads a counter stored as either 32 or 64-bit integer; missing counts as 0
+pub fn counter(item: &Document, field: &str) -> u32 {
+    match item.get(field) {
+        Some(Bson::Int32(value)) => (*value).max(0) as u32,
+        Some(Bson::Int64(value)) => (*value).clamp(0, u32::MAX as i64) as u32,
+        _ => 0,
+    }
+}
+
+fn guide_item_view(entry: &Document) -> Option<GuideItemView> {
+    let item = entry.get_document("item").ok()?;
+    let kind = match entry.get_str("kind").ok()? {
+        "do" => GuideKind::Do,
+        _ => GuideKind::Dont,
+    };
+    let date = |field: &str| item.get_datetime(field).ok().and_then(|at| at.try_to_rfc3339_string().ok());
+
+    Some(GuideItemView {
+        disaster_id: entry.get_object_id("disaster_id").ok()?.to_hex(),
+        disaster_name: entry
+            .get_array("disaster")
+            .ok()
+            .and_then(|disaster| disaster.first()?.as_document()?.get_str("name").ok().map(str::to_string)),
+        kind,
+        item_id: item.get_object_id("_id").ok()?.to_hex(),
+        user_id: item.get_object_id("user_id").map(|id| id.to_hex()).unwrap_or_default(),
+        message: item.get_str("message").unwrap_or_default().to_string(),
+        status: item.get_str("status").unwrap_or_default().to_string(),
+        reason: item.get_str("reason").ok().map(str::to_string),
+        submitted_at: date("submitted_at"),
+        moderated_at: date("moderated_at"),
+        helpfulness: item.get_f64("helpfulness").unwrap_or(0.0),
+        flags: counter(item, "flags"),
+    })
+}
+
+/// Runs `flattened_items_pipeline` and shapes the single `$facet` result into a page
+async fn guide_item_page(
+    dg_collection: &Collection<DisasterGuide>,
+    item_filter: Document,
+    page: Option<u64>,
+    per_page: Option<i64>,
+    sort: Document,
+) -> mongodb::error::Result<GuideItemPage> {
+    let (page, per_page) = paging::page_window(page, per_page);
+
+    let mut cursor = dg_collection
+        .aggregate(flattened_items_pipeline(item_filter, page, per_page, sort))
+        .await?;
+    let result = cursor.try_next().await?.unwrap_or_default();
+
+    let items = result
+        .get_array("items")
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|entry| entry.as_document().and_then(guide_item_view))
+                .collect()
+        })
+        .unwrap_or_default();
+    let total = result
+        .get_array("total")
+        .ok()
+        .and_then(|total| total.first()?.as_document()?.get("count").cloned())
+        .and_then(|count| match count {
+            Bson::Int32(count) => Some(count as u64),
+            Bson::Int64(count) => Some(count as u64),
+            _ => None,
+        })
+        .unwrap_or(0);
+
+    Ok(GuideItemPage { items, page, per_page, total })
+}
+
+
+
+/// Pending submissions across every disaster, oldest first
+pub async fn list_pending_items(
+    State(state): State<AppState>,
+    Query(query): Query<PendingQuery>,
+    disaster_id: Option<ObjectId>,
+) -> impl IntoResponse {
+    let db = state.db.lock().await;
+    let dg_collection: Collection<DisasterGuide> = db.database("disaster").collection("disaster_guide");
+
+    let mut filter = doc! { "item.status": GuideStatus::Pending.as_str() };
+    if let Some(kind) = query.kind {
+        filter.insert("kind", if kind == GuideKind::Do { "do" } else { "dont" });
+    }
+    if let Some(disaster_id) = disaster_id {
+        filter.insert("disaster_id", disaster_id);
+    }
+
+    // Older items without `submitted_at` still sort by creation through their ObjectId
+    let sort = doc! { "item.submitted_at": 1, "item._id": 1 };
+
+    match guide_item_page(&dg_collection, filter, query.page, query.per_page, sort).await {
+        Ok(page) => success_response("Pending items retrieved successfully", page, StatusCode::OK),
+        Err(e) => error_response(&format!("Failed to fetch pending items: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+
+
+/// Applies one decision to many items. Items that no longer exist are reported back
+/// instead of failing the whole batch.
+pub async fn bulk_moderate(
+    State(state): State<AppState>,
+    moderator: AuthUser,
+    targets: Vec<(ObjectId, GuideKind, ObjectId)>,
+    Json(decision): Json<ModerationRequest>,
+) -> impl IntoResponse {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let mut updated = Vec::new();
+    let mut not_found = Vec::new();
+    for (dr_id, kind, gi_id) in targets {
+        let target = RevisionTarget { entity: RevisionEntity::GuideItem, entity_id: gi_id, disaster_id: dr_id, kind: Some(kind) };
+        match set_item_status(&database, &target, &decision, moderator.id).await {
+            Ok(Some(_)) => updated.push(gi_id.to_hex()),
+            Ok(None) => not_found.push(gi_id.to_hex()),
+            Err(e) => return error_response(&format!("Update failed: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+
+    success_response(
+        "Bulk moderation applied",
+        json!({
+            "status": decision.status.as_str(),
+            "updated": updated,
+            "not_found": not_found
+        }),
+        StatusCode::OK,
+    )
+}
+
+
+
+/// Everything the caller has submitted, newest first, with the moderator's reason when rejected
+pub async fn list_my_submissions(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<PendingQuery>,
+) -> impl IntoResponse {
+    let db = state.db.lock().await;
+    let dg_collection: Collection<DisasterGuide> = db.database("disaster").collection("disaster_guide");
+
+    let mut filter = doc! { "item.user_id": user.id };
+    if let Some(kind) = query.kind {
+        filter.insert("kind", if kind == GuideKind::Do { "do" } else { "dont" });
+    }
+
+    let sort = doc! { "item.submitted_at": -1, "item._id": -1 };
+
+    match guide_item_page(&dg_collection, filter, query.page, query.per_page, sort).await {
+        Ok(page) => success_response("Submissions retrieved successfully", page, StatusCode::OK),
+        Err(e) => error_response(&format!("Failed to fetch submissions: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+
+
+/// Changes the text of a guide item. Moderators edit in place; an author editing their own item
+/// sends it back to review, and until it is accepted the previously published text stays live.
+pub async fn edit_guide_item(
+    State(state): State<AppState>,
+    Path((dr_id, kind, gi_id)): Path<(ObjectId, GuideKind, ObjectId)>,
+    user: AuthUser,
+    Json(edit): Json<EditGuideItemRequest>,
+) -> impl IntoResponse {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let item = match revision_model::find_guide_item(&database, dr_id, kind, gi_id).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return error_response("No matching record found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let is_moderator = user.has_permission(Permission::ModerateGuides);
+    let is_author = item.get_object_id("user_id").ok() == Some(user.id);
+    if !is_moderator && !is_author {
+        return error_response("Forbidden: only the author or a moderator can edit this item", StatusCode::FORBIDDEN);
+    }
+
+    let mut changes = doc! { "message": edit.message.trim() };
+    if !is_moderator {
+        changes.insert("status", GuideStatus::Pending.as_str());
+        changes.insert("reason", Bson::Null);
+    }
+
+    let target = RevisionTarget { entity: RevisionEntity::GuideItem, entity_id: gi_id, disaster_id: dr_id, kind: Some(kind) };
+    match revision_model::apply_item_change(&database, &target, changes, RevisionAction::Update, user.id, None).await {
+        Ok(Some(item)) => success_response(
+            "Guide item updated successfully",
+            json!({
+                "item_id": gi_id,
+                "updated_item": item
+            }),
+            StatusCode::OK,
+        ),
+        Ok(None) => error_response("No matching record found", StatusCode::NOT_FOUND),
+        Err(e) => error_response(&format!("Update failed: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+
+
+/// Lower bound of the 95% Wilson score interval for the share of upvotes, so a few votes
+/// do not outrank many mostly positive ones
+fn helpfulness(upvotes: u32, downvotes: u32) -> f64 {
+    let n = (upvotes + downvotes) as f64;
+    if n == 0.0 {
+        return 0.0;
+    }
+
+    const Z: f64 = 1.96;
+    let p = upvotes as f64 / n;
+    (p + Z * Z / (2.0 * n) - Z * ((p * (1.0 - p) + Z * Z / (4.0 * n)) / n).sqrt()) / (1.0 + Z * Z / n)
+}
+
+/// Items the public can currently see; only those can be voted on or reported
+pub fn is_published(item: &Document) -> bool {
+    item.get_str("status").ok() == Some(GuideStatus::Accepted.as_str())
+        || item.get_document("published").is_ok()
+}
+
+/// Number of open reports that sends an accepted item back to review, from `GUIDE_FLAG_THRESHOLD`
+fn flag_threshold() -> u32 {
+    std::env::var("GUIDE_FLAG_THRESHOLD")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|threshold| *threshold > 0)
+        .unwrap_or(3)
+}
+
+/// Casts, changes or (with `value: None`) withdraws the caller's vote. Each user holds
+/// at most one vote per item; the counters and helpfulness score are updated with it.
+pub async fn vote_guide_item(
+    State(state): State<AppState>,
+    Path((dr_id, kind, gi_id)): Path<(ObjectId, GuideKind, ObjectId)>,
+    user: AuthUser,
+    value: Option<VoteValue>,
+) -> impl IntoResponse {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let votes: Collection<GuideVote> = database.collection("guide_votes");
+    let dg_collection: Collection<DisasterGuide> = database.collection("disaster_guide");
+
+    let item = match revision_model::find_guide_item(&database, dr_id, kind, gi_id).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return error_response("No matching record found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    if !is_published(&item) {
+        return error_response("Only published items can be voted on", StatusCode::BAD_REQUEST);
+    }
+    if item.get_object_id("user_id").ok() == Some(user.id) {
+        return error_response("You cannot vote on your own item", StatusCode::FORBIDDEN);
+    }
+
+    let vote_filter = doc! { "item_id": gi_id, "user_id": user.id };
+    let previous = match votes.find_one(vote_filter.clone()).await {
+        Ok(vote) => vote.map_or(0, |vote| vote.value),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    let current = match value {
+        Some(VoteValue::Up) => 1,
+        Some(VoteValue::Down) => -1,
+        None => 0,
+    };
+
+    let saved = if current == 0 {
+        votes.delete_one(vote_filter).await.map(|_| ())
+    } else {
+        votes
+            .update_one(
+                vote_filter,
+                doc! {
+                    "$set": { "value": current, "at": DateTime::now() },
+                    "$setOnInsert": { "disaster_id": dr_id }
+                },
+            )
+            .upsert(true)
+            .await
+            .map(|_| ())
+    };
+    if let Err(e) = saved {
+        return error_response(&format!("Failed to save vote: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let tally = |count: u32, value: i32| {
+        let count = count as i64 - (previous == value) as i64 + (current == value) as i64;
+        count.max(0) as u32
+    };
+    let upvotes = tally(counter(&item, "upvotes"), 1);
+    let downvotes = tally(counter(&item, "downvotes"), -1);
+    let score = helpfulness(upvotes, downvotes);
+
+    let field = kind.field();
+    let updated = dg_collection
+        .update_one(
+            doc! { "disaster_id": dr_id, format!("{}._id", field): gi_id },
+            doc! {
+                "$set": {
+                    format!("{}.$.upvotes", field): upvotes,
+                    format!("{}.$.downvotes", field): downvotes,
+                    format!("{}.$.helpfulness", field): score,
+                }
+            },
+        )
+        .await;
+
+    match updated {
+        Ok(_) => success_response(
+            "Vote recorded",
+            json!({
+                "item_id": gi_id,
+                "upvotes": upvotes,
+                "downvotes": downvotes,
+                "helpfulness": score,
+                "your_vote": value.map(|value| if value == VoteValue::Up { "up" } else { "down" })
+            }),
+            StatusCode::OK,
+        ),
+        Err(e) => error_response(&format!("Failed to update item: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Flags a published item. Once `flag_threshold` users have open reports on it, the item goes
+/// back to `Pending` and is withdrawn from public reads until a moderator accepts it again.
+pub async fn report_guide_item(
+    State(state): State<AppState>,
+    Path((dr_id, kind, gi_id)): Path<(ObjectId, GuideKind, ObjectId)>,
+    user: AuthUser,
+    Json(report): Json<ReportRequest>,
+) -> impl IntoResponse {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let reports: Collection<GuideReport> = database.collection("guide_reports");
+    let dg_collection: Collection<DisasterGuide> = database.collection("disaster_guide");
+
+    let item = match revision_model::find_guide_item(&database, dr_id, kind, gi_id).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return error_response("No matching record found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    if !is_published(&item) {
+        return error_response("Only published items can be reported", StatusCode::BAD_REQUEST);
+    }
+
+    match reports.find_one(doc! { "item_id": gi_id, "user_id": user.id, "resolved": false }).await {
+        Ok(Some(_)) => return error_response("You have already reported this item", StatusCode::CONFLICT),
+        Ok(None) => {}
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let entry = GuideReport {
+        id: None,
+        item_id: gi_id,
+        disaster_id: dr_id,
+        user_id: user.id,
+        reason: report.reason.trim().to_string(),
+        at: DateTime::now(),
+        resolved: false,
+    };
+    if let Err(e) = reports.insert_one(entry).await {
+        return error_response(&format!("Failed to save report: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let flags = counter(&item, "flags") + 1;
+    let field = kind.field();
+    if let Err(e) = dg_collection
+        .update_one(
+            doc! { "disaster_id": dr_id, format!("{}._id", field): gi_id },
+            doc! { "$set": { format!("{}.$.flags", field): flags } },
+        )
+        .await
+    {
+        return error_response(&format!("Failed to update item: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let sent_to_review = flags >= flag_threshold();
+    if sent_to_review {
+        let changes = doc! {
+            "status": GuideStatus::Pending.as_str(),
+            "published": Bson::Null,
+            "reason": Bson::Null,
+        };
+        let target = RevisionTarget { entity: RevisionEntity::GuideItem, entity_id: gi_id, disaster_id: dr_id, kind: Some(kind) };
+        if let Err(e) = revision_model::apply_item_change(&database, &target, changes, RevisionAction::Flag, user.id, None).await {
+            return error_response(&format!("Failed to send item to review: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    success_response(
+        "Report received",
+        json!({
+            "item_id": gi_id,
+            "flags": flags,
+            "sent_to_review": sent_to_review
+        }),
+        StatusCode::OK,
+    )
+}
@@ -1,16 +1,19 @@
 use axum::{
-    extract::{Path, State}, 
-    http::StatusCode, 
+    extract::{Path, Query, State}, 
+    http::{header::ACCEPT_LANGUAGE, HeaderMap, StatusCode},
     response::{IntoResponse, Response}, 
     Json
 };
 use mongodb::bson::oid::ObjectId;
-use serde_json::json;
-use crate::utils::{db::AppState, response::error_response};
-use super::{disaster_model, disaster_structure::DisasterRecord};
+use crate::{middleware::permission::AuthUser, utils::{db::AppState, locale::negotiate, response::error_response}};
+use validator::Validate;
+use super::{disaster_model, disaster_structure::{BulkModerationRequest, DisasterListQuery, DisasterRecord, EditGuideItemRequest, GuideKind, ReportRequest, VoteRequest, GuideStatus, ModerationRequest, PendingQuery, UpdateDisasterRequest}};
+
+const MAX_BULK_ITEMS: usize = 100;
 
 pub async fn add_disaster_service(
     State(state): State<AppState>, 
+    user: AuthUser,
     Json(payload): Json<DisasterRecord>,
 ) -> Response {
     // Validate input
@@ -19,12 +22,13 @@ pub async fn add_disaster_service(
     }
 
     // Call your model's add_disaster function
-    disaster_model::add_disaster(State(state), Json(payload)).await.into_response()
+    disaster_model::add_disaster(State(state), user, Json(payload)).await.into_response()
 }
 
 pub async fn add_dos_service(
     State(state): State<AppState>,
     Path(dr_id): Path<ObjectId>,
+    user: AuthUser,
     Json(payload): Json<serde_json::Value>,
 ) -> Response {
     // Validate input
@@ -33,13 +37,14 @@ pub async fn add_dos_service(
     }
 
     // Call your model's add_dos function
-    disaster_model::add_dos(State(state), Path(dr_id), Json(payload)).await.into_response()
+    disaster_model::add_dos(State(state), Path(dr_id), user, Json(payload)).await.into_response()
 }
 
 
 pub async fn add_donts_service(
     State(state): State<AppState>,
     Path(dr_id): Path<ObjectId>,
+    user: AuthUser,
     Json(payload): Json<serde_json::Value>,
 ) -> Response {
     // Validate input
@@ -48,13 +53,14 @@ pub async fn add_donts_service(
     }
 
     // Call your model's add_donts function
-    disaster_model::add_donts(State(state), Path(dr_id), Json(payload)).await.into_response()
+    disaster_model::add_donts(State(state), Path(dr_id), user, Json(payload)).await.into_response()
 }
 
 
 pub async fn get_disaster_record_service(
     State(state): State<AppState>,
     Path(dr_id): Path<String>,
+    headers: HeaderMap,
 ) -> Response {
     // Extract the dr_id from the path
     let path_parts: Vec<&str> = dr_id.split('/').collect();
@@ -70,9 +76,13 @@ pub async fn get_disaster_record_service(
     };
 
     // Call the handler with validated ObjectId
+    // Translations along the caller's preferred languages, falling back to the original text
+    let accept_language = headers.get(ACCEPT_LANGUAGE).and_then(|value| value.to_str().ok());
+
     disaster_model::get_disaster_record(
         State(state),
         Path(dr_id),
+        negotiate(accept_language),
     ).await.into_response()
 }
 
@@ -100,10 +110,22 @@ pub async fn get_all_disaster_record_service(
     ).await.into_response()
 }
 
-pub async fn update_dos_service(
-    State(state): State<AppState>,
-    Path((dr_id, gi_id)): Path<(String, String)>,
-    Json(payload): Json<serde_json::Value>,
+/// Problem with a moderation decision, if any; rejections must tell the author why
+pub fn decision_error(decision: &ModerationRequest) -> Option<&'static str> {
+    let has_reason = decision.reason.as_deref().is_some_and(|reason| !reason.trim().is_empty());
+    match decision.status {
+        GuideStatus::Pending => Some("Status must be Accepted or Rejected"),
+        GuideStatus::Rejected if !has_reason => Some("A reason is required when rejecting"),
+        _ => None,
+    }
+}
+
+async fn moderate_item_service(
+    state: AppState,
+    moderator: AuthUser,
+    (dr_id, gi_id): (String, String),
+    kind: GuideKind,
+    decision: ModerationRequest,
 ) -> Response {
     // Validate ObjectIds
     let disaster_id = match ObjectId::parse_str(&dr_id) {
@@ -120,64 +142,207 @@ pub async fn update_dos_service(
         }
     };
 
-    // Validate payload for required status field
-    let status = match payload.get("status").and_then(|s| s.as_str()) {
-        Some(valid_status) => valid_status,
-        None => {
-            return error_response("Missing or invalid status field in request body", StatusCode::BAD_REQUEST);
-        }
-    };
+    if let Some(message) = decision_error(&decision) {
+        return error_response(message, StatusCode::BAD_REQUEST);
+    }
 
-    // Call the model function and get the response
-    let update_result = disaster_model::update_dos(
+    disaster_model::moderate_item(
         State(state),
-        Path((disaster_id, guide_item_id)),
-        Json(json!({ "status": status })),
+        Path((disaster_id, kind, guide_item_id)),
+        moderator,
+        Json(decision),
     )
-    .await;
+    .await
+    .into_response()
+}
 
-    // Return the response directly from update_dos or handle errors accordingly
-    update_result.into_response()
+pub async fn update_dos_service(
+    State(state): State<AppState>,
+    Path(ids): Path<(String, String)>,
+    moderator: AuthUser,
+    Json(payload): Json<ModerationRequest>,
+) -> Response {
+    moderate_item_service(state, moderator, ids, GuideKind::Do, payload).await
 }
 
 
 
 pub async fn update_donts_service(
     State(state): State<AppState>,
-    Path((dr_id, gi_id)): Path<(String, String)>,
-    Json(payload): Json<serde_json::Value>,
+    Path(ids): Path<(String, String)>,
+    moderator: AuthUser,
+    Json(payload): Json<ModerationRequest>,
 ) -> Response {
-    // Validate ObjectIds
-    let disaster_id = match ObjectId::parse_str(&dr_id) {
-        Ok(valid_id) => valid_id,
-        Err(_) => {
-            return error_response("Invalid disaster ID format", StatusCode::BAD_REQUEST);
-        }
+    moderate_item_service(state, moderator, ids, GuideKind::Dont, payload).await
+}
+
+
+
+pub async fn list_pending_service(
+    State(state): State<AppState>,
+    Query(query): Query<PendingQuery>,
+) -> Response {
+    let disaster_id = match query.disaster_id.as_deref().map(ObjectId::parse_str).transpose() {
+        Ok(disaster_id) => disaster_id,
+        Err(_) => return error_response("Invalid disaster record ID format", StatusCode::BAD_REQUEST),
     };
 
-    let guide_item_id = match ObjectId::parse_str(&gi_id) {
-        Ok(valid_id) => valid_id,
-        Err(_) => {
-            return error_response("Invalid guide item ID format", StatusCode::BAD_REQUEST);
+    disaster_model::list_pending_items(State(state), Query(query), disaster_id).await.into_response()
+}
+
+
+
+pub async fn bulk_moderation_service(
+    State(state): State<AppState>,
+    moderator: AuthUser,
+    Json(payload): Json<BulkModerationRequest>,
+) -> Response {
+    if payload.items.is_empty() || payload.items.len() > MAX_BULK_ITEMS {
+        return error_response(
+            &format!("Between 1 and {} items can be moderated at once", MAX_BULK_ITEMS),
+            StatusCode::BAD_REQUEST,
+        );
+    }
+
+    let decision = ModerationRequest { status: payload.status, reason: payload.reason };
+    if let Some(message) = decision_error(&decision) {
+        return error_response(message, StatusCode::BAD_REQUEST);
+    }
+
+    let mut targets = Vec::with_capacity(payload.items.len());
+    for item in payload.items {
+        match (ObjectId::parse_str(&item.disaster_id), ObjectId::parse_str(&item.item_id)) {
+            (Ok(dr_id), Ok(gi_id)) => targets.push((dr_id, item.kind, gi_id)),
+            _ => return error_response("Invalid disaster or guide item ID format", StatusCode::BAD_REQUEST),
         }
+    }
+
+    disaster_model::bulk_moderate(State(state), moderator, targets, Json(decision)).await.into_response()
+}
+
+
+
+pub async fn my_submissions_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<PendingQuery>,
+) -> Response {
+    disaster_model::list_my_submissions(State(state), user, Query(query)).await.into_response()
+}
+
+
+
+pub async fn list_disasters_service(
+    State(state): State<AppState>,
+    Query(query): Query<DisasterListQuery>,
+) -> Response {
+    disaster_model::list_disasters(State(state), Query(query)).await.into_response()
+}
+
+
+
+pub async fn update_disaster_service(
+    State(state): State<AppState>,
+    Path(dr_id): Path<String>,
+    user: AuthUser,
+    Json(payload): Json<UpdateDisasterRequest>,
+) -> Response {
+    let dr_id = match ObjectId::parse_str(&dr_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid disaster record ID format", StatusCode::BAD_REQUEST),
     };
 
-    // Validate payload for required status field
-    let status = match payload.get("status").and_then(|s| s.as_str()) {
-        Some(valid_status) => valid_status,
-        None => {
-            return error_response("Missing or invalid status field in request body", StatusCode::BAD_REQUEST);
-        }
+    if let Err(errors) = payload.validate() {
+        return error_response(&errors.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    disaster_model::update_disaster(State(state), Path(dr_id), user, Json(payload)).await.into_response()
+}
+
+
+
+pub async fn delete_disaster_service(
+    State(state): State<AppState>,
+    Path(dr_id): Path<String>,
+    user: AuthUser,
+) -> Response {
+    let dr_id = match ObjectId::parse_str(&dr_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid disaster record ID format", StatusCode::BAD_REQUEST),
     };
 
-    // Call the model function and get the response
-    let update_result = disaster_model::update_donts(
-        State(state),
-        Path((disaster_id, guide_item_id)),
-        Json(json!({ "status": status })),
-    )
-    .await;
+    disaster_model::delete_disaster(State(state), Path(dr_id), user).await.into_response()
+}
+
+
+
+pub async fn edit_guide_item_service(
+    State(state): State<AppState>,
+    Path((dr_id, kind, gi_id)): Path<(String, GuideKind, String)>,
+    user: AuthUser,
+    Json(payload): Json<EditGuideItemRequest>,
+) -> Response {
+    let (disaster_id, guide_item_id) = match (ObjectId::parse_str(&dr_id), ObjectId::parse_str(&gi_id)) {
+        (Ok(disaster_id), Ok(guide_item_id)) => (disaster_id, guide_item_id),
+        _ => return error_response("Invalid disaster or guide item ID format", StatusCode::BAD_REQUEST),
+    };
+
+    if let Err(errors) = payload.validate() {
+        return error_response(&errors.to_string(), StatusCode::BAD_REQUEST);
+    }
 
-    // Return the response directly from update_dos or handle errors accordingly
-    update_result.into_response()
-}
\ No newline at end of file
+    disaster_model::edit_guide_item(State(state), Path((disaster_id, kind, guide_item_id)), user, Json(payload)).await.into_response()
+}
+
+
+
+/// Parses the ids of a `/guide/{dr_id}/{kind}/{gi_id}` path
+fn guide_item_path((dr_id, kind, gi_id): (String, GuideKind, String)) -> Option<(ObjectId, GuideKind, ObjectId)> {
+    Some((ObjectId::parse_str(&dr_id).ok()?, kind, ObjectId::parse_str(&gi_id).ok()?))
+}
+
+pub async fn vote_service(
+    State(state): State<AppState>,
+    Path(path): Path<(String, GuideKind, String)>,
+    user: AuthUser,
+    Json(payload): Json<VoteRequest>,
+) -> Response {
+    let Some(ids) = guide_item_path(path) else {
+        return error_response("Invalid disaster or guide item ID format", StatusCode::BAD_REQUEST);
+    };
+
+    disaster_model::vote_guide_item(State(state), Path(ids), user, Some(payload.value)).await.into_response()
+}
+
+
+
+pub async fn remove_vote_service(
+    State(state): State<AppState>,
+    Path(path): Path<(String, GuideKind, String)>,
+    user: AuthUser,
+) -> Response {
+    let Some(ids) = guide_item_path(path) else {
+        return error_response("Invalid disaster or guide item ID format", StatusCode::BAD_REQUEST);
+    };
+
+    disaster_model::vote_guide_item(State(state), Path(ids), user, None).await.into_response()
+}
+
+
+
+pub async fn report_service(
+    State(state): State<AppState>,
+    Path(path): Path<(String, GuideKind, String)>,
+    user: AuthUser,
+    Json(payload): Json<ReportRequest>,
+) -> Response {
+    let Some(ids) = guide_item_path(path) else {
+        return error_response("Invalid disaster or guide item ID format", StatusCode::BAD_REQUEST);
+    };
+
+    if payload.reason.trim().len() < 5 {
+        return error_response("Reason must be at least 5 characters long", StatusCode::BAD_REQUEST);
+    }
+
+    disaster_model::report_guide_item(State(state), Path(ids), user, Json(payload)).await.into_response()
+}
@@ -1,7 +1,9 @@
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{oid::ObjectId, DateTime};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use crate::hazard::hazard_structure::Severity;
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct DisasterRecord {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -19,6 +21,15 @@ pub struct DisasterRecord {
     #[serde(default)]
     #[validate(url(message = "Invalid YouTube link format"))]
     pub youtube_link: String,
+
+    #[serde(default)]
+    pub revision: u32, // Current version in the `revisions` collection; 0 on records older than versioning
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hazard: Option<String>, // Code of a `HazardNode` in the hazard taxonomy
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
 }
 
 
@@ -45,9 +56,247 @@ pub struct GuideItem {
     pub user_id: ObjectId, // User who added this guide entry
 
     #[validate(length(min = 2, message = "Status must be at least 2 characters long"))]
-    pub status: String, // Example: "Accepted", "Pending". Delete entry if rejected
+    pub status: String, // One of `GuideStatus`: "Pending" on submission, then "Accepted" or "Rejected"
 
     #[validate(length(min = 5, message = "Message must be at least 5 characters long"))]
     pub message: String, // The actual guidance message
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitted_at: Option<DateTime>, // Missing on items added before moderation existed
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>, // Moderator's explanation, shown to the author
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderated_by: Option<ObjectId>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderated_at: Option<DateTime>,
+
+    #[serde(default)]
+    pub revision: u32, // Current version in the `revisions` collection
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<PublishedText>, // Last accepted text, served while a later edit awaits review
+
+    #[serde(default)]
+    pub upvotes: u32,
+
+    #[serde(default)]
+    pub downvotes: u32,
+
+    #[serde(default)]
+    pub helpfulness: f64, // Wilson lower bound of the upvote share; public reads sort by it
+
+    #[serde(default)]
+    pub flags: u32, // Open abuse reports since the item was last accepted
 }
 
+/// Accepted version of a guide item's message
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublishedText {
+    pub revision: u32,
+    pub message: String,
+}
+
+/// Body of `PATCH /disaster/guide/{dr_id}/{kind}/{gi_id}`
+#[derive(Debug, Deserialize, Validate)]
+pub struct EditGuideItemRequest {
+    #[validate(length(min = 5, message = "Message must be at least 5 characters long"))]
+    pub message: String,
+}
+
+/// Moderation state of a `GuideItem`. Rejected items are kept for the author's history
+/// and filtered out of public reads like pending ones.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum GuideStatus {
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+impl GuideStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GuideStatus::Pending => "Pending",
+            GuideStatus::Accepted => "Accepted",
+            GuideStatus::Rejected => "Rejected",
+        }
+    }
+}
+
+/// Which list of a `DisasterGuide` an item belongs to
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GuideKind {
+    Do,
+    Dont,
+}
+
+impl GuideKind {
+    /// Array field holding this kind of item
+    pub fn field(&self) -> &'static str {
+        match self {
+            GuideKind::Do => "do_s",
+            GuideKind::Dont => "dont_s",
+        }
+    }
+}
+
+/// Body of a moderation decision; a reason is required when rejecting
+#[derive(Debug, Deserialize)]
+pub struct ModerationRequest {
+    pub status: GuideStatus,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// One item of a bulk decision
+#[derive(Debug, Deserialize)]
+pub struct ModerationTarget {
+    pub disaster_id: String,
+    pub kind: GuideKind,
+    pub item_id: String,
+}
+
+/// Body of `POST /disaster/moderation/bulk`: one decision applied to every listed item
+#[derive(Debug, Deserialize)]
+pub struct BulkModerationRequest {
+    pub items: Vec<ModerationTarget>,
+    pub status: GuideStatus,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Query of `GET /disaster/moderation/pending`; oldest submissions first, pages start at 1
+#[derive(Debug, Deserialize)]
+pub struct PendingQuery {
+    pub kind: Option<GuideKind>,
+    pub disaster_id: Option<String>,
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+/// Guide item listed with the disaster it belongs to, used by the moderation queue
+/// and by the author's submission history
+#[derive(Debug, Serialize)]
+pub struct GuideItemView {
+    pub disaster_id: String,
+    pub disaster_name: Option<String>,
+    pub kind: GuideKind,
+    pub item_id: String,
+    pub user_id: String,
+    pub message: String,
+    pub status: String,
+    pub reason: Option<String>,
+    pub submitted_at: Option<String>,
+    pub moderated_at: Option<String>,
+    pub helpfulness: f64,
+    pub flags: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuideItemPage {
+    pub items: Vec<GuideItemView>,
+    pub page: u64,
+    pub per_page: i64,
+    pub total: u64,
+}
+
+
+/// Order of `GET /disaster`; `newest`/`oldest` follow creation time
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DisasterSort {
+    #[default]
+    Name,
+    #[serde(rename = "-name")]
+    NameDesc,
+    Newest,
+    Oldest,
+}
+
+/// Query of `GET /disaster`; pages start at 1
+#[derive(Debug, Deserialize)]
+pub struct DisasterListQuery {
+    pub name: Option<String>, // Case-insensitive substring
+    pub hazard: Option<String>, // Taxonomy code; subtypes are included
+    pub min_severity: Option<Severity>,
+    #[serde(default)]
+    pub sort: DisasterSort,
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisasterPage {
+    pub disasters: Vec<DisasterRecord>,
+    pub page: u64,
+    pub per_page: i64,
+    pub total: u64,
+}
+
+/// Body of `PATCH /disaster/{id}`; only the fields present are changed
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateDisasterRequest {
+    #[validate(length(min = 2, message = "Name must be at least 2 characters long"))]
+    pub name: Option<String>,
+
+    #[validate(length(min = 5, message = "Effects must be at least 5 characters long"))]
+    pub effects: Option<String>,
+
+    #[validate(length(min = 10, message = "Short description must be at least 10 characters long"))]
+    pub short_description: Option<String>,
+
+    #[validate(url(message = "Invalid YouTube link format"))]
+    pub youtube_link: Option<String>,
+
+    pub hazard: Option<String>,
+
+    pub severity: Option<Severity>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VoteValue {
+    Up,
+    Down,
+}
+
+/// Body of `POST /disaster/guide/{dr_id}/{kind}/{gi_id}/vote`
+#[derive(Debug, Deserialize)]
+pub struct VoteRequest {
+    pub value: VoteValue,
+}
+
+/// One user's vote on a guide item, stored in the `guide_votes` collection
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuideVote {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub item_id: ObjectId,
+    pub disaster_id: ObjectId,
+    pub user_id: ObjectId,
+    pub value: i32, // 1 for up, -1 for down
+    pub at: DateTime,
+}
+
+/// Body of `POST /disaster/guide/{dr_id}/{kind}/{gi_id}/report`
+#[derive(Debug, Deserialize)]
+pub struct ReportRequest {
+    pub reason: String,
+}
+
+/// Abuse report on a guide item, stored in the `guide_reports` collection.
+/// Reports are resolved when a moderator accepts the item again.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuideReport {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub item_id: ObjectId,
+    pub disaster_id: ObjectId,
+    pub user_id: ObjectId,
+    pub reason: String,
+    pub at: DateTime,
+    pub resolved: bool,
+}
@@ -1,9 +1,9 @@
 use std::sync::Arc;
 
-use axum::{middleware::from_fn, routing::{get, patch, post}, Router};
-use disaster_service::{add_disaster_service, add_donts_service, add_dos_service, get_all_disaster_record_service, get_disaster_record_service, update_donts_service, update_dos_service};
+use axum::{middleware::{from_fn, from_fn_with_state}, routing::{get, patch, post}, Router};
+use disaster_service::{add_disaster_service, add_donts_service, add_dos_service, bulk_moderation_service, delete_disaster_service, edit_guide_item_service, get_all_disaster_record_service, get_disaster_record_service, list_disasters_service, list_pending_service, my_submissions_service, remove_vote_service, report_service, update_disaster_service, update_donts_service, update_dos_service, vote_service};
 
-use crate::{middleware::auth::auth_middleware, utils::db::AppState};
+use crate::{middleware::{auth::auth_middleware, permission::{require_permission, Permission}}, hazard, revision, search, translation, utils::db::AppState};
 
 pub mod disaster_model;
 pub mod disaster_service;
@@ -11,14 +11,35 @@ pub mod disaster_structure;
 
 pub fn create_routes(state: Arc<AppState>) -> Router {
     Router::new()
-        .route("/add_disaster_record", post(add_disaster_service))
+        .route("/", get(list_disasters_service)
+            .route_layer(from_fn_with_state(Permission::ManageDisasters, require_permission)))
+        .route("/{dr_id}", patch(update_disaster_service)
+            .delete(delete_disaster_service)
+            .route_layer(from_fn_with_state(Permission::ManageDisasters, require_permission)))
+        .route("/add_disaster_record", post(add_disaster_service)
+            .route_layer(from_fn_with_state(Permission::ManageDisasters, require_permission)))
         .route("/add_do/{dr_id}", patch(add_dos_service))
         .route("/add_dont/{dr_id}", patch(add_donts_service))
         .route("/get_disaster_record/{dr_id}", get(get_disaster_record_service))
-        .route("/update_do/{dr_id}/{gi_id}", patch(update_dos_service))
-        .route("/update_dont/{dr_id}/{gi_id}", patch(update_donts_service))
-        .route("/get_all_disaster_record/{dr_id}", get(get_all_disaster_record_service))
+        .route("/update_do/{dr_id}/{gi_id}", patch(update_dos_service)
+            .route_layer(from_fn_with_state(Permission::ModerateGuides, require_permission)))
+        .route("/update_dont/{dr_id}/{gi_id}", patch(update_donts_service)
+            .route_layer(from_fn_with_state(Permission::ModerateGuides, require_permission)))
+        .route("/get_all_disaster_record/{dr_id}", get(get_all_disaster_record_service)
+            .route_layer(from_fn_with_state(Permission::ModerateGuides, require_permission)))
+        .route("/moderation/pending", get(list_pending_service)
+            .route_layer(from_fn_with_state(Permission::ModerateGuides, require_permission)))
+        .route("/moderation/bulk", post(bulk_moderation_service)
+            .route_layer(from_fn_with_state(Permission::ModerateGuides, require_permission)))
+        .route("/my_submissions", get(my_submissions_service))
+        .route("/guide/{dr_id}/{kind}/{gi_id}", patch(edit_guide_item_service))
+        .route("/guide/{dr_id}/{kind}/{gi_id}/vote", post(vote_service).delete(remove_vote_service))
+        .route("/guide/{dr_id}/{kind}/{gi_id}/report", post(report_service))
         .layer(from_fn(auth_middleware))  
         .with_state((*state).clone())
+        .nest("/revisions", revision::revision_routes(state.clone()))
+        .nest("/translations", translation::translation_routes(state.clone()))
+        .nest("/search", search::search_routes(state.clone()))
+        .nest("/hazards", hazard::hazard_routes(state))
 }
 
@@ -0,0 +1,376 @@
+//! Ingestion of the GDACS event list into `external_events`, and the queries behind `/events`.
+//! A background task polls the feed every `GDACS_POLL_SECONDS`; each event is kept as one
+//! document that follows its latest episode and records every alert level change.
+
+use std::{env, sync::Arc, time::Duration};
+
+use axum::{http::StatusCode, response::Response};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, to_bson, DateTime, Document},
+    options::IndexOptions,
+    Collection, Database, IndexModel,
+};
+use serde_json::Value;
+use tokio::{sync::{Mutex, OnceCell}, time::MissedTickBehavior};
+
+use super::events_structure::{
+    AlertChange, AlertLevel, EventLocation, EventPage, ExternalEvent, ExternalEventView, GdacsFeature, GdacsGeometry,
+    IngestionReport,
+};
+use crate::{
+    hazard::{hazard_model, hazard_structure::HazardNode},
+    utils::{
+        db::AppState,
+        disaster_event_data::{disaster_event_data, gdacs_events_url},
+        paging,
+        response::{error_response, success_response},
+    },
+};
+
+const SOURCE: &str = "gdacs";
+const DEFAULT_POLL_SECONDS: u64 = 900;
+const EARTH_RADIUS_KM: f64 = 6378.1;
+
+/// Created on first use rather than at startup, so the server starts without the database
+static EVENT_INDEXES: OnceCell<()> = OnceCell::const_new();
+/// The scheduled poll and a manual refresh must not upsert the same events concurrently
+static INGESTING: Mutex<()> = Mutex::const_new(());
+
+/// The `external_events` collection, with the unique key used for de-duplication and
+/// the 2dsphere index behind location filters
+async fn external_events(database: &Database) -> mongodb::error::Result<Collection<ExternalEvent>> {
+    let events: Collection<ExternalEvent> = database.collection("external_events");
+    EVENT_INDEXES
+        .get_or_try_init(|| async {
+            let indexes = vec![
+                IndexModel::builder()
+                    .keys(doc! { "source": 1, "event_type": 1, "event_id": 1 })
+                    .options(IndexOptions::builder().name("event_key".to_string()).unique(true).build())
+                    .build(),
+                IndexModel::builder()
+                    .keys(doc! { "location": "2dsphere" })
+                    .options(IndexOptions::builder().name("location_2dsphere".to_string()).build())
+                    .build(),
+            ];
+            events.create_indexes(indexes).await.map(|_| ())
+        })
+        .await?;
+    Ok(events)
+}
+
+/// GDACS dates are UTC, usually without an offset (`2025-01-31T08:00:00`)
+fn parse_feed_date(date: Option<&str>) -> Option<DateTime> {
+    let date = date?.trim();
+    DateTime::parse_rfc3339_str(date).ok().or_else(|| {
+        chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S%.f")
+            .ok()
+            .map(|at| DateTime::from_millis(at.and_utc().timestamp_millis()))
+    })
+}
+
+fn event_location(geometry: Option<&GdacsGeometry>) -> Option<EventLocation> {
+    let geometry = geometry.filter(|geometry| geometry.kind == "Point")?;
+    let coordinates = geometry.coordinates.as_array()?;
+    let (longitude, latitude) = (coordinates.first()?.as_f64()?, coordinates.get(1)?.as_f64()?);
+    let valid = (-180.0..=180.0).contains(&longitude) && (-90.0..=90.0).contains(&latitude);
+    valid.then(|| EventLocation { kind: "Point".to_string(), coordinates: [longitude, latitude] })
+}
+
+enum Upserted {
+    Inserted,
+    Updated { alert_changed: bool },
+    Stale,
+}
+
+/// Stores one feed entry. A newer episode, or a re-poll of the same one, refreshes the stored
+/// event; an older episode arriving late only marks the event as seen.
+async fn upsert_event(
+    database: &Database,
+    nodes: &[HazardNode],
+    feature: GdacsFeature,
+    alert_level: AlertLevel,
+    now: DateTime,
+) -> mongodb::error::Result<Upserted> {
+    let events = external_events(database).await?;
+    let properties = feature.properties;
+    let event_type = properties.eventtype.trim().to_uppercase();
+    let key = doc! { "source": SOURCE, "event_type": &event_type, "event_id": properties.eventid };
+
+    let stored = events.find_one(key.clone()).await?;
+    if stored.as_ref().is_some_and(|stored| properties.episodeid < stored.episode_id) {
+        // Still in the feed, so not swept as no longer current
+        events.update_one(key, doc! { "$set": { "last_seen_at": now } }).await?;
+        return Ok(Upserted::Stale);
+    }
+
+    let change = AlertChange { alert_level, alert_score: properties.alertscore, episode_id: properties.episodeid, at: now };
+    let event = ExternalEvent {
+        id: None,
+        source: SOURCE.to_string(),
+        hazard: hazard_model::node_for_gdacs_type(nodes, &event_type).map(|node| node.code.clone()),
+        event_type,
+        event_id: properties.eventid,
+        episode_id: properties.episodeid,
+        episodes: vec![properties.episodeid],
+        name: properties.name.trim().to_string(),
+        description: properties.description,
+        country: properties.country.filter(|country| !country.trim().is_empty()),
+        iso3: properties.iso3.filter(|iso3| !iso3.trim().is_empty()).map(|iso3| iso3.to_uppercase()),
+        alert_level,
+        alert_score: properties.alertscore,
+        severity: Some(alert_level.severity()),
+        severity_text: properties.severitydata.and_then(|severity| severity.severitytext),
+        location: event_location(feature.geometry.as_ref()),
+        from_date: parse_feed_date(properties.fromdate.as_deref()),
+        to_date: parse_feed_date(properties.todate.as_deref()),
+        modified_at: parse_feed_date(properties.datemodified.as_deref()),
+        is_current: match properties.iscurrent {
+            Some(Value::Bool(current)) => current,
+            Some(Value::String(current)) => current.eq_ignore_ascii_case("true"),
+            _ => true,
+        },
+        report_url: properties.url.and_then(|url| url.report),
+        alert_history: vec![change.clone()],
+        first_seen_at: now,
+        last_seen_at: now,
+    };
+
+    let Some(stored) = stored else {
+        events.insert_one(&event).await?;
+        return Ok(Upserted::Inserted);
+    };
+
+    let mut set = mongodb::bson::to_document(&event)?;
+    for kept in ["_id", "source", "event_type", "event_id", "episodes", "alert_history", "first_seen_at"] {
+        set.remove(kept);
+    }
+    let alert_changed = stored.alert_level != alert_level;
+    let mut update = doc! { "$set": set, "$addToSet": { "episodes": event.episode_id } };
+    if alert_changed {
+        update.insert("$push", doc! { "alert_history": to_bson(&change)? });
+    }
+
+    events.update_one(key, update).await?;
+    Ok(Upserted::Updated { alert_changed })
+}
+
+/// Polls the feed once and stores every event in it. Current events missing from the feed
+/// are no longer current.
+pub async fn ingest(state: &AppState) -> Result<IngestionReport, (StatusCode, String)> {
+    let _running = INGESTING.lock().await;
+
+    // Fetched before taking the database lock, which the API needs meanwhile
+    let feed = disaster_event_data(&gdacs_events_url()).await?;
+    let Some(features) = feed.get("features").and_then(Value::as_array) else {
+        return Err((StatusCode::BAD_GATEWAY, "GDACS feed has no features".to_string()));
+    };
+
+    let db_error = |e: mongodb::error::Error| (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e));
+    let nodes = {
+        let db = state.db.lock().await;
+        hazard_model::taxonomy(&db.database("disaster")).await.map_err(db_error)?
+    };
+
+    let started = DateTime::now();
+    let mut report = IngestionReport { fetched: features.len(), ..Default::default() };
+    for feature in features {
+        let parsed = serde_json::from_value::<GdacsFeature>(feature.clone())
+            .ok()
+            .and_then(|feature| AlertLevel::parse(&feature.properties.alertlevel).map(|level| (feature, level)));
+        let Some((feature, alert_level)) = parsed else {
+            report.invalid += 1;
+            continue;
+        };
+
+        // Locked per event so API requests are not held up for the whole feed
+        let db = state.db.lock().await;
+        match upsert_event(&db.database("disaster"), &nodes, feature, alert_level, started).await.map_err(db_error)? {
+            Upserted::Inserted => report.inserted += 1,
+            Upserted::Updated { alert_changed } => {
+                report.updated += 1;
+                report.alert_changes += usize::from(alert_changed);
+            }
+            Upserted::Stale => report.stale += 1,
+        }
+    }
+
+    if report.inserted + report.updated > 0 {
+        let db = state.db.lock().await;
+        db.database("disaster")
+            .collection::<Document>("external_events")
+            .update_many(
+                doc! { "source": SOURCE, "is_current": true, "last_seen_at": { "$lt": started } },
+                doc! { "$set": { "is_current": false } },
+            )
+            .await
+            .map_err(db_error)?;
+    }
+
+    Ok(report)
+}
+
+/// Starts polling GDACS every `GDACS_POLL_SECONDS` (15 minutes by default; `0` disables it)
+pub fn spawn_ingestion(state: Arc<AppState>) {
+    let seconds = env::var("GDACS_POLL_SECONDS")
+        .ok()
+        .and_then(|seconds| seconds.parse().ok())
+        .unwrap_or(DEFAULT_POLL_SECONDS);
+    if seconds == 0 {
+        println!("GDACS_POLL_SECONDS is 0, GDACS ingestion is disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(seconds));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            match ingest(&state).await {
+                Ok(report) => println!(
+                    "GDACS ingestion: {} fetched, {} new, {} updated, {} alert changes",
+                    report.fetched, report.inserted, report.updated, report.alert_changes
+                ),
+                Err((_, message)) => eprintln!("GDACS ingestion failed: {}", message),
+            }
+        }
+    });
+}
+
+pub async fn refresh(state: AppState) -> Response {
+    match ingest(&state).await {
+        Ok(report) => success_response("GDACS events refreshed", report, StatusCode::OK),
+        Err((status, message)) => error_response(&message, status),
+    }
+}
+
+/// Filters of `GET /events`, already parsed
+pub struct EventFilter {
+    pub event_types: Vec<String>,
+    pub min_alert: Option<AlertLevel>,
+    pub hazard: Option<String>,
+    pub iso3: Option<String>,
+    pub current_only: bool,
+    pub since: Option<DateTime>,
+    pub around: Option<(f64, f64, f64)>, // (longitude, latitude, radius in km)
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+/// Events matching the filter, most recent first
+pub async fn list_events(state: AppState, event_filter: EventFilter) -> Response {
+    let (page, per_page) = paging::page_window(event_filter.page, event_filter.per_page);
+
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let mut filter = doc! { "source": SOURCE };
+    if !event_filter.event_types.is_empty() {
+        filter.insert("event_type", doc! { "$in": &event_filter.event_types });
+    }
+    if let Some(min_alert) = event_filter.min_alert {
+        filter.insert("alert_level", doc! { "$in": min_alert.at_least() });
+    }
+    if let Some(hazard) = event_filter.hazard.as_deref() {
+        match hazard_model::hazard_filter(&database, hazard).await {
+            Ok(Some(codes)) => {
+                filter.insert("hazard", doc! { "$in": codes });
+            }
+            Ok(None) => return error_response("Hazard not found", StatusCode::NOT_FOUND),
+            Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+    if let Some(iso3) = event_filter.iso3 {
+        filter.insert("iso3", iso3);
+    }
+    if event_filter.current_only {
+        filter.insert("is_current", true);
+    }
+    if let Some(since) = event_filter.since {
+        filter.insert("$or", vec![doc! { "to_date": { "$gte": since } }, doc! { "to_date": null }]);
+    }
+    if let Some((longitude, latitude, radius_km)) = event_filter.around {
+        filter.insert(
+            "location",
+            doc! { "$geoWithin": { "$centerSphere": [[longitude, latitude], radius_km / EARTH_RADIUS_KM] } },
+        );
+    }
+
+    let found = async {
+        let events = external_events(&database).await?;
+        let total = events.count_documents(filter.clone()).await?;
+        let found: Vec<ExternalEvent> = events
+            .find(filter)
+            .sort(doc! { "from_date": -1, "_id": -1 })
+            .skip(paging::skip(page, per_page))
+            .limit(per_page)
+            .await?
+            .try_collect()
+            .await?;
+        mongodb::error::Result::Ok((total, found))
+    };
+
+    match found.await {
+        Ok((total, found)) => success_response(
+            "Events retrieved successfully",
+            EventPage { events: found.into_iter().map(ExternalEventView::from).collect(), page, per_page, total },
+            StatusCode::OK,
+        ),
+        Err(e) => error_response(&format!("Failed to fetch events: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn get_event(state: AppState, event_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let events: Collection<ExternalEvent> = db.database("disaster").collection("external_events");
+
+    match events.find_one(doc! { "_id": event_id }).await {
+        Ok(Some(event)) => success_response("Event retrieved successfully", ExternalEventView::from(event), StatusCode::OK),
+        Ok(None) => error_response("Event not found", StatusCode::NOT_FOUND),
+        Err(e) => error_response(&format!("Failed to fetch event: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn point(coordinates: Value) -> GdacsGeometry {
+        GdacsGeometry { kind: "Point".to_string(), coordinates }
+    }
+
+    #[test]
+    fn feed_dates_are_read_with_or_without_an_offset() {
+        let expected = DateTime::from_millis(1_700_000_000_000);
+        assert_eq!(parse_feed_date(Some("2023-11-14T22:13:20Z")), Some(expected));
+        assert_eq!(parse_feed_date(Some("2023-11-15T00:13:20+02:00")), Some(expected));
+        assert_eq!(parse_feed_date(Some(" 2023-11-14T22:13:20 ")), Some(expected));
+        assert_eq!(
+            parse_feed_date(Some("2023-11-14T22:13:20.250")),
+            Some(DateTime::from_millis(1_700_000_000_250))
+        );
+        assert_eq!(parse_feed_date(Some("14/11/2023")), None);
+        assert_eq!(parse_feed_date(Some("")), None);
+        assert_eq!(parse_feed_date(None), None);
+    }
+
+    #[test]
+    fn locations_are_points_within_range() {
+        let location = event_location(Some(&point(json!([121.5, -8.25, 10.0])))).unwrap();
+        assert_eq!(location.kind, "Point");
+        assert_eq!(location.coordinates, [121.5, -8.25]);
+        assert_eq!(event_location(Some(&point(json!([-180, 90])))).unwrap().coordinates, [-180.0, 90.0]);
+
+        assert!(event_location(None).is_none());
+        assert!(event_location(Some(&point(json!([121.5])))).is_none());
+        assert!(event_location(Some(&point(json!(["121.5", "-8.25"])))).is_none());
+        assert!(event_location(Some(&point(json!({ "lon": 121.5, "lat": -8.25 })))).is_none());
+        assert!(event_location(Some(&point(json!([180.5, 0.0])))).is_none());
+        assert!(event_location(Some(&point(json!([0.0, -90.5])))).is_none());
+
+        let polygon = GdacsGeometry { kind: "Polygon".to_string(), coordinates: json!([121.5, -8.25]) };
+        assert!(event_location(Some(&polygon)).is_none());
+    }
+}
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Response,
+};
+use mongodb::bson::{oid::ObjectId, DateTime};
+
+use super::{
+    events_model::{self, EventFilter},
+    events_structure::{AlertLevel, EventListQuery},
+};
+use crate::{
+    hazard::hazard_structure::GDACS_TYPES,
+    utils::{db::AppState, response::error_response},
+};
+
+const MAX_RADIUS_KM: f64 = 5000.0;
+
+pub async fn list_events_service(
+    State(state): State<AppState>,
+    Query(query): Query<EventListQuery>,
+) -> Response {
+    let event_types: Vec<String> = match query.event_type.as_deref() {
+        Some(types) => types.split(',').map(|event_type| event_type.trim().to_uppercase()).collect(),
+        None => Vec::new(),
+    };
+    if event_types.iter().any(|event_type| !GDACS_TYPES.contains(&event_type.as_str())) {
+        return error_response(&format!("Event type must be one of: {}", GDACS_TYPES.join(", ")), StatusCode::BAD_REQUEST);
+    }
+    let min_alert = match query.min_alert.as_deref().map(AlertLevel::parse) {
+        Some(None) => return error_response("Alert level must be Green, Orange or Red", StatusCode::BAD_REQUEST),
+        Some(level) => level,
+        None => None,
+    };
+    let since = match query.since.as_deref().map(|since| DateTime::parse_rfc3339_str(since.trim())) {
+        Some(Err(_)) => return error_response("since must be RFC 3339, e.g. 2025-01-31T08:00:00Z", StatusCode::BAD_REQUEST),
+        Some(Ok(since)) => Some(since),
+        None => None,
+    };
+    let around = match (query.longitude, query.latitude, query.radius_km) {
+        (None, None, None) => None,
+        (Some(longitude), Some(latitude), Some(radius_km))
+            if (-180.0..=180.0).contains(&longitude)
+                && (-90.0..=90.0).contains(&latitude)
+                && radius_km > 0.0
+                && radius_km <= MAX_RADIUS_KM =>
+        {
+            Some((longitude, latitude, radius_km))
+        }
+        _ => {
+            return error_response(
+                &format!("Give latitude, longitude and a radius_km of at most {}", MAX_RADIUS_KM),
+                StatusCode::BAD_REQUEST,
+            )
+        }
+    };
+
+    let filter = EventFilter {
+        event_types,
+        min_alert,
+        hazard: query.hazard,
+        iso3: query.country.map(|country| country.trim().to_uppercase()),
+        current_only: query.current.unwrap_or(true),
+        since,
+        around,
+        page: query.page,
+        per_page: query.per_page,
+    };
+    events_model::list_events(state, filter).await
+}
+
+pub async fn get_event_service(
+    State(state): State<AppState>,
+    Path(event_id): Path<String>,
+) -> Response {
+    match ObjectId::parse_str(&event_id) {
+        Ok(event_id) => events_model::get_event(state, event_id).await,
+        Err(_) => error_response("Invalid event ID format", StatusCode::BAD_REQUEST),
+    }
+}
+
+pub async fn refresh_events_service(State(state): State<AppState>) -> Response {
+    events_model::refresh(state).await
+}
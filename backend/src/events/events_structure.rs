@@ -0,0 +1,274 @@
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::hazard::hazard_structure::Severity;
+
+/// GDACS alert level, lowest to highest
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlertLevel {
+    Green,
+    Orange,
+    Red,
+}
+
+impl AlertLevel {
+    const ALL: [AlertLevel; 3] = [AlertLevel::Green, AlertLevel::Orange, AlertLevel::Red];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertLevel::Green => "Green",
+            AlertLevel::Orange => "Orange",
+            AlertLevel::Red => "Red",
+        }
+    }
+
+    pub fn parse(level: &str) -> Option<Self> {
+        AlertLevel::ALL.into_iter().find(|candidate| candidate.as_str().eq_ignore_ascii_case(level.trim()))
+    }
+
+    /// Stored values of this level and every higher one, for `min_alert` filters
+    pub fn at_least(&self) -> Vec<&'static str> {
+        AlertLevel::ALL.iter().filter(|level| *level >= self).map(AlertLevel::as_str).collect()
+    }
+
+    /// GDACS rates expected humanitarian impact as low, medium or high
+    pub fn severity(&self) -> Severity {
+        match self {
+            AlertLevel::Green => Severity::Minor,
+            AlertLevel::Orange => Severity::Moderate,
+            AlertLevel::Red => Severity::Severe,
+        }
+    }
+}
+
+// Feed format of the GDACS event list: a GeoJSON FeatureCollection. Only the fields used
+// are declared, and all of them are lenient since GDACS leaves many empty.
+
+#[derive(Debug, Deserialize)]
+pub struct GdacsFeature {
+    #[serde(default)]
+    pub geometry: Option<GdacsGeometry>,
+    pub properties: GdacsProperties,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GdacsGeometry {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub coordinates: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GdacsProperties {
+    pub eventtype: String,
+    pub eventid: i64,
+    #[serde(default)]
+    pub episodeid: i64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub alertlevel: String,
+    #[serde(default)]
+    pub alertscore: Option<f64>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub iso3: Option<String>,
+    #[serde(default)]
+    pub fromdate: Option<String>,
+    #[serde(default)]
+    pub todate: Option<String>,
+    #[serde(default)]
+    pub datemodified: Option<String>,
+    #[serde(default)]
+    pub iscurrent: Option<serde_json::Value>, // `"true"` or `true` depending on the feed version
+    #[serde(default)]
+    pub severitydata: Option<GdacsSeverity>,
+    #[serde(default)]
+    pub url: Option<GdacsUrls>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GdacsSeverity {
+    #[serde(default)]
+    pub severitytext: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GdacsUrls {
+    #[serde(default)]
+    pub report: Option<String>,
+}
+
+/// Position of an event as a GeoJSON point, `[longitude, latitude]`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EventLocation {
+    #[serde(rename = "type")]
+    pub kind: String, // Always `Point`
+    pub coordinates: [f64; 2],
+}
+
+/// Alert level of an event when it was first seen or whenever it changed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AlertChange {
+    pub alert_level: AlertLevel,
+    #[serde(default)]
+    pub alert_score: Option<f64>,
+    pub episode_id: i64,
+    pub at: DateTime,
+}
+
+/// Event reported by an external feed, stored in the `external_events` collection.
+/// One document per `(source, event_type, event_id)`, updated as new episodes arrive.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExternalEvent {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub source: String, // `gdacs`
+    pub event_type: String, // GDACS type: EQ, FL, TC, VO, DR or WF
+    pub event_id: i64,
+    pub episode_id: i64, // Latest episode seen
+    #[serde(default)]
+    pub episodes: Vec<i64>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub iso3: Option<String>,
+    pub alert_level: AlertLevel,
+    #[serde(default)]
+    pub alert_score: Option<f64>,
+    #[serde(default)]
+    pub severity: Option<Severity>, // Derived from the alert level
+    #[serde(default)]
+    pub severity_text: Option<String>,
+    #[serde(default)]
+    pub hazard: Option<String>, // Taxonomy node aligned with `event_type`
+    #[serde(default)]
+    pub location: Option<EventLocation>,
+    #[serde(default)]
+    pub from_date: Option<DateTime>,
+    #[serde(default)]
+    pub to_date: Option<DateTime>,
+    #[serde(default)]
+    pub modified_at: Option<DateTime>, // As reported by the feed
+    pub is_current: bool,
+    #[serde(default)]
+    pub report_url: Option<String>,
+    #[serde(default)]
+    pub alert_history: Vec<AlertChange>,
+    pub first_seen_at: DateTime,
+    pub last_seen_at: DateTime,
+}
+
+/// Outcome of one poll of the feed
+#[derive(Debug, Serialize, Default)]
+pub struct IngestionReport {
+    pub fetched: usize,
+    pub inserted: usize,
+    pub updated: usize,
+    pub alert_changes: usize,
+    pub stale: usize,   // Older episodes than the one already stored
+    pub invalid: usize, // Features that could not be parsed
+}
+
+/// Query of `GET /events`; pages start at 1
+#[derive(Debug, Deserialize)]
+pub struct EventListQuery {
+    pub event_type: Option<String>, // Comma separated GDACS types
+    pub min_alert: Option<String>,
+    pub hazard: Option<String>, // Taxonomy code; subtypes are included
+    pub country: Option<String>, // ISO 3166-1 alpha-3
+    pub current: Option<bool>, // Defaults to current events only
+    pub since: Option<String>, // RFC 3339; events still going on after this time
+    pub latitude: Option<f64>, // With `longitude` and `radius_km`: events around a point
+    pub longitude: Option<f64>,
+    pub radius_km: Option<f64>,
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AlertChangeView {
+    pub alert_level: AlertLevel,
+    pub alert_score: Option<f64>,
+    pub episode_id: i64,
+    pub at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExternalEventView {
+    pub id: String,
+    pub source: String,
+    pub event_type: String,
+    pub event_id: i64,
+    pub episode_id: i64,
+    pub name: String,
+    pub description: String,
+    pub country: Option<String>,
+    pub iso3: Option<String>,
+    pub alert_level: AlertLevel,
+    pub alert_score: Option<f64>,
+    pub severity: Option<Severity>,
+    pub severity_text: Option<String>,
+    pub hazard: Option<String>,
+    pub location: Option<EventLocation>,
+    pub from_date: Option<String>,
+    pub to_date: Option<String>,
+    pub is_current: bool,
+    pub report_url: Option<String>,
+    pub alert_history: Vec<AlertChangeView>,
+    pub first_seen_at: String,
+    pub last_seen_at: String,
+}
+
+impl From<ExternalEvent> for ExternalEventView {
+    fn from(event: ExternalEvent) -> Self {
+        let date = |at: DateTime| at.try_to_rfc3339_string().unwrap_or_default();
+        ExternalEventView {
+            id: event.id.map(|id| id.to_hex()).unwrap_or_default(),
+            source: event.source,
+            event_type: event.event_type,
+            event_id: event.event_id,
+            episode_id: event.episode_id,
+            name: event.name,
+            description: event.description,
+            country: event.country,
+            iso3: event.iso3,
+            alert_level: event.alert_level,
+            alert_score: event.alert_score,
+            severity: event.severity,
+            severity_text: event.severity_text,
+            hazard: event.hazard,
+            location: event.location,
+            from_date: event.from_date.map(date),
+            to_date: event.to_date.map(date),
+            is_current: event.is_current,
+            report_url: event.report_url,
+            alert_history: event
+                .alert_history
+                .into_iter()
+                .map(|change| AlertChangeView {
+                    alert_level: change.alert_level,
+                    alert_score: change.alert_score,
+                    episode_id: change.episode_id,
+                    at: date(change.at),
+                })
+                .collect(),
+            first_seen_at: date(event.first_seen_at),
+            last_seen_at: date(event.last_seen_at),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventPage {
+    pub events: Vec<ExternalEventView>,
+    pub page: u64,
+    pub per_page: i64,
+    pub total: u64,
+}
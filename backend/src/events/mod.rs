@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use axum::{middleware::{from_fn, from_fn_with_state}, routing::{get, post}, Extension, Router};
+use events_service::{get_event_service, list_events_service, refresh_events_service};
+
+use crate::{citizen::citizen_structure::CitizenRoute, middleware::{auth::auth_middleware, permission::{require_permission, Permission}}, utils::db::AppState};
+
+pub mod events_model;
+pub mod events_service;
+pub mod events_structure;
+
+/// Events ingested from GDACS. Anyone signed in, citizens included, can query them;
+/// forcing a poll takes the permission to manage disaster records.
+pub fn events_routes(state: Arc<AppState>) -> Router {
+    let manage = Router::new()
+        .route("/refresh", post(refresh_events_service))
+        .route_layer(from_fn_with_state(Permission::ManageDisasters, require_permission))
+        .layer(from_fn(auth_middleware));
+
+    let follow = Router::new()
+        .route("/", get(list_events_service))
+        .route("/{event_id}", get(get_event_service))
+        .layer(from_fn(auth_middleware))
+        .layer(Extension(CitizenRoute));
+
+    manage.merge(follow).with_state((*state).clone())
+}
@@ -0,0 +1,419 @@
+//! Managed hazard taxonomy. A default taxonomy aligned with the GDACS event types is seeded
+//! once into an empty `hazard_taxonomy` collection; from then on admins maintain it themselves.
+
+use std::collections::HashMap;
+
+use axum::{http::StatusCode, response::Response};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Bson, DateTime, Document},
+    Collection, Database,
+};
+use tokio::sync::OnceCell;
+
+use super::hazard_structure::{CreateHazardRequest, HazardNode, HazardTree, UpdateHazardRequest, GDACS_TYPES};
+use crate::utils::{
+    db::AppState,
+    response::{error_response, success_response},
+};
+
+/// `(code, name, parent, GDACS type)` of the taxonomy seeded on first use
+const DEFAULT_TAXONOMY: &[(&str, &str, Option<&str>, Option<&str>)] = &[
+    ("geophysical", "Geophysical", None, None),
+    ("earthquake", "Earthquake", Some("geophysical"), Some("EQ")),
+    ("volcanic_activity", "Volcanic activity", Some("geophysical"), Some("VO")),
+    ("tsunami", "Tsunami", Some("geophysical"), None),
+    ("dry_mass_movement", "Mass movement (dry)", Some("geophysical"), None),
+    ("hydrological", "Hydrological", None, None),
+    ("flood", "Flood", Some("hydrological"), Some("FL")),
+    ("flash_flood", "Flash flood", Some("flood"), None),
+    ("riverine_flood", "Riverine flood", Some("flood"), None),
+    ("coastal_flood", "Coastal flood", Some("flood"), None),
+    ("landslide", "Landslide", Some("hydrological"), None),
+    ("meteorological", "Meteorological", None, None),
+    ("tropical_cyclone", "Tropical cyclone", Some("meteorological"), Some("TC")),
+    ("storm", "Storm", Some("meteorological"), None),
+    ("extreme_temperature", "Extreme temperature", Some("meteorological"), None),
+    ("heat_wave", "Heat wave", Some("extreme_temperature"), None),
+    ("cold_wave", "Cold wave", Some("extreme_temperature"), None),
+    ("climatological", "Climatological", None, None),
+    ("drought", "Drought", Some("climatological"), Some("DR")),
+    ("wildfire", "Wildfire", Some("climatological"), Some("WF")),
+];
+
+/// Checked once per process; the `seeds` marker makes it once per database
+static SEEDED: OnceCell<()> = OnceCell::const_new();
+
+/// Lowercased code, or `None` unless it is a slug of letters, digits and underscores
+pub fn normalize_code(code: &str) -> Option<String> {
+    let code = code.trim().to_lowercase();
+    let valid = (2..=64).contains(&code.len()) && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some(code)
+}
+
+fn normalize_gdacs_type(gdacs_type: &str) -> Option<String> {
+    let gdacs_type = gdacs_type.trim().to_uppercase();
+    GDACS_TYPES.contains(&gdacs_type.as_str()).then_some(gdacs_type)
+}
+
+fn default_taxonomy() -> Vec<HazardNode> {
+    DEFAULT_TAXONOMY
+        .iter()
+        .map(|(code, name, parent, gdacs_type)| HazardNode {
+            code: code.to_string(),
+            name: name.to_string(),
+            parent: parent.map(str::to_string),
+            gdacs_type: gdacs_type.map(str::to_string),
+            description: String::new(),
+        })
+        .collect()
+}
+
+/// Seeds the default taxonomy the first time the database is used. A marker in `seeds` records
+/// that it happened, so a taxonomy the admins emptied on purpose is not seeded again.
+async fn seed_taxonomy(database: &Database, nodes: &Collection<HazardNode>) -> mongodb::error::Result<()> {
+    SEEDED
+        .get_or_try_init(|| async {
+            let marker = database
+                .collection::<Document>("seeds")
+                .update_one(
+                    doc! { "_id": "hazard_taxonomy" },
+                    doc! { "$setOnInsert": { "seeded_at": DateTime::now() } },
+                )
+                .upsert(true)
+                .await?;
+
+            // Databases that predate the marker already hold their taxonomy
+            if marker.upserted_id.is_some() && nodes.estimated_document_count().await? == 0 {
+                if let Err(e) = nodes.insert_many(default_taxonomy()).ordered(false).await {
+                    // Try again on the next read rather than leave a partial taxonomy for good
+                    database.collection::<Document>("seeds").delete_one(doc! { "_id": "hazard_taxonomy" }).await?;
+                    return Err(e);
+                }
+            }
+            mongodb::error::Result::Ok(())
+        })
+        .await
+        .map(|_| ())
+}
+
+/// Every taxonomy node, seeding the default taxonomy on first use
+pub async fn taxonomy(database: &Database) -> mongodb::error::Result<Vec<HazardNode>> {
+    let nodes: Collection<HazardNode> = database.collection("hazard_taxonomy");
+    seed_taxonomy(database, &nodes).await?;
+
+    nodes.find(doc! {}).sort(doc! { "name": 1 }).await?.try_collect().await
+}
+
+/// `code` and the codes of all its descendants, or `None` if there is no such node
+pub fn subtree(nodes: &[HazardNode], code: &str) -> Option<Vec<String>> {
+    nodes.iter().find(|node| node.code == code)?;
+
+    let mut codes = vec![code.to_string()];
+    let mut index = 0;
+    while index < codes.len() {
+        let parent = codes[index].clone();
+        codes.extend(nodes.iter().filter(|node| node.parent.as_deref() == Some(&parent)).map(|node| node.code.clone()));
+        index += 1;
+    }
+    Some(codes)
+}
+
+/// Codes covered by a hazard filter, loading the taxonomy; `Ok(None)` if the node does not exist
+pub async fn hazard_filter(database: &Database, code: &str) -> mongodb::error::Result<Option<Vec<String>>> {
+    let Some(code) = normalize_code(code) else { return Ok(None) };
+    Ok(subtree(&taxonomy(database).await?, &code))
+}
+
+/// Whether `code` names an existing node
+pub async fn hazard_exists(database: &Database, code: &str) -> mongodb::error::Result<bool> {
+    Ok(taxonomy(database).await?.iter().any(|node| node.code == code))
+}
+
+/// Node aligned with a GDACS event type, preferring the most general one
+pub fn node_for_gdacs_type<'a>(nodes: &'a [HazardNode], gdacs_type: &str) -> Option<&'a HazardNode> {
+    nodes
+        .iter()
+        .filter(|node| node.gdacs_type.as_deref().is_some_and(|node_type| node_type.eq_ignore_ascii_case(gdacs_type)))
+        .min_by_key(|node| depth(nodes, node))
+}
+
+/// Number of ancestors of `node`; bounded so a corrupted cycle cannot loop forever
+fn depth(nodes: &[HazardNode], node: &HazardNode) -> usize {
+    let mut depth = 0;
+    let mut parent = node.parent.as_deref();
+    while let Some(code) = parent.filter(|_| depth < nodes.len()) {
+        depth += 1;
+        parent = nodes.iter().find(|node| node.code == code).and_then(|node| node.parent.as_deref());
+    }
+    depth
+}
+
+fn build_tree(nodes: &[HazardNode], direct: &HashMap<String, u64>, parent: Option<&str>) -> Vec<HazardTree> {
+    nodes
+        .iter()
+        .filter(|node| node.parent.as_deref() == parent)
+        .map(|node| {
+            let children = build_tree(nodes, direct, Some(&node.code));
+            HazardTree {
+                code: node.code.clone(),
+                name: node.name.clone(),
+                gdacs_type: node.gdacs_type.clone(),
+                description: node.description.clone(),
+                disasters: direct.get(&node.code).copied().unwrap_or(0) + children.iter().map(|child| child.disasters).sum::<u64>(),
+                children,
+            }
+        })
+        .collect()
+}
+
+pub async fn get_taxonomy(state: AppState) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let loaded = async {
+        let nodes = taxonomy(&database).await?;
+        let counts: Vec<Document> = database
+            .collection::<Document>("disaster_record")
+            .aggregate(vec![
+                doc! { "$match": { "hazard": { "$type": "string" } } },
+                doc! { "$group": { "_id": "$hazard", "count": { "$sum": 1 } } },
+            ])
+            .await?
+            .try_collect()
+            .await?;
+        mongodb::error::Result::Ok((nodes, counts))
+    };
+
+    match loaded.await {
+        Ok((nodes, counts)) => {
+            let direct: HashMap<String, u64> = counts
+                .iter()
+                .filter_map(|count| {
+                    let records = match count.get("count")? {
+                        Bson::Int32(records) => *records as u64,
+                        Bson::Int64(records) => *records as u64,
+                        _ => return None,
+                    };
+                    Some((count.get_str("_id").ok()?.to_string(), records))
+                })
+                .collect();
+            success_response("Hazard taxonomy retrieved successfully", build_tree(&nodes, &direct, None), StatusCode::OK)
+        }
+        Err(e) => error_response(&format!("Failed to fetch hazard taxonomy: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn create_hazard(state: AppState, request: CreateHazardRequest) -> Response {
+    let Some(code) = normalize_code(&request.code) else {
+        return error_response("Code must be 2-64 letters, digits or underscores", StatusCode::BAD_REQUEST);
+    };
+    let gdacs_type = match request.gdacs_type.as_deref().map(normalize_gdacs_type) {
+        Some(None) => return error_response(&format!("GDACS type must be one of: {}", GDACS_TYPES.join(", ")), StatusCode::BAD_REQUEST),
+        Some(gdacs_type) => gdacs_type,
+        None => None,
+    };
+    let parent = match request.parent.as_deref().map(normalize_code) {
+        Some(None) => return error_response("Parent hazard not found", StatusCode::BAD_REQUEST),
+        Some(parent) => parent,
+        None => None,
+    };
+
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let nodes = match taxonomy(&database).await {
+        Ok(nodes) => nodes,
+        Err(e) => return error_response(&format!("Failed to fetch hazard taxonomy: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    if nodes.iter().any(|node| node.code == code) {
+        return error_response("A hazard with this code already exists", StatusCode::CONFLICT);
+    }
+    if parent.as_ref().is_some_and(|parent| !nodes.iter().any(|node| node.code == *parent)) {
+        return error_response("Parent hazard not found", StatusCode::BAD_REQUEST);
+    }
+
+    let node = HazardNode { code, name: request.name.trim().to_string(), parent, gdacs_type, description: request.description };
+    match database.collection::<HazardNode>("hazard_taxonomy").insert_one(&node).await {
+        Ok(_) => success_response("Hazard created successfully", node, StatusCode::CREATED),
+        Err(e) => error_response(&format!("Failed to create hazard: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn update_hazard(state: AppState, code: String, request: UpdateHazardRequest) -> Response {
+    let mut set = Document::new();
+    if let Some(name) = request.name {
+        set.insert("name", name.trim());
+    }
+    if let Some(description) = request.description {
+        set.insert("description", description);
+    }
+    match request.gdacs_type.as_deref().map(str::trim) {
+        Some("") => {
+            set.insert("gdacs_type", Bson::Null);
+        }
+        Some(gdacs_type) => match normalize_gdacs_type(gdacs_type) {
+            Some(gdacs_type) => {
+                set.insert("gdacs_type", gdacs_type);
+            }
+            None => return error_response(&format!("GDACS type must be one of: {}", GDACS_TYPES.join(", ")), StatusCode::BAD_REQUEST),
+        },
+        None => {}
+    }
+
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let nodes = match taxonomy(&database).await {
+        Ok(nodes) => nodes,
+        Err(e) => return error_response(&format!("Failed to fetch hazard taxonomy: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    let Some(descendants) = subtree(&nodes, &code) else {
+        return error_response("Hazard not found", StatusCode::NOT_FOUND);
+    };
+
+    match request.parent.as_deref().map(str::trim) {
+        Some("") => {
+            set.insert("parent", Bson::Null);
+        }
+        Some(parent) => {
+            let parent = parent.to_lowercase();
+            if !nodes.iter().any(|node| node.code == parent) {
+                return error_response("Parent hazard not found", StatusCode::BAD_REQUEST);
+            }
+            if descendants.contains(&parent) {
+                return error_response("A hazard cannot be moved below itself", StatusCode::BAD_REQUEST);
+            }
+            set.insert("parent", parent);
+        }
+        None => {}
+    }
+    if set.is_empty() {
+        return error_response("Nothing to update", StatusCode::BAD_REQUEST);
+    }
+
+    let hazards: Collection<HazardNode> = database.collection("hazard_taxonomy");
+    match hazards
+        .find_one_and_update(doc! { "_id": &code }, doc! { "$set": set })
+        .return_document(mongodb::options::ReturnDocument::After)
+        .await
+    {
+        Ok(Some(node)) => success_response("Hazard updated successfully", node, StatusCode::OK),
+        Ok(None) => error_response("Hazard not found", StatusCode::NOT_FOUND),
+        Err(e) => error_response(&format!("Failed to update hazard: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Removes a leaf node no disaster record or incident is filed under. Ingested events follow the
+/// taxonomy, so those under the node move to whichever node their GDACS type maps to once it is gone.
+pub async fn delete_hazard(state: AppState, code: String) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let hazards: Collection<HazardNode> = database.collection("hazard_taxonomy");
+
+    let in_use = async {
+        let children = hazards.count_documents(doc! { "parent": &code }).await?;
+        let records = database.collection::<Document>("disaster_record").count_documents(doc! { "hazard": &code }).await?;
+        let incidents = database.collection::<Document>("incidents").count_documents(doc! { "hazard": &code }).await?;
+        mongodb::error::Result::Ok((children, records, incidents))
+    };
+    match in_use.await {
+        Ok((children, _, _)) if children > 0 => {
+            return error_response("Move or delete the hazard's subtypes first", StatusCode::CONFLICT)
+        }
+        Ok((_, records, _)) if records > 0 => {
+            return error_response(&format!("{} disaster records are filed under this hazard", records), StatusCode::CONFLICT)
+        }
+        Ok((_, _, incidents)) if incidents > 0 => {
+            return error_response(&format!("{} incidents are filed under this hazard", incidents), StatusCode::CONFLICT)
+        }
+        Ok(_) => {}
+        Err(e) => return error_response(&format!("Failed to check hazard usage: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    match hazards.delete_one(doc! { "_id": &code }).await {
+        Ok(result) if result.deleted_count == 0 => return error_response("Hazard not found", StatusCode::NOT_FOUND),
+        Ok(_) => {}
+        Err(e) => return error_response(&format!("Failed to delete hazard: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let remap = async {
+        let remaining = taxonomy(&database).await?;
+        let events = database.collection::<Document>("external_events");
+        for gdacs_type in GDACS_TYPES {
+            let hazard = node_for_gdacs_type(&remaining, gdacs_type).map(|node| node.code.clone());
+            events
+                .update_many(doc! { "hazard": &code, "event_type": *gdacs_type }, doc! { "$set": { "hazard": hazard } })
+                .await?;
+        }
+        mongodb::error::Result::Ok(())
+    };
+    match remap.await {
+        Ok(()) => success_response("Hazard deleted successfully", code, StatusCode::OK),
+        Err(e) => error_response(&format!("Hazard deleted but its events could not be moved: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(code: &str, parent: Option<&str>, gdacs_type: Option<&str>) -> HazardNode {
+        HazardNode {
+            code: code.to_string(),
+            name: code.to_string(),
+            parent: parent.map(str::to_string),
+            gdacs_type: gdacs_type.map(str::to_string),
+            description: String::new(),
+        }
+    }
+
+    fn find<'a>(nodes: &'a [HazardNode], code: &str) -> &'a HazardNode {
+        nodes.iter().find(|node| node.code == code).unwrap()
+    }
+
+    #[test]
+    fn subtree_lists_a_node_and_all_its_descendants() {
+        let nodes = default_taxonomy();
+        assert_eq!(
+            subtree(&nodes, "hydrological").unwrap(),
+            ["hydrological", "flood", "landslide", "flash_flood", "riverine_flood", "coastal_flood"]
+        );
+        assert_eq!(subtree(&nodes, "heat_wave").unwrap(), ["heat_wave"]);
+        assert_eq!(subtree(&nodes, "meteor_strike"), None);
+    }
+
+    #[test]
+    fn depth_counts_ancestors_and_survives_cycles() {
+        let nodes = default_taxonomy();
+        assert_eq!(depth(&nodes, find(&nodes, "geophysical")), 0);
+        assert_eq!(depth(&nodes, find(&nodes, "flood")), 1);
+        assert_eq!(depth(&nodes, find(&nodes, "flash_flood")), 2);
+
+        let cycle = [node("a", Some("b"), None), node("b", Some("a"), None)];
+        assert!(depth(&cycle, &cycle[0]) <= cycle.len());
+    }
+
+    #[test]
+    fn gdacs_types_map_to_the_most_general_node() {
+        let mut nodes = default_taxonomy();
+        assert_eq!(node_for_gdacs_type(&nodes, "FL").map(|node| node.code.as_str()), Some("flood"));
+        assert_eq!(node_for_gdacs_type(&nodes, "tc").map(|node| node.code.as_str()), Some("tropical_cyclone"));
+        assert!(node_for_gdacs_type(&nodes, "XX").is_none());
+
+        nodes.push(node("urban_flood", Some("flash_flood"), Some("FL")));
+        assert_eq!(node_for_gdacs_type(&nodes, "FL").map(|node| node.code.as_str()), Some("flood"));
+
+        nodes.retain(|node| node.code != "flood");
+        assert_eq!(node_for_gdacs_type(&nodes, "FL").map(|node| node.code.as_str()), Some("urban_flood"));
+    }
+
+    #[test]
+    fn codes_are_lowercase_slugs() {
+        assert_eq!(normalize_code(" Flash_Flood "), Some("flash_flood".to_string()));
+        assert_eq!(normalize_code("x"), None);
+        assert_eq!(normalize_code("flash-flood"), None);
+        assert_eq!(normalize_gdacs_type("eq"), Some("EQ".to_string()));
+        assert_eq!(normalize_gdacs_type("XX"), None);
+    }
+}
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use validator::Validate;
+
+use super::{
+    hazard_model,
+    hazard_structure::{CreateHazardRequest, UpdateHazardRequest},
+};
+use crate::{
+    disaster::{disaster_model, disaster_structure::DisasterListQuery},
+    utils::{db::AppState, response::error_response},
+};
+
+pub async fn get_taxonomy_service(State(state): State<AppState>) -> Response {
+    hazard_model::get_taxonomy(state).await
+}
+
+pub async fn create_hazard_service(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateHazardRequest>,
+) -> Response {
+    if let Err(errors) = payload.validate() {
+        return error_response(&format!("Validation error: {}", errors), StatusCode::BAD_REQUEST);
+    }
+
+    hazard_model::create_hazard(state, payload).await
+}
+
+pub async fn update_hazard_service(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+    Json(payload): Json<UpdateHazardRequest>,
+) -> Response {
+    if let Err(errors) = payload.validate() {
+        return error_response(&format!("Validation error: {}", errors), StatusCode::BAD_REQUEST);
+    }
+
+    hazard_model::update_hazard(state, code.to_lowercase(), payload).await
+}
+
+pub async fn delete_hazard_service(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+) -> Response {
+    hazard_model::delete_hazard(state, code.to_lowercase()).await
+}
+
+/// Disaster records filed under a hazard or any of its subtypes
+pub async fn hazard_disasters_service(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+    Query(mut query): Query<DisasterListQuery>,
+) -> Response {
+    query.hazard = Some(code);
+    disaster_model::list_disasters(State(state), Query(query)).await.into_response()
+}
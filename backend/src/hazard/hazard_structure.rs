@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// GDACS event types a taxonomy node can be aligned with
+pub const GDACS_TYPES: &[&str] = &["EQ", "FL", "TC", "VO", "DR", "WF"];
+
+/// Node of the hazard taxonomy, stored in the `hazard_taxonomy` collection keyed by its code.
+/// Top-level nodes are hazard families (hydrological, geophysical…), children are hazard types
+/// and subtypes. Disaster records reference a node through `DisasterRecord::hazard`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HazardNode {
+    #[serde(rename = "_id")]
+    pub code: String, // Lowercase slug such as `flash_flood`
+    pub name: String,
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub gdacs_type: Option<String>, // One of `GDACS_TYPES`
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Severity of a disaster record, following the CAP scale
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Minor,
+    Moderate,
+    Severe,
+    Extreme,
+}
+
+impl Severity {
+    const ALL: [Severity; 4] = [Severity::Minor, Severity::Moderate, Severity::Severe, Severity::Extreme];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Minor => "minor",
+            Severity::Moderate => "moderate",
+            Severity::Severe => "severe",
+            Severity::Extreme => "extreme",
+        }
+    }
+
+    /// Stored values of this severity and every higher one, for `min_severity` filters
+    pub fn at_least(&self) -> Vec<&'static str> {
+        Severity::ALL.iter().filter(|severity| *severity >= self).map(Severity::as_str).collect()
+    }
+}
+
+/// Body of `POST /disaster/hazards`
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateHazardRequest {
+    pub code: String,
+    #[validate(length(min = 2, message = "Name must be at least 2 characters long"))]
+    pub name: String,
+    pub parent: Option<String>,
+    pub gdacs_type: Option<String>,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Body of `PATCH /disaster/hazards/{code}`; only the fields present are changed
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateHazardRequest {
+    #[validate(length(min = 2, message = "Name must be at least 2 characters long"))]
+    pub name: Option<String>,
+    pub parent: Option<String>, // Moves the node and its subtree; use `""` to make it top-level
+    pub gdacs_type: Option<String>, // Use `""` to clear
+    pub description: Option<String>,
+}
+
+/// Taxonomy node with its children, as returned by `GET /disaster/hazards`
+#[derive(Debug, Serialize)]
+pub struct HazardTree {
+    pub code: String,
+    pub name: String,
+    pub gdacs_type: Option<String>,
+    pub description: String,
+    pub disasters: u64, // Records filed under this node or any of its descendants
+    pub children: Vec<HazardTree>,
+}
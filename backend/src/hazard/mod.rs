@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use axum::{middleware::{from_fn, from_fn_with_state}, routing::{get, patch, post}, Router};
+use hazard_service::{create_hazard_service, delete_hazard_service, get_taxonomy_service, hazard_disasters_service, update_hazard_service};
+
+use crate::{middleware::{auth::auth_middleware, permission::{require_permission, Permission}}, utils::db::AppState};
+
+pub mod hazard_model;
+pub mod hazard_service;
+pub mod hazard_structure;
+
+/// Hazard taxonomy, nested under `/disaster/hazards`. Everyone signed in can browse it;
+/// changing it takes the same permission as managing disaster records.
+pub fn hazard_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(get_taxonomy_service)
+            .merge(post(create_hazard_service)
+                .route_layer(from_fn_with_state(Permission::ManageDisasters, require_permission))))
+        .route("/{code}", patch(update_hazard_service)
+            .delete(delete_hazard_service)
+            .route_layer(from_fn_with_state(Permission::ManageDisasters, require_permission)))
+        .route("/{code}/disasters", get(hazard_disasters_service))
+        .layer(from_fn(auth_middleware))
+        .with_state((*state).clone())
+}
@@ -0,0 +1,384 @@
+//! Live incidents. Each incident links to the `DisasterRecord` holding guidance for its kind of
+//! event, moves through the `IncidentStatus` lifecycle with every step kept in `history`, and
+//! lists the shelters and resources mobilised for it.
+
+use axum::{http::StatusCode, response::Response};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, to_bson, DateTime, Document},
+    options::{IndexOptions, ReturnDocument},
+    Collection, Database, IndexModel,
+};
+use tokio::sync::OnceCell;
+
+use super::incident_structure::{
+    AreaGeometry, CreateIncidentRequest, Incident, IncidentDetail, IncidentGuidance, IncidentPage, IncidentStatus,
+    IncidentView, Mobilised, StatusChange, StatusChangeRequest, UpdateIncidentRequest,
+};
+use crate::{
+    hazard::hazard_model,
+    middleware::permission::AuthUser,
+    resources::resources_structure::Resource,
+    shelters::shelters_structure::Shelter,
+    utils::{
+        db::AppState,
+        paging,
+        response::{error_response, success_response},
+    },
+};
+
+/// Created on first use rather than at startup, so the server starts without the database
+static AREA_INDEX: OnceCell<()> = OnceCell::const_new();
+
+/// The `incidents` collection, with the 2dsphere index on `area` that point lookups rely on
+async fn incidents(database: &Database) -> mongodb::error::Result<Collection<Incident>> {
+    let incidents: Collection<Incident> = database.collection("incidents");
+    AREA_INDEX
+        .get_or_try_init(|| async {
+            let index = IndexModel::builder()
+                .keys(doc! { "area": "2dsphere" })
+                .options(IndexOptions::builder().name("area_2dsphere".to_string()).build())
+                .build();
+            incidents.create_index(index).await.map(|_| ())
+        })
+        .await?;
+    Ok(incidents)
+}
+
+/// The incident's hazard: `hazard` if it names a taxonomy node, otherwise the one of its record
+async fn resolve_hazard(
+    database: &Database,
+    disaster_id: ObjectId,
+    hazard: Option<&str>,
+) -> Result<Option<String>, (StatusCode, String)> {
+    let db_error = |e: mongodb::error::Error| (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e));
+
+    let record = database
+        .collection::<Document>("disaster_record")
+        .find_one(doc! { "_id": disaster_id })
+        .await
+        .map_err(db_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Disaster record not found".to_string()))?;
+
+    match hazard.map(|hazard| hazard.trim().to_lowercase()) {
+        Some(hazard) => match hazard_model::hazard_exists(database, &hazard).await.map_err(db_error)? {
+            true => Ok(Some(hazard)),
+            false => Err((StatusCode::BAD_REQUEST, format!("Unknown hazard '{}'", hazard))),
+        },
+        None => Ok(record.get_str("hazard").ok().map(str::to_string)),
+    }
+}
+
+pub async fn create_incident(
+    state: AppState,
+    user: AuthUser,
+    disaster_id: ObjectId,
+    started_at: DateTime,
+    request: CreateIncidentRequest,
+) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let hazard = match resolve_hazard(&database, disaster_id, request.hazard.as_deref()).await {
+        Ok(hazard) => hazard,
+        Err((status, message)) => return error_response(&message, status),
+    };
+
+    let now = DateTime::now();
+    let status = request.status.unwrap_or(IncidentStatus::Watch);
+    let incident = Incident {
+        id: None,
+        title: request.title.trim().to_string(),
+        description: request.description,
+        disaster_id,
+        hazard,
+        severity: request.severity,
+        status,
+        started_at,
+        ended_at: None,
+        area: request.area,
+        shelter_ids: Vec::new(),
+        resource_ids: Vec::new(),
+        history: vec![StatusChange { status, at: now, by: user.id, note: None }],
+        created_by: user.id,
+        created_at: now,
+        updated_at: now,
+    };
+
+    let inserted = match incidents(&database).await {
+        Ok(incidents) => incidents.insert_one(&incident).await,
+        Err(e) => Err(e),
+    };
+    match inserted {
+        Ok(result) => success_response(
+            "Incident created successfully",
+            IncidentView::from(Incident { id: result.inserted_id.as_object_id(), ..incident }),
+            StatusCode::CREATED,
+        ),
+        Err(e) => error_response(&format!("Failed to create incident: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Filters of `GET /incidents`, already parsed
+pub struct IncidentFilter {
+    pub statuses: Vec<IncidentStatus>,
+    pub hazard: Option<String>,
+    pub disaster_id: Option<ObjectId>,
+    pub point: Option<(f64, f64)>, // (longitude, latitude)
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+/// Incidents matching the filter, most recently started first
+pub async fn list_incidents(state: AppState, incident_filter: IncidentFilter) -> Response {
+    let (page, per_page) = paging::page_window(incident_filter.page, incident_filter.per_page);
+
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let mut filter = if incident_filter.statuses.is_empty() {
+        doc! { "status": { "$ne": IncidentStatus::Closed.as_str() } }
+    } else {
+        let statuses: Vec<&str> = incident_filter.statuses.iter().map(IncidentStatus::as_str).collect();
+        doc! { "status": { "$in": statuses } }
+    };
+    if let Some(hazard) = incident_filter.hazard.as_deref() {
+        match hazard_model::hazard_filter(&database, hazard).await {
+            Ok(Some(codes)) => {
+                filter.insert("hazard", doc! { "$in": codes });
+            }
+            Ok(None) => return error_response("Hazard not found", StatusCode::NOT_FOUND),
+            Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+    if let Some(disaster_id) = incident_filter.disaster_id {
+        filter.insert("disaster_id", disaster_id);
+    }
+    if let Some((longitude, latitude)) = incident_filter.point {
+        filter.insert(
+            "area",
+            doc! { "$geoIntersects": { "$geometry": { "type": "Point", "coordinates": [longitude, latitude] } } },
+        );
+    }
+
+    let found = async {
+        let incidents = incidents(&database).await?;
+        let total = incidents.count_documents(filter.clone()).await?;
+        let found: Vec<Incident> = incidents
+            .find(filter)
+            .sort(doc! { "started_at": -1, "_id": -1 })
+            .skip(paging::skip(page, per_page))
+            .limit(per_page)
+            .await?
+            .try_collect()
+            .await?;
+        mongodb::error::Result::Ok((total, found))
+    };
+
+    match found.await {
+        Ok((total, found)) => success_response(
+            "Incidents retrieved successfully",
+            IncidentPage { incidents: found.into_iter().map(IncidentView::from).collect(), page, per_page, total },
+            StatusCode::OK,
+        ),
+        Err(e) => error_response(&format!("Failed to fetch incidents: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn get_incident(state: AppState, incident_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let found = async {
+        let Some(incident) = database.collection::<Incident>("incidents").find_one(doc! { "_id": incident_id }).await? else {
+            return Ok(None);
+        };
+        let record = database
+            .collection::<Document>("disaster_record")
+            .find_one(doc! { "_id": incident.disaster_id })
+            .projection(doc! { "name": 1 })
+            .await?;
+        let shelters: Vec<Shelter> = database
+            .collection::<Shelter>("shelters")
+            .find(doc! { "_id": { "$in": &incident.shelter_ids } })
+            .await?
+            .try_collect()
+            .await?;
+        let resources: Vec<Resource> = database
+            .collection::<Resource>("resources")
+            .find(doc! { "_id": { "$in": &incident.resource_ids } })
+            .await?
+            .try_collect()
+            .await?;
+
+        let guidance = IncidentGuidance {
+            disaster_id: incident.disaster_id.to_hex(),
+            name: record.and_then(|record| record.get_str("name").ok().map(str::to_string)),
+        };
+        mongodb::error::Result::Ok(Some(IncidentDetail { incident: incident.into(), guidance, shelters, resources }))
+    };
+
+    match found.await {
+        Ok(Some(detail)) => success_response("Incident retrieved successfully", detail, StatusCode::OK),
+        Ok(None) => error_response("Incident not found", StatusCode::NOT_FOUND),
+        Err(e) => error_response(&format!("Failed to fetch incident: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Applies `update` to an open incident and returns it as stored afterwards
+async fn update_open_incident(database: &Database, incident_id: ObjectId, update: Document) -> Response {
+    let filter = doc! { "_id": incident_id, "status": { "$ne": IncidentStatus::Closed.as_str() } };
+    let updated = match incidents(database).await {
+        Ok(incidents) => incidents.find_one_and_update(filter, update).return_document(ReturnDocument::After).await,
+        Err(e) => Err(e),
+    };
+
+    match updated {
+        Ok(Some(incident)) => success_response("Incident updated successfully", IncidentView::from(incident), StatusCode::OK),
+        Ok(None) => match database.collection::<Document>("incidents").count_documents(doc! { "_id": incident_id }).await {
+            Ok(0) => error_response("Incident not found", StatusCode::NOT_FOUND),
+            _ => error_response("Closed incidents cannot be changed", StatusCode::CONFLICT),
+        },
+        Err(e) => error_response(&format!("Failed to update incident: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn update_incident(
+    state: AppState,
+    incident_id: ObjectId,
+    disaster_id: Option<ObjectId>,
+    started_at: Option<DateTime>,
+    request: UpdateIncidentRequest,
+) -> Response {
+    let mut set = Document::new();
+    if let Some(title) = request.title {
+        set.insert("title", title.trim());
+    }
+    if let Some(description) = request.description {
+        set.insert("description", description);
+    }
+    if let Some(severity) = request.severity {
+        set.insert("severity", severity.as_str());
+    }
+    if let Some(started_at) = started_at {
+        set.insert("started_at", started_at);
+    }
+    if let Some(area) = request.area.as_ref().map(to_bson::<AreaGeometry>) {
+        match area {
+            Ok(area) => {
+                set.insert("area", area);
+            }
+            Err(e) => return error_response(&format!("Invalid area: {}", e), StatusCode::BAD_REQUEST),
+        }
+    }
+    if set.is_empty() && disaster_id.is_none() && request.hazard.is_none() {
+        return error_response("Nothing to update", StatusCode::BAD_REQUEST);
+    }
+
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    // A new record or hazard is checked against the record the incident ends up linked to
+    if disaster_id.is_some() || request.hazard.is_some() {
+        let current = match database.collection::<Incident>("incidents").find_one(doc! { "_id": incident_id }).await {
+            Ok(Some(incident)) => incident,
+            Ok(None) => return error_response("Incident not found", StatusCode::NOT_FOUND),
+            Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        };
+        let linked = disaster_id.unwrap_or(current.disaster_id);
+        let hazard = request.hazard.as_deref().or(if disaster_id.is_some() { None } else { current.hazard.as_deref() });
+        match resolve_hazard(&database, linked, hazard).await {
+            Ok(hazard) => {
+                set.insert("disaster_id", linked);
+                set.insert("hazard", hazard);
+            }
+            Err((status, message)) => return error_response(&message, status),
+        }
+    }
+    set.insert("updated_at", DateTime::now());
+
+    update_open_incident(&database, incident_id, doc! { "$set": set }).await
+}
+
+/// Moves an incident along its lifecycle; closing it records the end time
+pub async fn change_status(state: AppState, user: AuthUser, incident_id: ObjectId, request: StatusChangeRequest) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let current = match database.collection::<Incident>("incidents").find_one(doc! { "_id": incident_id }).await {
+        Ok(Some(incident)) => incident.status,
+        Ok(None) => return error_response("Incident not found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    if !current.can_become(request.status) {
+        return error_response(
+            &format!("An incident cannot go from {} to {}", current.as_str(), request.status.as_str()),
+            StatusCode::CONFLICT,
+        );
+    }
+
+    let now = DateTime::now();
+    let note = request.note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+    let change = StatusChange { status: request.status, at: now, by: user.id, note };
+    let change = match to_bson(&change) {
+        Ok(change) => change,
+        Err(_) => return error_response("Failed to serialize status change", StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let mut set = doc! { "status": request.status.as_str(), "updated_at": now };
+    if request.status == IncidentStatus::Closed {
+        set.insert("ended_at", now);
+    }
+
+    // Matching the status read above keeps concurrent transitions from skipping the checks
+    let filter = doc! { "_id": incident_id, "status": current.as_str() };
+    let update = doc! { "$set": set, "$push": { "history": change } };
+    match database
+        .collection::<Incident>("incidents")
+        .find_one_and_update(filter, update)
+        .return_document(ReturnDocument::After)
+        .await
+    {
+        Ok(Some(incident)) => success_response("Incident status updated successfully", IncidentView::from(incident), StatusCode::OK),
+        Ok(None) => error_response("Incident status changed meanwhile, please retry", StatusCode::CONFLICT),
+        Err(e) => error_response(&format!("Failed to update incident: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Adds a shelter or resource to those mobilised for an open incident
+pub async fn link(state: AppState, incident_id: ObjectId, mobilised: Mobilised, target_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    match database.collection::<Document>(mobilised.collection()).count_documents(doc! { "_id": target_id }).await {
+        Ok(0) => return error_response(&format!("{} not found", mobilised.name()), StatusCode::NOT_FOUND),
+        Ok(_) => {}
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let update = doc! {
+        "$addToSet": { mobilised.field(): target_id },
+        "$set": { "updated_at": DateTime::now() },
+    };
+    update_open_incident(&database, incident_id, update).await
+}
+
+pub async fn unlink(state: AppState, incident_id: ObjectId, mobilised: Mobilised, target_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let update = doc! {
+        "$pull": { mobilised.field(): target_id },
+        "$set": { "updated_at": DateTime::now() },
+    };
+    update_open_incident(&database, incident_id, update).await
+}
+
+/// Drops a deleted shelter or resource from every incident it was mobilised for
+pub async fn forget_mobilised(database: &Database, mobilised: Mobilised, target_id: ObjectId) -> mongodb::error::Result<()> {
+    database
+        .collection::<Document>("incidents")
+        .update_many(doc! { mobilised.field(): target_id }, doc! { "$pull": { mobilised.field(): target_id } })
+        .await
+        .map(|_| ())
+}
@@ -0,0 +1,194 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use validator::Validate;
+
+use super::{
+    incident_model::{self, IncidentFilter},
+    incident_structure::{
+        AreaGeometry, CreateIncidentRequest, IncidentListQuery, IncidentStatus, LinkRequest, Mobilised,
+        StatusChangeRequest, UpdateIncidentRequest,
+    },
+};
+use crate::{
+    middleware::permission::AuthUser,
+    utils::{db::AppState, response::error_response},
+};
+
+fn parse_id(id: &str, what: &str) -> Result<ObjectId, String> {
+    ObjectId::parse_str(id).map_err(|_| format!("Invalid {} ID format", what))
+}
+
+fn parse_time(at: Option<&str>) -> Result<Option<DateTime>, &'static str> {
+    at.map(|at| DateTime::parse_rfc3339_str(at.trim()).map_err(|_| "Times must be RFC 3339, e.g. 2025-01-31T08:00:00Z"))
+        .transpose()
+}
+
+fn area_error(area: Option<&AreaGeometry>) -> Option<&'static str> {
+    area.and_then(AreaGeometry::validation_error)
+}
+
+pub async fn create_incident_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<CreateIncidentRequest>,
+) -> Response {
+    if let Err(errors) = payload.validate() {
+        return error_response(&format!("Validation error: {}", errors), StatusCode::BAD_REQUEST);
+    }
+    if let Some(message) = area_error(payload.area.as_ref()) {
+        return error_response(message, StatusCode::BAD_REQUEST);
+    }
+    if payload.status == Some(IncidentStatus::Closed) || payload.status == Some(IncidentStatus::Recovery) {
+        return error_response("A new incident starts as watch, warning or active", StatusCode::BAD_REQUEST);
+    }
+    let disaster_id = match parse_id(&payload.disaster_id, "disaster record") {
+        Ok(id) => id,
+        Err(message) => return error_response(&message, StatusCode::BAD_REQUEST),
+    };
+    let started_at = match parse_time(payload.started_at.as_deref()) {
+        Ok(started_at) => started_at.unwrap_or_else(DateTime::now),
+        Err(message) => return error_response(message, StatusCode::BAD_REQUEST),
+    };
+
+    incident_model::create_incident(state, user, disaster_id, started_at, payload).await
+}
+
+pub async fn list_incidents_service(
+    State(state): State<AppState>,
+    Query(query): Query<IncidentListQuery>,
+) -> Response {
+    let statuses = match query.status.as_deref() {
+        Some(statuses) => match statuses.split(',').map(IncidentStatus::parse).collect::<Option<Vec<_>>>() {
+            Some(statuses) => statuses,
+            None => return error_response("Status must be watch, warning, active, recovery or closed", StatusCode::BAD_REQUEST),
+        },
+        None => Vec::new(),
+    };
+    let disaster_id = match query.disaster_id.as_deref().map(|id| parse_id(id, "disaster record")).transpose() {
+        Ok(id) => id,
+        Err(message) => return error_response(&message, StatusCode::BAD_REQUEST),
+    };
+    let point = match (query.longitude, query.latitude) {
+        (Some(longitude), Some(latitude)) if (-180.0..=180.0).contains(&longitude) && (-90.0..=90.0).contains(&latitude) => {
+            Some((longitude, latitude))
+        }
+        (None, None) => None,
+        _ => return error_response("Give both latitude and longitude, within range", StatusCode::BAD_REQUEST),
+    };
+
+    let filter = IncidentFilter {
+        statuses,
+        hazard: query.hazard,
+        disaster_id,
+        point,
+        page: query.page,
+        per_page: query.per_page,
+    };
+    incident_model::list_incidents(state, filter).await
+}
+
+pub async fn get_incident_service(
+    State(state): State<AppState>,
+    Path(incident_id): Path<String>,
+) -> Response {
+    match parse_id(&incident_id, "incident") {
+        Ok(incident_id) => incident_model::get_incident(state, incident_id).await,
+        Err(message) => error_response(&message, StatusCode::BAD_REQUEST),
+    }
+}
+
+pub async fn update_incident_service(
+    State(state): State<AppState>,
+    Path(incident_id): Path<String>,
+    Json(payload): Json<UpdateIncidentRequest>,
+) -> Response {
+    if let Err(errors) = payload.validate() {
+        return error_response(&format!("Validation error: {}", errors), StatusCode::BAD_REQUEST);
+    }
+    if let Some(message) = area_error(payload.area.as_ref()) {
+        return error_response(message, StatusCode::BAD_REQUEST);
+    }
+    let incident_id = match parse_id(&incident_id, "incident") {
+        Ok(id) => id,
+        Err(message) => return error_response(&message, StatusCode::BAD_REQUEST),
+    };
+    let disaster_id = match payload.disaster_id.as_deref().map(|id| parse_id(id, "disaster record")).transpose() {
+        Ok(id) => id,
+        Err(message) => return error_response(&message, StatusCode::BAD_REQUEST),
+    };
+    let started_at = match parse_time(payload.started_at.as_deref()) {
+        Ok(started_at) => started_at,
+        Err(message) => return error_response(message, StatusCode::BAD_REQUEST),
+    };
+
+    incident_model::update_incident(state, incident_id, disaster_id, started_at, payload).await
+}
+
+pub async fn change_status_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(incident_id): Path<String>,
+    Json(payload): Json<StatusChangeRequest>,
+) -> Response {
+    match parse_id(&incident_id, "incident") {
+        Ok(incident_id) => incident_model::change_status(state, user, incident_id, payload).await,
+        Err(message) => error_response(&message, StatusCode::BAD_REQUEST),
+    }
+}
+
+async fn link_service(state: AppState, incident_id: &str, mobilised: Mobilised, target_id: &str) -> Response {
+    let incident_id = match parse_id(incident_id, "incident") {
+        Ok(id) => id,
+        Err(message) => return error_response(&message, StatusCode::BAD_REQUEST),
+    };
+    match parse_id(target_id, &mobilised.name().to_lowercase()) {
+        Ok(target_id) => incident_model::link(state, incident_id, mobilised, target_id).await,
+        Err(message) => error_response(&message, StatusCode::BAD_REQUEST),
+    }
+}
+
+async fn unlink_service(state: AppState, incident_id: &str, mobilised: Mobilised, target_id: &str) -> Response {
+    let incident_id = match parse_id(incident_id, "incident") {
+        Ok(id) => id,
+        Err(message) => return error_response(&message, StatusCode::BAD_REQUEST),
+    };
+    match parse_id(target_id, &mobilised.name().to_lowercase()) {
+        Ok(target_id) => incident_model::unlink(state, incident_id, mobilised, target_id).await,
+        Err(message) => error_response(&message, StatusCode::BAD_REQUEST),
+    }
+}
+
+pub async fn link_shelter_service(
+    State(state): State<AppState>,
+    Path(incident_id): Path<String>,
+    Json(payload): Json<LinkRequest>,
+) -> Response {
+    link_service(state, &incident_id, Mobilised::Shelter, &payload.id).await
+}
+
+pub async fn unlink_shelter_service(
+    State(state): State<AppState>,
+    Path((incident_id, shelter_id)): Path<(String, String)>,
+) -> Response {
+    unlink_service(state, &incident_id, Mobilised::Shelter, &shelter_id).await
+}
+
+pub async fn link_resource_service(
+    State(state): State<AppState>,
+    Path(incident_id): Path<String>,
+    Json(payload): Json<LinkRequest>,
+) -> Response {
+    link_service(state, &incident_id, Mobilised::Resource, &payload.id).await
+}
+
+pub async fn unlink_resource_service(
+    State(state): State<AppState>,
+    Path((incident_id, resource_id)): Path<(String, String)>,
+) -> Response {
+    unlink_service(state, &incident_id, Mobilised::Resource, &resource_id).await
+}
@@ -0,0 +1,297 @@
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    hazard::hazard_structure::Severity,
+    resources::resources_structure::Resource,
+    shelters::shelters_structure::Shelter,
+};
+
+/// Lifecycle of an incident. Watch, warning and active can follow each other as the situation
+/// develops; recovery only follows active, and closed is final.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IncidentStatus {
+    Watch,
+    Warning,
+    Active,
+    Recovery,
+    Closed,
+}
+
+impl IncidentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncidentStatus::Watch => "watch",
+            IncidentStatus::Warning => "warning",
+            IncidentStatus::Active => "active",
+            IncidentStatus::Recovery => "recovery",
+            IncidentStatus::Closed => "closed",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        match status.trim().to_lowercase().as_str() {
+            "watch" => Some(IncidentStatus::Watch),
+            "warning" => Some(IncidentStatus::Warning),
+            "active" => Some(IncidentStatus::Active),
+            "recovery" => Some(IncidentStatus::Recovery),
+            "closed" => Some(IncidentStatus::Closed),
+            _ => None,
+        }
+    }
+
+    pub fn can_become(&self, next: IncidentStatus) -> bool {
+        use IncidentStatus::*;
+        match (self, next) {
+            (Closed, _) => false,
+            (_, Closed) => true,
+            (Watch | Warning | Active, Watch | Warning | Active) => *self != next,
+            (Active, Recovery) | (Recovery, Active) => true,
+            _ => false,
+        }
+    }
+}
+
+/// GeoJSON geometry of the affected area, `[longitude, latitude]` positions
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum AreaGeometry {
+    Polygon { coordinates: Vec<Vec<[f64; 2]>> },
+    MultiPolygon { coordinates: Vec<Vec<Vec<[f64; 2]>>> },
+}
+
+impl AreaGeometry {
+    /// Problem with the geometry, if any: every ring needs at least four valid positions
+    /// and must end where it starts
+    pub fn validation_error(&self) -> Option<&'static str> {
+        let polygons: Vec<&Vec<Vec<[f64; 2]>>> = match self {
+            AreaGeometry::Polygon { coordinates } => vec![coordinates],
+            AreaGeometry::MultiPolygon { coordinates } => coordinates.iter().collect(),
+        };
+        if polygons.is_empty() || polygons.iter().any(|rings| rings.is_empty()) {
+            return Some("Area must contain at least one ring");
+        }
+
+        for ring in polygons.into_iter().flatten() {
+            if ring.len() < 4 {
+                return Some("Area rings need at least four positions");
+            }
+            if ring.first() != ring.last() {
+                return Some("Area rings must be closed");
+            }
+            let valid = ring
+                .iter()
+                .all(|[longitude, latitude]| (-180.0..=180.0).contains(longitude) && (-90.0..=90.0).contains(latitude));
+            if !valid {
+                return Some("Area positions must be [longitude, latitude] within range");
+            }
+        }
+        None
+    }
+}
+
+/// One step of an incident's lifecycle
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatusChange {
+    pub status: IncidentStatus,
+    pub at: DateTime,
+    pub by: ObjectId,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Event happening now, stored in the `incidents` collection. Reference content about the kind
+/// of disaster stays in `disaster_record`; an incident links to that record for its guidance.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Incident {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub disaster_id: ObjectId, // `DisasterRecord` with guidance for this kind of event
+    #[serde(default)]
+    pub hazard: Option<String>, // Taxonomy code, taken from the disaster record unless given
+    #[serde(default)]
+    pub severity: Option<Severity>,
+    pub status: IncidentStatus,
+    pub started_at: DateTime,
+    #[serde(default)]
+    pub ended_at: Option<DateTime>, // Set when the incident is closed
+    #[serde(default)]
+    pub area: Option<AreaGeometry>,
+    #[serde(default)]
+    pub shelter_ids: Vec<ObjectId>,
+    #[serde(default)]
+    pub resource_ids: Vec<ObjectId>,
+    #[serde(default)]
+    pub history: Vec<StatusChange>,
+    pub created_by: ObjectId,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+/// Body of `POST /incidents`
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateIncidentRequest {
+    #[validate(length(min = 3, message = "Title must be at least 3 characters long"))]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub disaster_id: String,
+    pub hazard: Option<String>,
+    pub severity: Option<Severity>,
+    pub status: Option<IncidentStatus>, // Defaults to `watch`; cannot start closed
+    pub started_at: Option<String>,     // RFC 3339, defaults to now
+    pub area: Option<AreaGeometry>,
+}
+
+/// Body of `PATCH /incidents/{id}`; only the fields present are changed
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateIncidentRequest {
+    #[validate(length(min = 3, message = "Title must be at least 3 characters long"))]
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub disaster_id: Option<String>,
+    pub hazard: Option<String>,
+    pub severity: Option<Severity>,
+    pub started_at: Option<String>,
+    pub area: Option<AreaGeometry>,
+}
+
+/// Body of `POST /incidents/{id}/status`
+#[derive(Debug, Deserialize)]
+pub struct StatusChangeRequest {
+    pub status: IncidentStatus,
+    pub note: Option<String>,
+}
+
+/// Body of `POST /incidents/{id}/shelters` and `/resources`
+#[derive(Debug, Deserialize)]
+pub struct LinkRequest {
+    pub id: String,
+}
+
+/// Query of `GET /incidents`; open incidents only unless `status` asks for closed ones
+#[derive(Debug, Deserialize)]
+pub struct IncidentListQuery {
+    pub status: Option<String>, // Comma separated
+    pub hazard: Option<String>, // Taxonomy code; subtypes are included
+    pub disaster_id: Option<String>,
+    pub latitude: Option<f64>,  // With `longitude`: incidents whose area covers the point
+    pub longitude: Option<f64>,
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusChangeView {
+    pub status: IncidentStatus,
+    pub at: String,
+    pub by: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IncidentView {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub disaster_id: String,
+    pub hazard: Option<String>,
+    pub severity: Option<Severity>,
+    pub status: IncidentStatus,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub area: Option<AreaGeometry>,
+    pub shelter_ids: Vec<String>,
+    pub resource_ids: Vec<String>,
+    pub history: Vec<StatusChangeView>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<Incident> for IncidentView {
+    fn from(incident: Incident) -> Self {
+        let date = |at: DateTime| at.try_to_rfc3339_string().unwrap_or_default();
+        IncidentView {
+            id: incident.id.map(|id| id.to_hex()).unwrap_or_default(),
+            title: incident.title,
+            description: incident.description,
+            disaster_id: incident.disaster_id.to_hex(),
+            hazard: incident.hazard,
+            severity: incident.severity,
+            status: incident.status,
+            started_at: date(incident.started_at),
+            ended_at: incident.ended_at.map(date),
+            area: incident.area,
+            shelter_ids: incident.shelter_ids.into_iter().map(ObjectId::to_hex).collect(),
+            resource_ids: incident.resource_ids.into_iter().map(ObjectId::to_hex).collect(),
+            history: incident
+                .history
+                .into_iter()
+                .map(|change| StatusChangeView { status: change.status, at: date(change.at), by: change.by.to_hex(), note: change.note })
+                .collect(),
+            created_at: date(incident.created_at),
+            updated_at: date(incident.updated_at),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct IncidentPage {
+    pub incidents: Vec<IncidentView>,
+    pub page: u64,
+    pub per_page: i64,
+    pub total: u64,
+}
+
+/// Disaster record an incident links to, for its guidance
+#[derive(Debug, Serialize)]
+pub struct IncidentGuidance {
+    pub disaster_id: String,
+    pub name: Option<String>, // Missing if the record no longer exists
+}
+
+/// `GET /incidents/{id}`: the incident with its linked shelters and resources resolved
+#[derive(Debug, Serialize)]
+pub struct IncidentDetail {
+    #[serde(flatten)]
+    pub incident: IncidentView,
+    pub guidance: IncidentGuidance,
+    pub shelters: Vec<Shelter>,
+    pub resources: Vec<Resource>,
+}
+
+/// What can be mobilised for an incident
+#[derive(Debug, Clone, Copy)]
+pub enum Mobilised {
+    Shelter,
+    Resource,
+}
+
+impl Mobilised {
+    pub fn collection(&self) -> &'static str {
+        match self {
+            Mobilised::Shelter => "shelters",
+            Mobilised::Resource => "resources",
+        }
+    }
+
+    /// Field of `Incident` holding the links
+    pub fn field(&self) -> &'static str {
+        match self {
+            Mobilised::Shelter => "shelter_ids",
+            Mobilised::Resource => "resource_ids",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mobilised::Shelter => "Shelter",
+            Mobilised::Resource => "Resource",
+        }
+    }
+}
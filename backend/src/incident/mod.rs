@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use axum::{middleware::{from_fn, from_fn_with_state}, routing::{delete, get, patch, post}, Extension, Router};
+use incident_service::{change_status_service, create_incident_service, get_incident_service, link_resource_service, link_shelter_service, list_incidents_service, unlink_resource_service, unlink_shelter_service, update_incident_service};
+
+use crate::{citizen::citizen_structure::CitizenRoute, middleware::{auth::auth_middleware, permission::{require_permission, Permission}}, utils::db::AppState};
+
+pub mod incident_model;
+pub mod incident_service;
+pub mod incident_structure;
+
+/// Live incidents. Anyone signed in, citizens included, can follow them;
+/// managing them takes the same permission as managing disaster records.
+pub fn incident_routes(state: Arc<AppState>) -> Router {
+    let manage = Router::new()
+        .route("/", post(create_incident_service))
+        .route("/{incident_id}", patch(update_incident_service))
+        .route("/{incident_id}/status", post(change_status_service))
+        .route("/{incident_id}/shelters", post(link_shelter_service))
+        .route("/{incident_id}/shelters/{shelter_id}", delete(unlink_shelter_service))
+        .route("/{incident_id}/resources", post(link_resource_service))
+        .route("/{incident_id}/resources/{resource_id}", delete(unlink_resource_service))
+        .route_layer(from_fn_with_state(Permission::ManageDisasters, require_permission))
+        .layer(from_fn(auth_middleware));
+
+    let follow = Router::new()
+        .route("/", get(list_incidents_service))
+        .route("/{incident_id}", get(get_incident_service))
+        .layer(from_fn(auth_middleware))
+        .layer(Extension(CitizenRoute));
+
+    manage.merge(follow).with_state((*state).clone())
+}
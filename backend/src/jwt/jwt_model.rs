@@ -0,0 +1,245 @@
+use std::{env, fs};
+
+use data_encoding::BASE64URL_NOPAD;
+use jsonwebtoken::{
+    decode, decode_header, encode,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm, OctetKeyPairParameters,
+        OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use mongodb::bson::oid::ObjectId;
+use sha2::{Digest, Sha256};
+use simple_asn1::{from_der, ASN1Block};
+
+use super::jwt_structure::{JwtKeys, VerificationKey};
+use crate::{user::user_structure::Claims, utils::crypto::generate_token};
+
+const DEFAULT_ISSUER: &str = "disaster-management";
+const DEFAULT_AUDIENCE: &str = "disaster-management-api";
+const RSA_ENCRYPTION_OID: [u64; 7] = [1, 2, 840, 113549, 1, 1, 1];
+const ED25519_OID: [u64; 4] = [1, 3, 101, 112];
+
+/// Key id derived from the key material, so rotating keys never needs a manual `kid`
+fn derive_kid(material: &[u8]) -> String {
+    hex::encode(&Sha256::digest(material)[..8])
+}
+
+fn comma_list(name: &str) -> Vec<String> {
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn read_file(path: &str) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|e| panic!("Failed to read JWT key file {}: {}", path, e))
+}
+
+/// Turns a public key PEM (SPKI `PUBLIC KEY`, or PKCS#1 `RSA PUBLIC KEY`) into a JWK
+fn public_key_jwk(pem_bytes: &[u8]) -> Result<(Algorithm, Jwk), String> {
+    let pem = pem::parse(pem_bytes).map_err(|e| e.to_string())?;
+    let blocks = from_der(pem.contents()).map_err(|e| e.to_string())?;
+
+    let (algorithm, algorithm_parameters) = match (pem.tag(), blocks.first()) {
+        ("RSA PUBLIC KEY", Some(block)) => (Algorithm::RS256, rsa_parameters(block)?),
+        ("PUBLIC KEY", Some(ASN1Block::Sequence(_, spki))) => match spki.as_slice() {
+            [ASN1Block::Sequence(_, algorithm_id), ASN1Block::BitString(_, _, key)] => {
+                let oid = match algorithm_id.first() {
+                    Some(ASN1Block::ObjectIdentifier(_, oid)) => oid.as_vec::<u64>().map_err(|e| e.to_string())?,
+                    _ => return Err("missing key algorithm".to_string()),
+                };
+
+                if oid == RSA_ENCRYPTION_OID {
+                    let rsa_key = from_der(key).map_err(|e| e.to_string())?;
+                    let block = rsa_key.first().ok_or("empty RSA key")?;
+                    (Algorithm::RS256, rsa_parameters(block)?)
+                } else if oid == ED25519_OID {
+                    let parameters = OctetKeyPairParameters {
+                        key_type: OctetKeyPairType::OctetKeyPair,
+                        curve: EllipticCurve::Ed25519,
+                        x: BASE64URL_NOPAD.encode(key),
+                    };
+                    (Algorithm::EdDSA, AlgorithmParameters::OctetKeyPair(parameters))
+                } else {
+                    return Err("only RSA and Ed25519 keys are supported".to_string());
+                }
+            }
+            _ => return Err("malformed public key".to_string()),
+        },
+        _ => return Err(format!("unsupported PEM block '{}'", pem.tag())),
+    };
+
+    // Same kid whichever PEM format the key was stored in
+    let material = match &algorithm_parameters {
+        AlgorithmParameters::RSA(rsa) => format!("{}.{}", rsa.n, rsa.e),
+        AlgorithmParameters::OctetKeyPair(okp) => okp.x.clone(),
+        _ => String::new(),
+    };
+
+    let jwk = Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(match algorithm {
+                Algorithm::EdDSA => KeyAlgorithm::EdDSA,
+                _ => KeyAlgorithm::RS256,
+            }),
+            key_id: Some(derive_kid(material.as_bytes())),
+            ..Default::default()
+        },
+        algorithm: algorithm_parameters,
+    };
+
+    Ok((algorithm, jwk))
+}
+
+/// RSA modulus and exponent from a PKCS#1 `RSAPublicKey` sequence
+fn rsa_parameters(block: &ASN1Block) -> Result<AlgorithmParameters, String> {
+    match block {
+        ASN1Block::Sequence(_, fields) => match fields.as_slice() {
+            [ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)] => Ok(AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n: BASE64URL_NOPAD.encode(&n.to_bytes_be().1),
+                e: BASE64URL_NOPAD.encode(&e.to_bytes_be().1),
+            })),
+            _ => Err("malformed RSA public key".to_string()),
+        },
+        _ => Err("malformed RSA public key".to_string()),
+    }
+}
+
+fn verification_key_from_pem(path: &str) -> (VerificationKey, Jwk) {
+    let (algorithm, jwk) =
+        public_key_jwk(&read_file(path)).unwrap_or_else(|e| panic!("Invalid JWT public key {}: {}", path, e));
+    let key = DecodingKey::from_jwk(&jwk).unwrap_or_else(|e| panic!("Invalid JWT public key {}: {}", path, e));
+    let kid = jwk.common.key_id.clone().unwrap_or_default();
+
+    (VerificationKey { kid, algorithm, key }, jwk)
+}
+
+fn verification_key_from_secret(secret: &str) -> VerificationKey {
+    VerificationKey {
+        kid: derive_kid(secret.as_bytes()),
+        algorithm: Algorithm::HS256,
+        key: DecodingKey::from_secret(secret.as_bytes()),
+    }
+}
+
+/// Builds the key ring from the environment:
+///
+/// * `JWT_ALGORITHM` - `HS256` (default), `RS256` or `EdDSA`
+/// * `JWT_SECRET` - HS256 signing secret; a random one is generated when unset, so tokens do not
+///   survive a restart
+/// * `JWT_PRIVATE_KEY_FILE` / `JWT_PUBLIC_KEY_FILE` - PEM key pair for RS256 and EdDSA
+/// * `JWT_PREVIOUS_SECRETS` / `JWT_PREVIOUS_PUBLIC_KEY_FILES` - comma separated keys that were
+///   rotated out but still verify tokens issued before the rotation
+/// * `JWT_ISSUER` / `JWT_AUDIENCE` - the `iss` and `aud` claims
+pub fn keys_from_env() -> JwtKeys {
+    let algorithm = match env::var("JWT_ALGORITHM").unwrap_or_default().to_uppercase().as_str() {
+        "" | "HS256" => Algorithm::HS256,
+        "RS256" => Algorithm::RS256,
+        "EDDSA" => Algorithm::EdDSA,
+        other => panic!("Unsupported JWT_ALGORITHM '{}', expected HS256, RS256 or EdDSA", other),
+    };
+
+    let mut verification_keys = Vec::new();
+    let mut jwks = JwkSet { keys: Vec::new() };
+
+    let (signing_kid, encoding_key) = match algorithm {
+        Algorithm::HS256 => {
+            let secret = env::var("JWT_SECRET").unwrap_or_else(|_| {
+                eprintln!("JWT_SECRET is not set, using a random secret; tokens will not survive a restart");
+                generate_token()
+            });
+            let key = verification_key_from_secret(&secret);
+            let kid = key.kid.clone();
+            verification_keys.push(key);
+            (kid, EncodingKey::from_secret(secret.as_bytes()))
+        }
+        _ => {
+            let private_path = env::var("JWT_PRIVATE_KEY_FILE").expect("JWT_PRIVATE_KEY_FILE must be set for RS256/EdDSA");
+            let public_path = env::var("JWT_PUBLIC_KEY_FILE").expect("JWT_PUBLIC_KEY_FILE must be set for RS256/EdDSA");
+            let private_pem = read_file(&private_path);
+
+            let encoding_key = match algorithm {
+                Algorithm::RS256 => EncodingKey::from_rsa_pem(&private_pem),
+                _ => EncodingKey::from_ed_pem(&private_pem),
+            }
+            .unwrap_or_else(|e| panic!("Invalid JWT private key {}: {}", private_path, e));
+
+            let (key, jwk) = verification_key_from_pem(&public_path);
+            if key.algorithm != algorithm {
+                panic!("JWT_PUBLIC_KEY_FILE does not match JWT_ALGORITHM");
+            }
+            let kid = key.kid.clone();
+            verification_keys.push(key);
+            jwks.keys.push(jwk);
+            (kid, encoding_key)
+        }
+    };
+
+    for secret in comma_list("JWT_PREVIOUS_SECRETS") {
+        verification_keys.push(verification_key_from_secret(&secret));
+    }
+    for path in comma_list("JWT_PREVIOUS_PUBLIC_KEY_FILES") {
+        let (key, jwk) = verification_key_from_pem(&path);
+        verification_keys.push(key);
+        jwks.keys.push(jwk);
+    }
+
+    JwtKeys {
+        algorithm,
+        signing_kid,
+        encoding_key,
+        verification_keys,
+        issuer: env::var("JWT_ISSUER").unwrap_or_else(|_| DEFAULT_ISSUER.to_string()),
+        audience: env::var("JWT_AUDIENCE").unwrap_or_else(|_| DEFAULT_AUDIENCE.to_string()),
+        jwks,
+    }
+}
+
+impl JwtKeys {
+    /// Signs an access token for `sub` bound to a session. `sub` is the user's email, or the
+    /// phone number for `citizen` scoped tokens.
+    pub fn sign(
+        &self,
+        sub: &str,
+        session_id: &ObjectId,
+        ttl_seconds: i64,
+        scope: Option<&str>,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let claims = Claims {
+            sub: sub.to_string(),
+            exp: (now + ttl_seconds) as usize,
+            iat: now as usize,
+            iss: self.issuer.clone(),
+            aud: self.audience.clone(),
+            jti: generate_token(),
+            sid: session_id.to_hex(),
+            scope: scope.map(str::to_string),
+        };
+
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.signing_kid.clone());
+
+        encode(&header, &claims, &self.encoding_key)
+    }
+
+    /// Verifies signature, expiry, issuer and audience with the key named by the `kid` header
+    pub fn verify(&self, token: &str) -> Option<Claims> {
+        let kid = decode_header(token).ok()?.kid?;
+        let key = self.verification_keys.iter().find(|key| key.kid == kid)?;
+
+        let mut validation = Validation::new(key.algorithm);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        decode::<Claims>(token, &key.key, &validation).ok().map(|data| data.claims)
+    }
+}
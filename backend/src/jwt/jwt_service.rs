@@ -0,0 +1,9 @@
+use axum::{extract::State, Json};
+use jsonwebtoken::jwk::JwkSet;
+
+use crate::utils::db::AppState;
+
+/// Public keys in JWK Set format, for services validating our access tokens
+pub async fn jwks_service(State(state): State<AppState>) -> Json<JwkSet> {
+    Json(state.jwt_keys.jwks.clone())
+}
@@ -0,0 +1,19 @@
+use jsonwebtoken::{jwk::JwkSet, Algorithm, DecodingKey, EncodingKey};
+
+/// Key accepted when verifying tokens, looked up by the `kid` header
+pub struct VerificationKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub key: DecodingKey,
+}
+
+/// Signing key plus every key still accepted for verification, built once at startup
+pub struct JwtKeys {
+    pub algorithm: Algorithm,
+    pub signing_kid: String,
+    pub encoding_key: EncodingKey,
+    pub verification_keys: Vec<VerificationKey>,
+    pub issuer: String,
+    pub audience: String,
+    pub jwks: JwkSet, // Public keys only; empty when signing with a shared secret
+}
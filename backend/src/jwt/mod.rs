@@ -0,0 +1,16 @@
+use std::sync::Arc;
+
+use axum::{routing::get, Router};
+use jwt_service::jwks_service;
+
+use crate::utils::db::AppState;
+
+pub mod jwt_model;
+pub mod jwt_service;
+pub mod jwt_structure;
+
+pub fn jwks_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/jwks.json", get(jwks_service))
+        .with_state((*state).clone())
+}
@@ -1,5 +1,5 @@
 mod utils;
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
 use utils::db::initialize_db; 
 mod routes;
@@ -9,12 +9,28 @@ mod user;
 mod disaster;
 use routes::merge_routes;
 mod shelters;
+mod session;
+mod admin;
+mod security;
+mod api_key;
+mod two_factor;
+mod organization;
+mod jwt;
+mod oidc;
+mod citizen;
+mod revision;
+mod translation;
+mod search;
+mod hazard;
+mod incident;
+mod events;
 #[tokio::main]
 async fn main() {
     let state = Arc::new(initialize_db().await);  
+    events::events_model::spawn_ingestion(state.clone());
     let app = merge_routes(state);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8000").await.unwrap();
     println!("server running on port 0.0.0.0:8000");
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
 }
@@ -1,109 +1,218 @@
-use std::{env, sync::Arc};
+use std::sync::Arc;
 
-use axum::{body::Body, extract::Request, http::HeaderValue, middleware::Next, response::Response};
+use axum::{body::Body, extract::Request, http::Method, middleware::Next, response::Response};
 use hyper::StatusCode;
-use jsonwebtoken::{decode, DecodingKey, Validation};
-use mongodb::bson::{doc, Bson};
-
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    Database,
+};
+
+
+use crate::{
+    citizen::citizen_structure::{CitizenRoute, CurrentCitizen, CITIZEN_SCOPE},
+    api_key::{
+        api_key_model::find_active_key,
+        api_key_structure::{ApiKeyAccess, ApiKeyRoute, API_KEY_PREFIX},
+    },
+    jwt::jwt_structure::JwtKeys,
+    middleware::permission::AuthUser,
+    session::{session_model::touch_session, session_structure::CurrentSession},
+    utils::{
+        auth_cookie::{csrf_token_matches, read_cookie, ACCESS_COOKIE},
+        db::AppState,
+        response::error_response,
+    },
+};
+
+/// Credential presented by the caller
+enum Credential {
+    Jwt(String),
+    Cookie(String), // Access token from the browser cookie, subject to the CSRF check
+    ApiKey(String),
+}
 
-use crate::{user::{user_model::generate_jwt, user_structure::Claims}, utils::db::AppState};
+/// What a credential resolved to
+enum Authenticated {
+    Session(CurrentSession),
+    ApiKey(ApiKeyAccess),
+    Citizen(CurrentSession, CurrentCitizen), // Phone-login token, only valid on routers with a `CitizenRoute`
+}
 
+/// Validates the credential and exposes the caller to downstream handlers, both as the
+/// user id `String` and as an `AuthUser` with its roles.
+///
+/// Bearer JWTs are checked against their session; they are short-lived and renewed through
+/// `/user/refresh`. Browsers may send the access token in the `token` cookie instead, in which
+/// case state-changing requests must repeat the `csrf_token` cookie in the `X-CSRF-Token` header. API keys (`X-API-Key`, or a bearer token starting with `dm_`) act as their
+/// owner but are only accepted on routers that declare an `ApiKeyRoute`, and only with the
+/// matching read/write scope. Accounts that have not verified their email can only use read routes,
+/// and accounts deactivated by an admin are refused outright. Citizen tokens from phone login
+/// are only accepted on routers that declare a `CitizenRoute`.
 pub async fn auth_middleware(
     mut req: Request<Body>,  
     next: Next,   
 ) -> Result<Response, StatusCode> {
-    let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "disaster".to_string());
-
-    let token = req
+    let bearer = req
         .headers()
         .get("Authorization")
         .and_then(|hv| hv.to_str().ok())
         .filter(|hv| hv.starts_with("Bearer "))
         .map(|hv| hv.trim_start_matches("Bearer ").to_string());
 
-    if token.is_none() {
-        return Ok(Response::builder()
-            .status(StatusCode::UNAUTHORIZED)
-            .body(Body::from("Unauthorized: Invalid or missing token"))
-            .unwrap());
+    let api_key = req
+        .headers()
+        .get("X-API-Key")
+        .and_then(|hv| hv.to_str().ok())
+        .map(str::to_string);
+
+    let credential = match (api_key, bearer, read_cookie(req.headers(), ACCESS_COOKIE)) {
+        (Some(key), _, _) => Credential::ApiKey(key),
+        (None, Some(token), _) if token.starts_with(API_KEY_PREFIX) => Credential::ApiKey(token),
+        (None, Some(token), _) => Credential::Jwt(token),
+        (None, None, Some(token)) => Credential::Cookie(token),
+        (None, None, None) => {
+            return Ok(error_response("Unauthorized: Invalid or missing token", StatusCode::UNAUTHORIZED));
+        }
+    };
+
+    let is_read = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+
+    // The browser attaches cookies to cross-site requests too, so writes must prove same-origin
+    if matches!(credential, Credential::Cookie(_)) && !is_read && !csrf_token_matches(req.headers()) {
+        return Ok(error_response("Forbidden: missing or invalid CSRF token", StatusCode::FORBIDDEN));
+    }
+    let required_scope = req
+        .extensions()
+        .get::<ApiKeyRoute>()
+        .map(|route| if is_read { route.read } else { route.write });
+
+    if matches!(credential, Credential::ApiKey(_)) && required_scope.is_none() {
+        return Ok(error_response("Forbidden: API keys are not accepted on this route", StatusCode::FORBIDDEN));
     }
 
-    let token = token.unwrap();
-
-    match decode::<Claims>(
-        &token,
-        &DecodingKey::from_secret(secret.as_ref()),
-        &Validation::default(),
-    ) {
-        Ok(token_data) => {
-            let user_email = &token_data.claims.sub;
-            let state = match req.extensions().get::<Arc<AppState>>() {
-                Some(state) => state.clone(),
-                None => return Err(StatusCode::INTERNAL_SERVER_ERROR),
-            };
-
-            let db = state.db.clone(); 
-            let collection = db.lock().await
-                .database("disaster")
-                .collection::<mongodb::bson::Document>("users");
-
-            match collection.find_one(doc! { "email": user_email }).await {
-                
-                Ok(Some(user_doc)) => {
-                    if let Some(db_token) = user_doc.get_str("token").ok() {
-                        if db_token == token {
-                            // let new_token = match generate_jwt(user_email) {
-                            //     Ok(new_token) => new_token,
-                            //     Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
-                            // };
-
-
-                            // println!("New Token: {}", new_token);
-                            
-                           
-                            // Update token in DB
-                            // collection.update_one(
-                                // doc! { "email": user_email },
-                                // doc! { "$set": { "token": new_token.clone() } },
-                            // )
-                            // .await
-                            // .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-                            let user_id = user_doc.get("_id")
-                                .and_then(Bson::as_object_id)
-                                .map(|oid| oid.to_string())
-                                .unwrap_or_else(|| "unknown".to_string());
-                                
-                                req.extensions_mut().insert(user_id.clone());
-
-                                let response = next.run(req).await; // Pass the modified request forward
-                                
-                               
-                            
-                            // headers.insert(
-                            //     "Authorization",
-                            //     HeaderValue::from_str(&format!("Bearer {}", new_token)).unwrap(),
-                            // );
-
-                            // // Set the token in a cookie
-                            // let cookie_value = format!(
-                            //     "token={}; HttpOnly; Path=/; Max-Age=86400; SameSite=Strict",
-                            //     new_token
-                            // );
-                            // headers.insert("Set-Cookie", HeaderValue::from_str(&cookie_value).unwrap());
-
-                            return Ok(response);
-                        }
-                    }
-                }
-                _ => {}
-            }
+    let accepts_citizens = req.extensions().get::<CitizenRoute>().is_some();
+
+    let state = match req.extensions().get::<Arc<AppState>>() {
+        Some(state) => state.clone(),
+        None => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let resolved = match &credential {
+        Credential::Jwt(token) | Credential::Cookie(token) => authenticate_jwt(&database, &state.jwt_keys, token).await,
+        Credential::ApiKey(key) => authenticate_api_key(&database, key).await,
+    };
+    drop(db);
+
+    let Some((user_id, user_doc, authenticated)) = resolved else {
+        return Ok(error_response("Unauthorized: Invalid or expired token", StatusCode::UNAUTHORIZED));
+    };
+
+    if let Authenticated::ApiKey(access) = &authenticated {
+        if let Some(required) = required_scope.filter(|scope| !access.scopes.contains(scope)) {
+            return Ok(error_response(
+                &format!("Forbidden: API key is missing the '{}' scope", required.as_str()),
+                StatusCode::FORBIDDEN,
+            ));
         }
-        Err(_) => {}
     }
 
-    Ok(Response::builder()
-        .status(StatusCode::UNAUTHORIZED)
-        .body(Body::from("Unauthorized: Invalid or expired token"))
-        .unwrap())
+    if matches!(authenticated, Authenticated::Citizen(..)) && !accepts_citizens {
+        return Ok(error_response("Forbidden: citizen tokens are not accepted on this route", StatusCode::FORBIDDEN));
+    }
+
+    if matches!(user_doc.get_bool("deactivated"), Ok(true)) {
+        return Ok(error_response("Forbidden: this account has been deactivated", StatusCode::FORBIDDEN));
+    }
+
+    // Accounts registered before verification existed have no `verified` field
+    let unverified = matches!(user_doc.get_bool("verified"), Ok(false));
+    if unverified && !is_read {
+        return Ok(error_response(
+            "Forbidden: verify your email address before making changes",
+            StatusCode::FORBIDDEN,
+        ));
+    }
+
+    req.extensions_mut().insert(user_id.to_hex());
+    let mut auth_user = AuthUser::from_document(user_id, &user_doc);
+    match authenticated {
+        Authenticated::Session(session) => {
+            req.extensions_mut().insert(session);
+        }
+        Authenticated::ApiKey(access) => {
+            auth_user.key_org_id = access.org_id;
+            req.extensions_mut().insert(access);
+        }
+        Authenticated::Citizen(session, citizen) => {
+            req.extensions_mut().insert(session);
+            req.extensions_mut().insert(citizen);
+        }
+    }
+    req.extensions_mut().insert(auth_user);
+
+    Ok(next.run(req).await)
+}
+
+/// Layer for public routes that partners may also call with an API key: anonymous callers
+/// pass through untouched, but a presented key goes through `auth_middleware`, so it must be
+/// valid and hold the read/write scope of the router's `ApiKeyRoute`.
+pub async fn api_key_middleware(
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let bearer_key = req
+        .headers()
+        .get("Authorization")
+        .and_then(|hv| hv.to_str().ok())
+        .is_some_and(|hv| hv.trim_start_matches("Bearer ").starts_with(API_KEY_PREFIX));
+
+    if !bearer_key && !req.headers().contains_key("X-API-Key") {
+        return Ok(next.run(req).await);
+    }
+
+    auth_middleware(req, next).await
+}
+
+/// Resolves a JWT whose session is still active. Citizen tokens name a phone number in `sub`
+/// and resolve against the `citizens` collection instead of `users`.
+async fn authenticate_jwt(db: &Database, keys: &JwtKeys, token: &str) -> Option<(ObjectId, Document, Authenticated)> {
+    let claims = keys.verify(token)?;
+
+    let session_id = ObjectId::parse_str(&claims.sid).ok()?;
+    let (collection, filter) = match claims.scope.as_deref() {
+        None => ("users", doc! { "email": &claims.sub }),
+        Some(CITIZEN_SCOPE) => ("citizens", doc! { "phone": &claims.sub }),
+        Some(_) => return None,
+    };
+    let user_doc = db.collection::<Document>(collection).find_one(filter).await.ok()??;
+    let user_id = user_doc.get_object_id("_id").ok()?;
+
+    // The session must still be active; revoked sessions invalidate their access tokens
+    touch_session(db, session_id, user_id).await.ok()??;
+
+    let session = CurrentSession { id: session_id };
+    let authenticated = match claims.scope {
+        Some(_) => Authenticated::Citizen(session, CurrentCitizen { id: user_id }),
+        None => Authenticated::Session(session),
+    };
+
+    Some((user_id, user_doc, authenticated))
+}
+
+/// Resolves an active API key to its owner
+async fn authenticate_api_key(db: &Database, raw_key: &str) -> Option<(ObjectId, Document, Authenticated)> {
+    let key = find_active_key(db, raw_key).await.ok()??;
+
+    let user_doc = db
+        .collection::<Document>("users")
+        .find_one(doc! { "_id": key.owner_id })
+        .await
+        .ok()??;
+
+    let access = ApiKeyAccess { scopes: key.scopes, org_id: key.org_id };
+
+    Some((key.owner_id, user_doc, Authenticated::ApiKey(access)))
 }
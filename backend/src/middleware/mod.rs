@@ -1,6 +1,3 @@
 pub(crate) mod auth;
-pub mod local;
-pub mod ngo;
-pub mod admin;
 pub mod log;
-pub mod community;
+pub mod permission;
@@ -0,0 +1,131 @@
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, Request, State},
+    http::{request::Parts, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use mongodb::bson::{oid::ObjectId, Document};
+
+use crate::{
+    two_factor::two_factor_model::totp_required_roles,
+    user::user_structure::Role,
+    utils::response::error_response,
+};
+
+/// Actions guarded by role. Each role grants a fixed set of permissions (see `permissions_for`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ManageUsers,
+    ManageDisasters,
+    ModerateGuides,
+    ManageShelters,
+    ManageApiKeys,
+    ManageOrganizations, // Act on any organization's members and data
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ManageUsers => "manage_users",
+            Permission::ManageDisasters => "manage_disasters",
+            Permission::ModerateGuides => "moderate_guides",
+            Permission::ManageShelters => "manage_shelters",
+            Permission::ManageApiKeys => "manage_api_keys",
+            Permission::ManageOrganizations => "manage_organizations",
+        }
+    }
+}
+
+pub fn permissions_for(role: Role) -> &'static [Permission] {
+    match role {
+        Role::Admin => &[
+            Permission::ManageUsers,
+            Permission::ManageDisasters,
+            Permission::ModerateGuides,
+            Permission::ManageShelters,
+            Permission::ManageApiKeys,
+            Permission::ManageOrganizations,
+        ],
+        Role::Ngo => &[Permission::ManageShelters, Permission::ManageApiKeys],
+        Role::Local => &[Permission::ModerateGuides],
+        Role::Community => &[],
+    }
+}
+
+/// The authenticated caller, resolved once by `auth_middleware` and cached in request extensions
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: ObjectId,
+    pub roles: Vec<Role>,
+    pub key_org_id: Option<ObjectId>, // Set when an API key bound to an organization was used
+}
+
+impl AuthUser {
+    /// Reads the granted roles from a user document: the `roles` array plus the legacy single `role`.
+    /// Roles listed in `TOTP_REQUIRED_ROLES` are withheld until the user has enabled two-factor.
+    pub fn from_document(id: ObjectId, user_doc: &Document) -> Self {
+        let mut roles: Vec<Role> = user_doc
+            .get_array("roles")
+            .map(|roles| {
+                roles
+                    .iter()
+                    .filter_map(|role| role.as_str().and_then(Role::parse))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if let Some(role) = user_doc.get_str("role").ok().and_then(Role::parse) {
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
+        }
+
+        if !matches!(user_doc.get_bool("totp_enabled"), Ok(true)) {
+            let required = totp_required_roles();
+            roles.retain(|role| !required.contains(role));
+        }
+
+        AuthUser { id, roles, key_org_id: None }
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.roles
+            .iter()
+            .any(|role| permissions_for(*role).contains(&permission))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthUser>()
+            .cloned()
+            .ok_or_else(|| error_response("Unauthorized: Invalid or missing token", StatusCode::UNAUTHORIZED))
+    }
+}
+
+/// Layer guarding a route with a permission, used as
+/// `from_fn_with_state(Permission::ManageShelters, require_permission)` inside `auth_middleware`
+pub async fn require_permission(
+    State(permission): State<Permission>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let allowed = req
+        .extensions()
+        .get::<AuthUser>()
+        .is_some_and(|user| user.has_permission(permission));
+
+    if !allowed {
+        return error_response(
+            &format!("Forbidden: '{}' permission is required", permission.as_str()),
+            StatusCode::FORBIDDEN,
+        );
+    }
+
+    next.run(req).await
+}
@@ -0,0 +1,20 @@
+use std::sync::Arc;
+
+use axum::{routing::get, Router};
+use oidc_service::{callback_service, list_providers_service, login_service};
+
+use crate::utils::db::AppState;
+
+pub mod oidc_model;
+pub mod oidc_service;
+pub mod oidc_structure;
+
+/// Authorization-code + PKCE login with external identity providers. The provider's redirect URI
+/// should lead back to `/{provider}/callback` with the `code` and `state` it received.
+pub fn oidc_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_providers_service))
+        .route("/{provider}/login", get(login_service))
+        .route("/{provider}/callback", get(callback_service))
+        .with_state((*state).clone())
+}
@@ -0,0 +1,423 @@
+use std::{env, time::Duration};
+
+use axum::{http::StatusCode, response::Response};
+use data_encoding::BASE64URL_NOPAD;
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    Collection, Database,
+};
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+
+use super::oidc_structure::{Discovery, IdTokenClaims, OidcProvider, OidcState, TokenEndpointResponse};
+use crate::{
+    session::session_structure::ClientInfo,
+    user::{
+        user_model::{complete_login, mfa_challenge_response},
+        user_structure::{Role, RoleStatus},
+    },
+    utils::{
+        crypto::{constant_time_eq, generate_token, hash_token},
+        db::AppState,
+    },
+};
+
+const STATE_TTL_MINUTES: i64 = 10;
+const HTTP_TIMEOUT_SECONDS: u64 = 10;
+/// Signature algorithms accepted on ID tokens; shared-secret algorithms are never accepted
+const ID_TOKEN_ALGORITHMS: [Algorithm; 7] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// Names listed in `OIDC_PROVIDERS`, e.g. `gov,partner`
+pub fn provider_names() -> Vec<String> {
+    env::var("OIDC_PROVIDERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Reads one provider's settings from `OIDC_<NAME>_ISSUER`, `_CLIENT_ID`, `_CLIENT_SECRET`,
+/// `_REDIRECT_URI`, `_SCOPES`, `_GROUPS_CLAIM` and `_ROLE_MAP` (`group=role,group=role`)
+pub fn provider(name: &str) -> Option<OidcProvider> {
+    let name = name.to_lowercase();
+    if !provider_names().contains(&name) {
+        return None;
+    }
+
+    let var = |key: &str| env::var(format!("OIDC_{}_{}", name.to_uppercase(), key)).ok();
+
+    let role_map = var("ROLE_MAP")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|pair| {
+            let (group, role) = pair.split_once('=')?;
+            Some((group.trim().to_string(), Role::parse(role.trim())?))
+        })
+        .collect();
+
+    Some(OidcProvider {
+        issuer: var("ISSUER")?.trim_end_matches('/').to_string(),
+        client_id: var("CLIENT_ID")?,
+        client_secret: var("CLIENT_SECRET").filter(|secret| !secret.is_empty()),
+        redirect_uri: var("REDIRECT_URI")?,
+        scopes: var("SCOPES").unwrap_or_else(|| "openid email profile".to_string()),
+        groups_claim: var("GROUPS_CLAIM").unwrap_or_else(|| "groups".to_string()),
+        role_map,
+        name,
+    })
+}
+
+fn http_client() -> Result<reqwest::Client, (StatusCode, String)> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECONDS))
+        .build()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("HTTP client error: {}", e)))
+}
+
+fn upstream_error(context: &str, e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::BAD_GATEWAY, format!("Identity provider {}: {}", context, e))
+}
+
+async fn discover(http: &reqwest::Client, provider: &OidcProvider) -> Result<Discovery, (StatusCode, String)> {
+    let url = format!("{}/.well-known/openid-configuration", provider.issuer);
+    let discovery: Discovery = http
+        .get(&url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| upstream_error("discovery failed", e))?
+        .json()
+        .await
+        .map_err(|e| upstream_error("discovery failed", e))?;
+
+    // The document must describe the issuer we were configured with (OIDC Discovery 4.3)
+    if discovery.issuer.trim_end_matches('/') != provider.issuer {
+        return Err(upstream_error("discovery failed", "issuer mismatch"));
+    }
+
+    Ok(discovery)
+}
+
+/// Starts the authorization-code flow: stores state, nonce and PKCE verifier and returns
+/// the URL to send the browser to
+pub async fn authorization_url(state: AppState, provider: &OidcProvider) -> Result<String, (StatusCode, String)> {
+    let discovery = discover(&http_client()?, provider).await?;
+
+    let state_param = generate_token();
+    let nonce = generate_token();
+    let code_verifier = generate_token();
+    let code_challenge = BASE64URL_NOPAD.encode(&Sha256::digest(code_verifier.as_bytes()));
+
+    let url = Url::parse_with_params(
+        &discovery.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", provider.client_id.as_str()),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("scope", provider.scopes.as_str()),
+            ("state", state_param.as_str()),
+            ("nonce", nonce.as_str()),
+            ("code_challenge", code_challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|e| upstream_error("returned an invalid authorization endpoint", e))?;
+
+    let now = DateTime::now();
+    let pending = OidcState {
+        id: None,
+        state_hash: hash_token(&state_param),
+        provider: provider.name.clone(),
+        nonce,
+        code_verifier,
+        created_at: now,
+        expires_at: DateTime::from_millis(now.timestamp_millis() + STATE_TTL_MINUTES * 60_000),
+        used: false,
+    };
+
+    let db = state.db.lock().await;
+    let states: Collection<OidcState> = db.database("disaster").collection("oidc_states");
+    states.insert_one(pending).await.map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    })?;
+
+    Ok(url.to_string())
+}
+
+/// Checks signature (against the provider's JWKS), issuer, audience, expiry and nonce
+async fn validate_id_token(
+    http: &reqwest::Client,
+    discovery: &Discovery,
+    provider: &OidcProvider,
+    id_token: &str,
+    expected_nonce: &str,
+) -> Result<IdTokenClaims, (StatusCode, String)> {
+    let invalid = |reason: &str| (StatusCode::UNAUTHORIZED, format!("Invalid ID token: {}", reason));
+
+    let header = decode_header(id_token).map_err(|_| invalid("malformed header"))?;
+    if !ID_TOKEN_ALGORITHMS.contains(&header.alg) {
+        return Err(invalid("unsupported signing algorithm"));
+    }
+
+    let jwks: JwkSet = http
+        .get(&discovery.jwks_uri)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| upstream_error("JWKS fetch failed", e))?
+        .json()
+        .await
+        .map_err(|e| upstream_error("JWKS fetch failed", e))?;
+
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| invalid("unknown signing key"))?;
+    let key = DecodingKey::from_jwk(jwk).map_err(|_| invalid("unusable signing key"))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[&discovery.issuer]);
+    validation.set_audience(&[&provider.client_id]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+        .map_err(|e| invalid(&e.to_string()))?
+        .claims;
+
+    let nonce_matches = claims
+        .nonce
+        .as_deref()
+        .is_some_and(|nonce| constant_time_eq(nonce.as_bytes(), expected_nonce.as_bytes()));
+    if !nonce_matches {
+        return Err(invalid("nonce mismatch"));
+    }
+
+    Ok(claims)
+}
+
+/// Roles granted by the user's IdP groups; the groups claim may be an array or a single string
+fn mapped_roles(provider: &OidcProvider, claims: &IdTokenClaims) -> Vec<Role> {
+    let groups: Vec<&str> = match claims.other.get(&provider.groups_claim) {
+        Some(serde_json::Value::Array(values)) => values.iter().filter_map(|value| value.as_str()).collect(),
+        Some(serde_json::Value::String(value)) => vec![value.as_str()],
+        _ => Vec::new(),
+    };
+
+    let mut roles = Vec::new();
+    for (group, role) in &provider.role_map {
+        if groups.contains(&group.as_str()) && !roles.contains(role) {
+            roles.push(*role);
+        }
+    }
+    roles
+}
+
+fn role_strings(roles: &[Role]) -> Vec<&'static str> {
+    roles.iter().map(|role| role.as_str()).collect()
+}
+
+/// Finds the user linked to this IdP subject, links an existing account with the same verified
+/// email, or provisions a new one. Roles that came from IdP groups are kept in sync on every login,
+/// while roles granted inside the application are left alone.
+async fn link_or_provision(
+    database: &Database,
+    provider: &OidcProvider,
+    claims: &IdTokenClaims,
+) -> Result<(ObjectId, String), (StatusCode, String)> {
+    let users: Collection<Document> = database.collection("users");
+    let db_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    };
+    let idp_roles = mapped_roles(provider, claims);
+    let identity = doc! { "provider": &provider.name, "subject": &claims.sub };
+
+    let linked = users
+        .find_one(doc! { "identities": { "$elemMatch": identity.clone() } })
+        .await
+        .map_err(db_error)?;
+
+    let user_doc = match linked {
+        Some(user_doc) => user_doc,
+        None => {
+            let email = claims
+                .email
+                .clone()
+                .ok_or((StatusCode::BAD_REQUEST, "Identity provider did not share an email address".to_string()))?;
+
+            match users.find_one(doc! { "email": &email }).await.map_err(db_error)? {
+                // Only an address the IdP has verified may take over an existing account
+                Some(_) if claims.email_verified != Some(true) => {
+                    return Err((
+                        StatusCode::CONFLICT,
+                        "An account with this email already exists and the identity provider has not verified the address".to_string(),
+                    ));
+                }
+                Some(user_doc) => user_doc,
+                None => {
+                    let new_user = doc! {
+                        "email": &email,
+                        "name": claims.name.clone().unwrap_or_else(|| email.clone()),
+                        "password": "", // No password login; `verify_password` rejects an empty hash
+                        "roles": role_strings(&idp_roles),
+                        "role_status": RoleStatus::Approved.as_str(),
+                        "verified": true,
+                        "identities": []
+                    };
+                    let inserted = users.insert_one(new_user).await.map_err(db_error)?;
+                    users
+                        .find_one(doc! { "_id": inserted.inserted_id })
+                        .await
+                        .map_err(db_error)?
+                        .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "Failed to create user".to_string()))?
+                }
+            }
+        }
+    };
+
+    let user_id = user_doc
+        .get_object_id("_id")
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Invalid user record".to_string()))?;
+    let email = user_doc.get_str("email").unwrap_or_default().to_string();
+
+    if matches!(user_doc.get_bool("deactivated"), Ok(true)) {
+        return Err((StatusCode::FORBIDDEN, "This account has been deactivated".to_string()));
+    }
+
+    let as_roles = |field: &str| -> Vec<Role> {
+        user_doc
+            .get_array(field)
+            .map(|values| values.iter().filter_map(|value| value.as_str().and_then(Role::parse)).collect())
+            .unwrap_or_default()
+    };
+    let previous_idp_roles = as_roles("idp_roles");
+    let mut roles: Vec<Role> = as_roles("roles")
+        .into_iter()
+        .filter(|role| !previous_idp_roles.contains(role))
+        .collect();
+    for role in &idp_roles {
+        if !roles.contains(role) {
+            roles.push(*role);
+        }
+    }
+
+    let mut update = doc! {
+        "$set": {
+            "roles": role_strings(&roles),
+            "idp_roles": role_strings(&idp_roles),
+            "last_idp_login_at": DateTime::now()
+        }
+    };
+    let already_linked = user_doc
+        .get_array("identities")
+        .is_ok_and(|identities| identities.iter().any(|linked| linked.as_document() == Some(&identity)));
+    if !already_linked {
+        update.insert("$push", doc! { "identities": identity });
+    }
+
+    users
+        .update_one(doc! { "_id": user_id }, update)
+        .await
+        .map_err(db_error)?;
+
+    Ok((user_id, email))
+}
+
+/// Finishes the flow: consumes the state, exchanges the code with the PKCE verifier, validates
+/// the ID token and logs the linked user in, or asks for their second factor if they enabled one
+pub async fn handle_callback(
+    state: AppState,
+    provider: &OidcProvider,
+    code: &str,
+    state_param: &str,
+    client: ClientInfo,
+) -> Result<Response, (StatusCode, String)> {
+    let pending = {
+        let db = state.db.lock().await;
+        let states: Collection<OidcState> = db.database("disaster").collection("oidc_states");
+        states
+            .find_one_and_update(
+                doc! {
+                    "state_hash": hash_token(state_param),
+                    "provider": &provider.name,
+                    "used": false,
+                    "expires_at": { "$gt": DateTime::now() }
+                },
+                doc! { "$set": { "used": true } },
+            )
+            .await
+            .map_err(|_| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Database error".to_string(),
+                )
+            })?
+            .ok_or((StatusCode::BAD_REQUEST, "Invalid or expired login state, please start again".to_string()))?
+    };
+
+    let http = http_client()?;
+    let discovery = discover(&http, provider).await?;
+
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", provider.redirect_uri.as_str()),
+        ("client_id", provider.client_id.as_str()),
+        ("code_verifier", pending.code_verifier.as_str()),
+    ];
+    if let Some(secret) = &provider.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+
+    let tokens: TokenEndpointResponse = http
+        .post(&discovery.token_endpoint)
+        .form(&form)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| upstream_error("token exchange failed", e))?
+        .json()
+        .await
+        .map_err(|e| upstream_error("token exchange failed", e))?;
+
+    let claims = validate_id_token(&http, &discovery, provider, &tokens.id_token, &pending.nonce).await?;
+
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let (user_id, email) = link_or_provision(&database, provider, &claims).await?;
+
+    // The provider vouches for the identity, not for our second factor, so a linked account
+    // with two-factor enabled still has to complete it
+    let totp_enabled = database
+        .collection::<Document>("users")
+        .count_documents(doc! { "_id": user_id, "totp_enabled": true })
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database error".to_string(),
+            )
+        })?;
+    if totp_enabled > 0 {
+        return mfa_challenge_response(&database, user_id).await;
+    }
+
+    complete_login(&database, &state.jwt_keys, user_id, &email, client).await
+}
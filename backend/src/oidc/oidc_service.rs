@@ -0,0 +1,43 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect},
+    Json,
+};
+use serde_json::json;
+
+use super::{oidc_model, oidc_structure::CallbackQuery};
+use crate::{session::session_service::client_info, utils::db::AppState};
+
+pub async fn list_providers_service() -> impl IntoResponse {
+    Json(json!({ "providers": oidc_model::provider_names() }))
+}
+
+pub async fn login_service(State(state): State<AppState>, Path(provider): Path<String>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let provider = oidc_model::provider(&provider).ok_or((StatusCode::NOT_FOUND, "Unknown identity provider".to_string()))?;
+    let url = oidc_model::authorization_url(state, &provider).await?;
+    Ok(Redirect::to(&url))
+}
+
+pub async fn callback_service(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(provider): Path<String>,
+    Query(query): Query<CallbackQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let provider = oidc_model::provider(&provider).ok_or((StatusCode::NOT_FOUND, "Unknown identity provider".to_string()))?;
+
+    if let Some(error) = query.error {
+        let description = query.error_description.unwrap_or_default();
+        return Err((StatusCode::UNAUTHORIZED, format!("Identity provider refused the login: {} {}", error, description).trim().to_string()));
+    }
+
+    let (Some(code), Some(state_param)) = (query.code, query.state) else {
+        return Err((StatusCode::BAD_REQUEST, "Code and state are required".to_string()));
+    };
+
+    oidc_model::handle_callback(state, &provider, &code, &state_param, client_info(&headers, addr)).await
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::user::user_structure::Role;
+
+/// Identity provider configured through `OIDC_<NAME>_*` variables
+#[derive(Debug, Clone)]
+pub struct OidcProvider {
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>, // None for public clients relying on PKCE alone
+    pub redirect_uri: String,
+    pub scopes: String,
+    pub groups_claim: String,
+    pub role_map: Vec<(String, Role)>, // IdP group -> role granted while the user is in it
+}
+
+/// Subset of `/.well-known/openid-configuration` used by the flow
+#[derive(Debug, Deserialize)]
+pub struct Discovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenEndpointResponse {
+    pub id_token: String,
+}
+
+/// Claims read from a validated ID token
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: Option<bool>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub nonce: Option<String>,
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>, // Holds the groups claim, whose name is configurable
+}
+
+/// Pending authorization request in `oidc_states`, consumed by the callback
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OidcState {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub state_hash: String, // SHA-256 of the `state` parameter
+    pub provider: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub created_at: DateTime,
+    pub expires_at: DateTime,
+    pub used: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
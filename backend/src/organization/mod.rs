@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use axum::{
+    middleware::from_fn,
+    routing::{get, patch, post},
+    Router,
+};
+use organization_service::{
+    add_member_service, create_organization_service, get_organization_service, list_organizations_service,
+    remove_member_service, update_member_service,
+};
+
+use crate::{middleware::auth::auth_middleware, utils::db::AppState};
+
+pub mod organization_model;
+pub mod organization_service;
+pub mod organization_structure;
+
+pub fn organization_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_organizations_service).post(create_organization_service))
+        .route("/{org_id}", get(get_organization_service))
+        .route("/{org_id}/members", post(add_member_service))
+        .route(
+            "/{org_id}/members/{user_id}",
+            patch(update_member_service).delete(remove_member_service),
+        )
+        .layer(from_fn(auth_middleware))
+        .with_state((*state).clone())
+}
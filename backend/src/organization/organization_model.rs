@@ -0,0 +1,301 @@
+use axum::{http::StatusCode, response::Response};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    Collection, Database,
+};
+
+use super::organization_structure::{
+    AddMemberRequest, CreateOrganizationRequest, Membership, OrgRole, Organization, OrganizationView,
+};
+use crate::{
+    middleware::permission::{AuthUser, Permission},
+    utils::{
+        db::AppState,
+        response::{error_response, success_response},
+    },
+};
+
+/// Ids of the organizations the user belongs to
+pub async fn member_org_ids(db: &Database, user_id: ObjectId) -> mongodb::error::Result<Vec<ObjectId>> {
+    let organizations: Collection<Organization> = db.collection("organizations");
+    let orgs: Vec<Organization> = organizations
+        .find(doc! { "members.user_id": user_id })
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(orgs.into_iter().filter_map(|org| org.id).collect())
+}
+
+/// Organizations whose data the caller may write; `None` means every organization (admins).
+/// An API key bound to an organization narrows this down to that organization.
+pub async fn writable_org_ids(db: &Database, user: &AuthUser) -> mongodb::error::Result<Option<Vec<ObjectId>>> {
+    let orgs = if user.has_permission(Permission::ManageOrganizations) {
+        None
+    } else {
+        Some(member_org_ids(db, user.id).await?)
+    };
+
+    Ok(match (orgs, user.key_org_id) {
+        (None, Some(key_org)) => Some(vec![key_org]),
+        (Some(ids), Some(key_org)) => Some(ids.into_iter().filter(|id| *id == key_org).collect()),
+        (orgs, None) => orgs,
+    })
+}
+
+/// Whether data owned by `owner_org_id` may be changed. Records created before organizations
+/// existed have no owner and are left to admins.
+pub fn can_write(writable: &Option<Vec<ObjectId>>, owner_org_id: Option<ObjectId>) -> bool {
+    match (writable, owner_org_id) {
+        (None, _) => true,
+        (Some(ids), Some(owner)) => ids.contains(&owner),
+        (Some(_), None) => false,
+    }
+}
+
+/// Picks the owning organization for a new record: the requested one if the caller may write
+/// for it, otherwise the caller's only organization
+pub async fn resolve_owner_org(
+    db: &Database,
+    user: &AuthUser,
+    requested: Option<ObjectId>,
+) -> Result<Option<ObjectId>, (StatusCode, String)> {
+    let writable = writable_org_ids(db, user)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e)))?;
+
+    match (requested, writable) {
+        (Some(org_id), writable) if can_write(&writable, Some(org_id)) => Ok(Some(org_id)),
+        (Some(_), _) => Err((
+            StatusCode::FORBIDDEN,
+            "You are not a member of that organization".to_string(),
+        )),
+        (None, None) => Ok(None),
+        (None, Some(ids)) => match ids.as_slice() {
+            [org_id] => Ok(Some(*org_id)),
+            [] => Err((
+                StatusCode::FORBIDDEN,
+                "Join an organization before creating records".to_string(),
+            )),
+            _ => Err((
+                StatusCode::BAD_REQUEST,
+                "owner_org_id is required when you belong to several organizations".to_string(),
+            )),
+        },
+    }
+}
+
+pub async fn create_organization(state: AppState, user_id: ObjectId, request: CreateOrganizationRequest) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<Organization> = db.database("disaster").collection("organizations");
+
+    let name = request.name.trim().to_string();
+    match collection.find_one(doc! { "name": &name }).await {
+        Ok(Some(_)) => return error_response("Organization already present", StatusCode::BAD_REQUEST),
+        Ok(None) => {}
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let now = DateTime::now();
+    let mut org = Organization {
+        id: None,
+        name,
+        kind: request.kind,
+        description: request.description,
+        members: vec![Membership {
+            user_id,
+            role: OrgRole::Owner,
+            joined_at: now,
+        }],
+        created_by: user_id,
+        created_at: now,
+    };
+
+    match collection.insert_one(&org).await {
+        Ok(result) => {
+            org.id = result.inserted_id.as_object_id();
+            success_response("Organization created successfully", OrganizationView::from(org), StatusCode::CREATED)
+        }
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Admins see every organization, everyone else the ones they belong to
+pub async fn list_organizations(state: AppState, user: AuthUser) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<Organization> = db.database("disaster").collection("organizations");
+
+    let filter = if user.has_permission(Permission::ManageOrganizations) {
+        doc! {}
+    } else {
+        doc! { "members.user_id": user.id }
+    };
+
+    match collection.find(filter).sort(doc! { "name": 1 }).await {
+        Ok(cursor) => match cursor.try_collect::<Vec<Organization>>().await {
+            Ok(orgs) => {
+                let orgs: Vec<OrganizationView> = orgs.into_iter().map(OrganizationView::from).collect();
+                success_response("Organizations retrieved successfully", orgs, StatusCode::OK)
+            }
+            Err(e) => error_response(&format!("Failed to collect organizations: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        },
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Loads an organization along with the caller's role in it. Admins act as owners.
+async fn find_with_role(
+    collection: &Collection<Organization>,
+    user: &AuthUser,
+    org_id: ObjectId,
+) -> Result<(Organization, OrgRole), (StatusCode, String)> {
+    let org = collection
+        .find_one(doc! { "_id": org_id })
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e)))?
+        .ok_or((StatusCode::NOT_FOUND, "Organization not found".to_string()))?;
+
+    let role = if user.has_permission(Permission::ManageOrganizations) {
+        Some(OrgRole::Owner)
+    } else {
+        org.role_of(user.id)
+    };
+
+    match role {
+        Some(role) => Ok((org, role)),
+        None => Err((StatusCode::FORBIDDEN, "You are not a member of this organization".to_string())),
+    }
+}
+
+pub async fn get_organization(state: AppState, user: AuthUser, org_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<Organization> = db.database("disaster").collection("organizations");
+
+    match find_with_role(&collection, &user, org_id).await {
+        Ok((org, _)) => success_response("Organization retrieved successfully", OrganizationView::from(org), StatusCode::OK),
+        Err((status, message)) => error_response(&message, status),
+    }
+}
+
+pub async fn add_member(state: AppState, user: AuthUser, org_id: ObjectId, request: AddMemberRequest) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let collection: Collection<Organization> = database.collection("organizations");
+
+    let (org, caller_role) = match find_with_role(&collection, &user, org_id).await {
+        Ok(found) => found,
+        Err((status, message)) => return error_response(&message, status),
+    };
+
+    let role = request.role.unwrap_or(OrgRole::Member);
+    if caller_role < OrgRole::Manager || (role == OrgRole::Owner && caller_role < OrgRole::Owner) {
+        return error_response("Your organization role cannot add this member", StatusCode::FORBIDDEN);
+    }
+
+    let member_id = match database
+        .collection::<Document>("users")
+        .find_one(doc! { "email": request.email.trim() })
+        .await
+    {
+        Ok(Some(user_doc)) => match user_doc.get_object_id("_id") {
+            Ok(id) => id,
+            Err(_) => return error_response("Invalid user record", StatusCode::INTERNAL_SERVER_ERROR),
+        },
+        Ok(None) => return error_response("User not found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    if org.role_of(member_id).is_some() {
+        return error_response("User is already a member", StatusCode::BAD_REQUEST);
+    }
+
+    let membership = doc! {
+        "user_id": member_id,
+        "role": role.as_str(),
+        "joined_at": DateTime::now()
+    };
+
+    match collection
+        .update_one(doc! { "_id": org_id }, doc! { "$push": { "members": membership } })
+        .await
+    {
+        Ok(_) => success_response("Member added successfully", member_id.to_hex(), StatusCode::CREATED),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn update_member(
+    state: AppState,
+    user: AuthUser,
+    org_id: ObjectId,
+    member_id: ObjectId,
+    role: OrgRole,
+) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<Organization> = db.database("disaster").collection("organizations");
+
+    let (org, caller_role) = match find_with_role(&collection, &user, org_id).await {
+        Ok(found) => found,
+        Err((status, message)) => return error_response(&message, status),
+    };
+
+    if caller_role < OrgRole::Owner {
+        return error_response("Only owners can change member roles", StatusCode::FORBIDDEN);
+    }
+
+    match org.role_of(member_id) {
+        None => return error_response("Member not found", StatusCode::NOT_FOUND),
+        Some(OrgRole::Owner) if role != OrgRole::Owner && org.owner_count() == 1 => {
+            return error_response("An organization needs at least one owner", StatusCode::BAD_REQUEST);
+        }
+        Some(_) => {}
+    }
+
+    match collection
+        .update_one(
+            doc! { "_id": org_id, "members.user_id": member_id },
+            doc! { "$set": { "members.$.role": role.as_str() } },
+        )
+        .await
+    {
+        Ok(_) => success_response("Member role updated successfully", member_id.to_hex(), StatusCode::OK),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Managers remove members, owners remove anyone, and every member may leave
+pub async fn remove_member(state: AppState, user: AuthUser, org_id: ObjectId, member_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<Organization> = db.database("disaster").collection("organizations");
+
+    let (org, caller_role) = match find_with_role(&collection, &user, org_id).await {
+        Ok(found) => found,
+        Err((status, message)) => return error_response(&message, status),
+    };
+
+    let member_role = match org.role_of(member_id) {
+        Some(role) => role,
+        None => return error_response("Member not found", StatusCode::NOT_FOUND),
+    };
+
+    let leaving = member_id == user.id;
+    if !leaving && (caller_role < OrgRole::Manager || member_role > caller_role) {
+        return error_response("Your organization role cannot remove this member", StatusCode::FORBIDDEN);
+    }
+
+    if member_role == OrgRole::Owner && org.owner_count() == 1 {
+        return error_response("An organization needs at least one owner", StatusCode::BAD_REQUEST);
+    }
+
+    match collection
+        .update_one(
+            doc! { "_id": org_id },
+            doc! { "$pull": { "members": { "user_id": member_id } } },
+        )
+        .await
+    {
+        Ok(_) => success_response("Member removed successfully", member_id.to_hex(), StatusCode::OK),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,90 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+
+use super::{
+    organization_model,
+    organization_structure::{AddMemberRequest, CreateOrganizationRequest, UpdateMemberRequest},
+};
+use crate::{
+    middleware::permission::AuthUser,
+    utils::{db::AppState, response::error_response},
+};
+
+pub async fn create_organization_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<CreateOrganizationRequest>,
+) -> Response {
+    if payload.name.trim().len() < 2 {
+        return error_response("Name must be at least 2 characters long", StatusCode::BAD_REQUEST);
+    }
+
+    organization_model::create_organization(state, user.id, payload).await
+}
+
+pub async fn list_organizations_service(State(state): State<AppState>, user: AuthUser) -> Response {
+    organization_model::list_organizations(state, user).await
+}
+
+pub async fn get_organization_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(org_id): Path<String>,
+) -> Response {
+    let org_id = match ObjectId::parse_str(&org_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid organization ID format", StatusCode::BAD_REQUEST),
+    };
+
+    organization_model::get_organization(state, user, org_id).await
+}
+
+pub async fn add_member_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(org_id): Path<String>,
+    Json(payload): Json<AddMemberRequest>,
+) -> Response {
+    let org_id = match ObjectId::parse_str(&org_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid organization ID format", StatusCode::BAD_REQUEST),
+    };
+
+    if payload.email.trim().is_empty() {
+        return error_response("Email must not be empty", StatusCode::BAD_REQUEST);
+    }
+
+    organization_model::add_member(state, user, org_id, payload).await
+}
+
+pub async fn update_member_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((org_id, member_id)): Path<(String, String)>,
+    Json(payload): Json<UpdateMemberRequest>,
+) -> Response {
+    let (org_id, member_id) = match (ObjectId::parse_str(&org_id), ObjectId::parse_str(&member_id)) {
+        (Ok(org_id), Ok(member_id)) => (org_id, member_id),
+        _ => return error_response("Invalid organization or user ID format", StatusCode::BAD_REQUEST),
+    };
+
+    organization_model::update_member(state, user, org_id, member_id, payload.role).await
+}
+
+pub async fn remove_member_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((org_id, member_id)): Path<(String, String)>,
+) -> Response {
+    let (org_id, member_id) = match (ObjectId::parse_str(&org_id), ObjectId::parse_str(&member_id)) {
+        (Ok(org_id), Ok(member_id)) => (org_id, member_id),
+        _ => return error_response("Invalid organization or user ID format", StatusCode::BAD_REQUEST),
+    };
+
+    organization_model::remove_member(state, user, org_id, member_id).await
+}
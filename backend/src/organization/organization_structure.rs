@@ -0,0 +1,122 @@
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrgKind {
+    Ngo,
+    GovernmentAgency,
+    CommunityGroup,
+}
+
+/// Role inside one organization, independent of the account-wide `Role`.
+/// Variants are ordered by privilege.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum OrgRole {
+    Member,  // Manages the organization's shelters and resources
+    Manager, // Also adds and removes members
+    Owner,   // Also changes member roles
+}
+
+impl OrgRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrgRole::Member => "member",
+            OrgRole::Manager => "manager",
+            OrgRole::Owner => "owner",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Membership {
+    pub user_id: ObjectId,
+    pub role: OrgRole,
+    pub joined_at: DateTime,
+}
+
+/// NGO, agency or group owning shelters and resources, stored in `organizations`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Organization {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    pub kind: OrgKind,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub members: Vec<Membership>,
+    pub created_by: ObjectId,
+    pub created_at: DateTime,
+}
+
+impl Organization {
+    pub fn role_of(&self, user_id: ObjectId) -> Option<OrgRole> {
+        self.members
+            .iter()
+            .find(|member| member.user_id == user_id)
+            .map(|member| member.role)
+    }
+
+    pub fn owner_count(&self) -> usize {
+        self.members.iter().filter(|member| member.role == OrgRole::Owner).count()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberView {
+    pub user_id: String,
+    pub role: OrgRole,
+    pub joined_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrganizationView {
+    pub id: String,
+    pub name: String,
+    pub kind: OrgKind,
+    pub description: Option<String>,
+    pub members: Vec<MemberView>,
+    pub created_at: String,
+}
+
+impl From<Organization> for OrganizationView {
+    fn from(org: Organization) -> Self {
+        OrganizationView {
+            id: org.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: org.name,
+            kind: org.kind,
+            description: org.description,
+            members: org
+                .members
+                .into_iter()
+                .map(|member| MemberView {
+                    user_id: member.user_id.to_hex(),
+                    role: member.role,
+                    joined_at: member.joined_at.try_to_rfc3339_string().unwrap_or_default(),
+                })
+                .collect(),
+            created_at: org.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOrganizationRequest {
+    pub name: String,
+    pub kind: OrgKind,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddMemberRequest {
+    pub email: String,
+    #[serde(default)]
+    pub role: Option<OrgRole>, // Defaults to member
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMemberRequest {
+    pub role: OrgRole,
+}
@@ -1,11 +1,14 @@
 use axum::{
     middleware::from_fn, 
-    routing::{delete, get, patch, post}, Router
+    routing::{delete, get, patch, post}, Extension, Router
 };
 use resources_service::{
     create_resource_service, delete_resource_service, get_resources_service, update_resource_service,
 };
-use crate::{middleware::auth::auth_middleware, utils::db::AppState
+use crate::{
+    api_key::api_key_structure::{ApiKeyRoute, ApiScope},
+    middleware::auth::{api_key_middleware, auth_middleware},
+    utils::db::AppState,
 };
 
 pub mod resources_model;
@@ -19,7 +22,9 @@ pub fn resources_routes(state: AppState) -> Router {
         .route("/update_resource", patch(update_resource_service))
         
         .layer(from_fn(auth_middleware))
-        .route("/get_resources", get(get_resources_service)) 
+        // Public, but an API key sent here still needs the `resources:read` scope
+        .route("/get_resources", get(get_resources_service).route_layer(from_fn(api_key_middleware))) 
+        .layer(Extension(ApiKeyRoute { read: ApiScope::ResourcesRead, write: ApiScope::ResourcesWrite }))
         .with_state(state)
 }       
 
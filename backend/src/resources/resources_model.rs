@@ -1,19 +1,25 @@
+//! Database operations for managing disaster relief resources
+//!
+//! This module provides operations for resources in the MongoDB database.
+//! Each function handles database interactions and returns appropriate responses
+//! using the common response format.
+
 use axum::{extract::State, Json, http::StatusCode, response::IntoResponse};
 use futures::TryStreamExt;
-use mongodb::{bson::{self, doc, oid::ObjectId}, Collection};
-use crate::utils::{db::AppState, response::{success_response, error_response}};
+use mongodb::{bson::{self, doc, oid::ObjectId}, Collection, Database};
+use crate::{
+    incident::{incident_model::forget_mobilised, incident_structure::Mobilised},
+    middleware::permission::AuthUser,
+    organization::organization_model::{can_write, resolve_owner_org, writable_org_ids},
+    utils::{db::AppState, response::{success_response, error_response}},
+};
 use super::resources_structure::Resource;
 
-/// Database operations for managing disaster relief resources
-/// 
-/// This module provides operations for resources in the MongoDB database.
-/// Each function handles database interactions and returns appropriate responses
-/// using the common response format.
-
 /// Creates a new resource in the database
 /// 
 /// # Arguments
 /// * `state` - Application state containing the database connection
+/// * `user` - The caller; the resource is owned by their organization
 /// * `resource` - The resource data to be created
 /// 
 /// # Returns
@@ -41,10 +47,16 @@ use super::resources_structure::Resource;
 /// ```
 pub async fn create_resource(
     state: State<AppState>,
-    resource: Json<Resource>,
+    user: AuthUser,
+    mut resource: Json<Resource>,
 ) -> impl IntoResponse {
     let db = state.db.lock().await;
     let collection: Collection<Resource> = db.database("disaster").collection("resources");
+
+    resource.owner_org_id = match resolve_owner_org(&db.database("disaster"), &user, resource.owner_org_id).await {
+        Ok(org_id) => org_id,
+        Err((status, message)) => return error_response(&message, status),
+    };
    
     match collection.insert_one(resource.0.clone()).await {
         Ok(result) => {
@@ -132,10 +144,12 @@ pub async fn get_resources(
 /// 
 /// # Arguments
 /// * `state` - Application state containing the database connection
+/// * `user` - The caller, who must be able to write for the owning organization
 /// * `id` - The ObjectId of the resource to delete
 /// 
 /// # Returns
 /// * Success Response (200 OK) if resource was deleted
+/// * Error Response (403 Forbidden) if resource belongs to another organization
 /// * Error Response (404 Not Found) if resource doesn't exist
 /// * Error Response (400 Bad Request) if ID format is invalid
 /// * Error Response (500 Internal Server Error) if database operation fails
@@ -150,6 +164,7 @@ pub async fn get_resources(
 /// ```
 pub async fn delete_resource(
     state: State<AppState>,
+    user: AuthUser,
     id: String,
 ) -> impl IntoResponse {
     let db = state.db.lock().await;
@@ -160,9 +175,16 @@ pub async fn delete_resource(
         Err(_) => return error_response("Invalid ID format", StatusCode::BAD_REQUEST),
     };
 
+    if let Err((status, message)) = check_writable(&db.database("disaster"), &user, obj_id, None).await {
+        return error_response(&message, status);
+    }
+
     match collection.delete_one(doc! { "_id": obj_id }).await {
         Ok(result) => {
             if result.deleted_count == 1 {
+                if let Err(e) = forget_mobilised(&db.database("disaster"), Mobilised::Resource, obj_id).await {
+                    return error_response(&format!("Resource deleted but incidents still list it: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+                }
                 success_response(
                     "Resource deleted successfully",
                     "Resource removed from database",
@@ -183,11 +205,13 @@ pub async fn delete_resource(
 /// 
 /// # Arguments
 /// * `state` - Application state containing the database connection
+/// * `user` - The caller, who must be able to write for the owning organization
 /// * `resource` - The updated resource data
 /// * `id` - The ObjectId of the resource to update
 /// 
 /// # Returns
 /// * Success Response (200 OK) if resource was updated
+/// * Error Response (403 Forbidden) if resource belongs to another organization
 /// * Error Response (404 Not Found) if resource doesn't exist
 /// * Error Response (400 Bad Request) if ID format is invalid
 /// * Error Response (500 Internal Server Error) if database operation fails
@@ -202,7 +226,8 @@ pub async fn delete_resource(
 /// ```
 pub async fn update_resource(
     state: State<AppState>,
-    resource: Json<Resource>,
+    user: AuthUser,
+    mut resource: Json<Resource>,
     id: String,
 ) -> impl IntoResponse {
     let db = state.db.lock().await;
@@ -213,6 +238,11 @@ pub async fn update_resource(
         Err(_) => return error_response("Invalid ID format", StatusCode::BAD_REQUEST),
     };
 
+    if let Err((status, message)) = check_writable(&db.database("disaster"), &user, obj_id, resource.owner_org_id).await {
+        return error_response(&message, status);
+    }
+    resource.id = None;
+
     let update_doc = match bson::to_document(&resource.0) {
         Ok(doc) => doc! { "$set": doc },
         Err(e) => return error_response(
@@ -238,4 +268,31 @@ pub async fn update_resource(
             StatusCode::INTERNAL_SERVER_ERROR
         ),
     }
-}   
+}
+
+/// Checks that the caller may change a resource, and may hand it to `new_owner` when one is given
+async fn check_writable(
+    db: &Database,
+    user: &AuthUser,
+    obj_id: ObjectId,
+    new_owner: Option<ObjectId>,
+) -> Result<(), (StatusCode, String)> {
+    let db_error = |e: mongodb::error::Error| (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e));
+
+    let existing = db
+        .collection::<Resource>("resources")
+        .find_one(doc! { "_id": obj_id })
+        .await
+        .map_err(db_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Resource not found".to_string()))?;
+
+    let writable = writable_org_ids(db, user).await.map_err(db_error)?;
+    if !can_write(&writable, existing.owner_org_id) {
+        return Err((StatusCode::FORBIDDEN, "Resource belongs to another organization".to_string()));
+    }
+    if new_owner.is_some() && new_owner != existing.owner_org_id && !can_write(&writable, new_owner) {
+        return Err((StatusCode::FORBIDDEN, "You are not a member of that organization".to_string()));
+    }
+
+    Ok(())
+}
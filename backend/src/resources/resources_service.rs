@@ -5,9 +5,9 @@ use axum::{
     body::Body,
     Json,
 };
-use crate::utils::{
-    db::AppState, 
-    response::error_response
+use crate::{
+    middleware::permission::AuthUser,
+    utils::{db::AppState, response::error_response},
 };
 use super::{
     resources_model::{create_resource, delete_resource, get_resources, update_resource},
@@ -16,6 +16,7 @@ use super::{
 
 pub async fn create_resource_service(
     state: State<AppState>,
+    user: AuthUser,
     resource: Json<Resource>,
 ) -> Response<Body> {
     // Validate resource data
@@ -39,7 +40,7 @@ pub async fn create_resource_service(
         ).into_response();
     }
 
-    create_resource(state, resource).await.into_response()
+    create_resource(state, user, resource).await.into_response()
 }
 
 pub async fn get_resources_service(state: State<AppState>) -> Response<Body> {
@@ -49,6 +50,7 @@ pub async fn get_resources_service(state: State<AppState>) -> Response<Body> {
 
 pub async fn delete_resource_service(
     state: State<AppState>,
+    user: AuthUser,
     headers: HeaderMap,
 ) -> Response<Body> {
     let id = match headers.get("id") {
@@ -59,11 +61,12 @@ pub async fn delete_resource_service(
         None => return error_response("Missing id header", StatusCode::BAD_REQUEST).into_response(),
     };
 
-    delete_resource(state, id).await.into_response()
+    delete_resource(state, user, id).await.into_response()
 }
 
 pub async fn update_resource_service(
     state: State<AppState>,
+    user: AuthUser,
     headers: HeaderMap,
     resource: Json<Resource>,
 ) -> Response<Body> {
@@ -87,10 +90,10 @@ pub async fn update_resource_service(
         None => return error_response("Missing id header", StatusCode::BAD_REQUEST).into_response(),
     };
 
-    update_resource(state, resource, id).await.into_response()
+    update_resource(state, user, resource, id).await.into_response()
 }
 
 /// Validates if the given coordinates are within valid ranges
 fn is_valid_coordinates(latitude: f64, longitude: f64) -> bool {
-    latitude >= -90.0 && latitude <= 90.0 && longitude >= -180.0 && longitude <= 180.0
+    (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)
 }
\ No newline at end of file
@@ -11,6 +11,8 @@ pub struct Resource {
     pub description: String,
     pub location: Location,
     pub status: ResourceStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_org_id: Option<ObjectId>, // Organization holding the resource, set from the caller
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use axum::{middleware::{from_fn, from_fn_with_state}, routing::{get, post}, Router};
+use revision_service::{get_revision_service, list_revisions_service, revert_service};
+
+use crate::{middleware::{auth::auth_middleware, permission::{require_permission, Permission}}, utils::db::AppState};
+
+pub mod revision_model;
+pub mod revision_service;
+pub mod revision_structure;
+
+/// History of disaster records and guide items, nested under `/disaster/revisions`.
+/// Reverts check the permission matching the revised entity themselves.
+pub fn revision_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_revisions_service)
+            .route_layer(from_fn_with_state(Permission::ModerateGuides, require_permission)))
+        .route("/{revision_id}", get(get_revision_service)
+            .route_layer(from_fn_with_state(Permission::ModerateGuides, require_permission)))
+        .route("/{revision_id}/revert", post(revert_service))
+        .layer(from_fn(auth_middleware))
+        .with_state((*state).clone())
+}
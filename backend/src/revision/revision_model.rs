@@ -0,0 +1,383 @@
+//! Versioned writes for disaster records and guide items. Every change goes through
+//! `apply_record_change` / `apply_item_change`, which bump the entity's `revision` and store an
+//! immutable `Revision` with the author, a snapshot and a diff in the `revisions` collection.
+
+use axum::{http::StatusCode, response::Response};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, Bson, DateTime, Document},
+    Collection, Database,
+};
+
+use super::revision_structure::{
+    Revision, RevisionAction, RevisionChange, RevisionEntity, RevisionPage, RevisionTarget, RevisionView,
+};
+use crate::{
+    disaster::disaster_structure::{GuideKind, GuideStatus},
+    middleware::permission::{AuthUser, Permission},
+    utils::{
+        db::AppState,
+        paging,
+        response::{error_response, success_response},
+    },
+};
+
+/// Tracked fields of `document`; missing ones are recorded as null
+pub fn snapshot(entity: RevisionEntity, document: &Document) -> Document {
+    entity
+        .tracked_fields()
+        .iter()
+        .map(|field| (field.to_string(), document.get(*field).cloned().unwrap_or(Bson::Null)))
+        .collect()
+}
+
+fn diff(entity: RevisionEntity, before: Option<&Document>, after: &Document) -> Document {
+    let before = before.map(|before| snapshot(entity, before)).unwrap_or_default();
+    let after = snapshot(entity, after);
+
+    after
+        .iter()
+        .filter_map(|(field, to)| {
+            let from = before.get(field).cloned().unwrap_or(Bson::Null);
+            (from != *to).then(|| (field.clone(), Bson::Document(doc! { "from": from, "to": to.clone() })))
+        })
+        .collect()
+}
+
+/// Version the next change to `entity_id` will get
+pub async fn next_version(database: &Database, entity_id: ObjectId) -> mongodb::error::Result<u32> {
+    let revisions: Collection<Revision> = database.collection("revisions");
+    let latest = revisions
+        .find_one(doc! { "entity_id": entity_id })
+        .sort(doc! { "version": -1 })
+        .await?;
+
+    Ok(latest.map_or(1, |revision| revision.version + 1))
+}
+
+pub async fn record_revision(
+    database: &Database,
+    target: &RevisionTarget,
+    version: u32,
+    change: RevisionChange<'_>,
+) -> mongodb::error::Result<()> {
+    let revisions: Collection<Revision> = database.collection("revisions");
+    let revision = Revision {
+        id: None,
+        entity: target.entity,
+        entity_id: target.entity_id,
+        disaster_id: target.disaster_id,
+        kind: target.kind,
+        version,
+        action: change.action,
+        author_id: change.author_id,
+        at: DateTime::now(),
+        snapshot: snapshot(target.entity, change.after),
+        diff: diff(target.entity, change.before, change.after),
+        reverted_to: change.reverted_to,
+    };
+    revisions.insert_one(revision).await?;
+    Ok(())
+}
+
+/// Applies `changes` to a disaster record and records the revision.
+/// Returns the record as it is after the change, or `None` if it does not exist.
+pub async fn apply_record_change(
+    database: &Database,
+    dr_id: ObjectId,
+    changes: Document,
+    action: RevisionAction,
+    author_id: ObjectId,
+    reverted_to: Option<u32>,
+) -> mongodb::error::Result<Option<Document>> {
+    let records: Collection<Document> = database.collection("disaster_record");
+    let Some(before) = records.find_one(doc! { "_id": dr_id }).await? else {
+        return Ok(None);
+    };
+
+    let version = next_version(database, dr_id).await?;
+    let mut after = before.clone();
+    after.extend(changes.clone());
+    after.insert("revision", version);
+
+    let mut set = changes;
+    set.insert("revision", version);
+    records.update_one(doc! { "_id": dr_id }, doc! { "$set": set }).await?;
+
+    let target = RevisionTarget {
+        entity: RevisionEntity::DisasterRecord,
+        entity_id: dr_id,
+        disaster_id: dr_id,
+        kind: None,
+    };
+    record_revision(
+        database,
+        &target,
+        version,
+        RevisionChange { action, author_id, before: Some(&before), after: &after, reverted_to },
+    )
+    .await?;
+
+    Ok(Some(after))
+}
+
+/// The guide item `gi_id` of disaster `dr_id` as stored
+pub async fn find_guide_item(
+    database: &Database,
+    dr_id: ObjectId,
+    kind: GuideKind,
+    gi_id: ObjectId,
+) -> mongodb::error::Result<Option<Document>> {
+    let guides: Collection<Document> = database.collection("disaster_guide");
+    let field = kind.field();
+
+    let guide = guides
+        .find_one(doc! { "disaster_id": dr_id, format!("{}._id", field): gi_id })
+        .await?;
+
+    Ok(guide.and_then(|guide| {
+        guide
+            .get_array(field)
+            .ok()?
+            .iter()
+            .filter_map(|item| item.as_document())
+            .find(|item| item.get_object_id("_id").ok() == Some(gi_id))
+            .cloned()
+    }))
+}
+
+/// The guide item after `changes`, and the update writing it into the `field` array. Whenever the
+/// item ends up `Accepted`, its message becomes the published text served to the public; a
+/// rejection takes it off public reads. Otherwise the previously published text (if any) stays
+/// live while the change waits for review.
+fn item_update(field: &str, before: &Document, changes: &Document, version: u32) -> (Document, Document) {
+    let mut after = before.clone();
+    after.extend(changes.clone());
+    after.insert("revision", version);
+
+    let status = after.get_str("status").unwrap_or_default().to_string();
+    let mut unset = Document::new();
+    if status == GuideStatus::Accepted.as_str() {
+        let message = after.get_str("message").unwrap_or_default().to_string();
+        after.insert("published", doc! { "revision": version, "message": message });
+    } else if status == GuideStatus::Rejected.as_str() && after.remove("published").is_some() {
+        unset.insert(format!("{}.$.published", field), "");
+    }
+
+    let set: Document = after
+        .iter()
+        .filter(|(key, _)| changes.contains_key(*key) || *key == "revision" || *key == "published")
+        .map(|(key, value)| (format!("{}.$.{}", field, key), value.clone()))
+        .collect();
+
+    let mut update = doc! { "$set": set };
+    if !unset.is_empty() {
+        update.insert("$unset", unset);
+    }
+    (after, update)
+}
+
+/// Applies `changes` to a guide item and records the revision (see `item_update` for what
+/// gets published)
+pub async fn apply_item_change(
+    database: &Database,
+    target: &RevisionTarget,
+    changes: Document,
+    action: RevisionAction,
+    author_id: ObjectId,
+    reverted_to: Option<u32>,
+) -> mongodb::error::Result<Option<Document>> {
+    let kind = target.kind.unwrap_or(GuideKind::Do);
+    let Some(before) = find_guide_item(database, target.disaster_id, kind, target.entity_id).await? else {
+        return Ok(None);
+    };
+
+    let version = next_version(database, target.entity_id).await?;
+    let field = kind.field();
+    let (after, update) = item_update(field, &before, &changes, version);
+
+    let guides: Collection<Document> = database.collection("disaster_guide");
+    let updated = guides
+        .update_one(
+            doc! { "disaster_id": target.disaster_id, format!("{}._id", field): target.entity_id },
+            update,
+        )
+        .await?;
+    if updated.matched_count == 0 {
+        return Ok(None);
+    }
+
+    record_revision(
+        database,
+        target,
+        version,
+        RevisionChange { action, author_id, before: Some(&before), after: &after, reverted_to },
+    )
+    .await?;
+
+    Ok(Some(after))
+}
+
+pub async fn list_revisions(
+    state: AppState,
+    disaster_id: ObjectId,
+    item_id: Option<ObjectId>,
+    page: Option<u64>,
+    per_page: Option<i64>,
+) -> Response {
+    let db = state.db.lock().await;
+    let revisions: Collection<Revision> = db.database("disaster").collection("revisions");
+
+    let mut filter = doc! { "disaster_id": disaster_id };
+    if let Some(item_id) = item_id {
+        filter.insert("entity_id", item_id);
+    }
+
+    let (page, per_page) = paging::page_window(page, per_page);
+
+    let total = match revisions.count_documents(filter.clone()).await {
+        Ok(total) => total,
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match revisions
+        .find(filter)
+        .sort(doc! { "at": -1, "_id": -1 })
+        .skip(paging::skip(page, per_page))
+        .limit(per_page)
+        .await
+    {
+        Ok(cursor) => match cursor.try_collect::<Vec<Revision>>().await {
+            Ok(found) => {
+                let revisions = found.into_iter().map(RevisionView::from).collect();
+                let page = RevisionPage { revisions, page, per_page, total };
+                success_response("Revisions retrieved successfully", page, StatusCode::OK)
+            }
+            Err(e) => error_response(&format!("Failed to collect revisions: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        },
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn get_revision(state: AppState, revision_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let revisions: Collection<Revision> = db.database("disaster").collection("revisions");
+
+    match revisions.find_one(doc! { "_id": revision_id }).await {
+        Ok(Some(revision)) => success_response("Revision retrieved successfully", RevisionView::from(revision), StatusCode::OK),
+        Ok(None) => error_response("Revision not found", StatusCode::NOT_FOUND),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Restores the tracked content of a prior revision as a new revision. Records need
+/// `ManageDisasters`; guide items need `ModerateGuides`, and a reverted item is published.
+pub async fn revert(state: AppState, user: AuthUser, revision_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let revisions: Collection<Revision> = database.collection("revisions");
+
+    let revision = match revisions.find_one(doc! { "_id": revision_id }).await {
+        Ok(Some(revision)) => revision,
+        Ok(None) => return error_response("Revision not found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let required = match revision.entity {
+        RevisionEntity::DisasterRecord => Permission::ManageDisasters,
+        RevisionEntity::GuideItem => Permission::ModerateGuides,
+    };
+    if !user.has_permission(required) {
+        return error_response(
+            &format!("Forbidden: '{}' permission is required", required.as_str()),
+            StatusCode::FORBIDDEN,
+        );
+    }
+
+    if revision.action == RevisionAction::Delete {
+        return error_response("Cannot revert to a deletion", StatusCode::BAD_REQUEST);
+    }
+
+    let result = match revision.entity {
+        RevisionEntity::DisasterRecord => {
+            let changes: Document = revision
+                .snapshot
+                .iter()
+                .filter(|(_, value)| !matches!(value, Bson::Null))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            apply_record_change(
+                &database,
+                revision.entity_id,
+                changes,
+                RevisionAction::Revert,
+                user.id,
+                Some(revision.version),
+            )
+            .await
+        }
+        RevisionEntity::GuideItem => {
+            let message = revision.snapshot.get_str("message").unwrap_or_default();
+            let changes = doc! {
+                "message": message,
+                "status": GuideStatus::Accepted.as_str(),
+                "reason": Bson::Null,
+                "moderated_by": user.id,
+                "moderated_at": DateTime::now(),
+            };
+            let target = RevisionTarget {
+                entity: revision.entity,
+                entity_id: revision.entity_id,
+                disaster_id: revision.disaster_id,
+                kind: revision.kind,
+            };
+            apply_item_change(&database, &target, changes, RevisionAction::Revert, user.id, Some(revision.version)).await
+        }
+    };
+
+    match result {
+        Ok(Some(current)) => success_response(
+            &format!("Reverted to revision {}", revision.version),
+            snapshot(revision.entity, &current),
+            StatusCode::OK,
+        ),
+        Ok(None) => error_response("The revised entity no longer exists", StatusCode::NOT_FOUND),
+        Err(e) => error_response(&format!("Revert failed: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disaster::disaster_model::is_published;
+
+    fn moderated(item: &Document, status: GuideStatus, version: u32) -> (Document, Document) {
+        item_update("do_s", item, &doc! { "status": status.as_str(), "reason": "Checked" }, version)
+    }
+
+    #[test]
+    fn rejecting_an_accepted_item_takes_it_off_public_reads() {
+        let submitted = doc! { "_id": ObjectId::new(), "message": "Boil drinking water", "status": "Pending" };
+
+        let (accepted, update) = moderated(&submitted, GuideStatus::Accepted, 2);
+        assert!(is_published(&accepted));
+        assert!(update.get_document("$set").unwrap().contains_key("do_s.$.published"));
+
+        let (rejected, update) = moderated(&accepted, GuideStatus::Rejected, 3);
+        assert!(!is_published(&rejected));
+        assert!(!update.get_document("$set").unwrap().contains_key("do_s.$.published"));
+        assert!(update.get_document("$unset").unwrap().contains_key("do_s.$.published"));
+    }
+
+    #[test]
+    fn a_pending_edit_keeps_the_accepted_text_live() {
+        let submitted = doc! { "_id": ObjectId::new(), "message": "Boil drinking water", "status": "Pending" };
+        let (accepted, _) = moderated(&submitted, GuideStatus::Accepted, 2);
+
+        let edit = doc! { "message": "Boil drinking water for a minute", "status": "Pending" };
+        let (edited, update) = item_update("do_s", &accepted, &edit, 3);
+        assert!(is_published(&edited));
+        assert_eq!(edited.get_document("published").unwrap().get_str("message"), Ok("Boil drinking water"));
+        assert!(update.get_document("$unset").is_err());
+    }
+}
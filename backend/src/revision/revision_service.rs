@@ -0,0 +1,49 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Response,
+};
+use mongodb::bson::oid::ObjectId;
+
+use super::{revision_model, revision_structure::RevisionQuery};
+use crate::{
+    middleware::permission::AuthUser,
+    utils::{db::AppState, response::error_response},
+};
+
+pub async fn list_revisions_service(
+    State(state): State<AppState>,
+    Query(query): Query<RevisionQuery>,
+) -> Response {
+    let disaster_id = match ObjectId::parse_str(&query.disaster_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid disaster record ID format", StatusCode::BAD_REQUEST),
+    };
+    let item_id = match query.item_id.as_deref().map(ObjectId::parse_str).transpose() {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid guide item ID format", StatusCode::BAD_REQUEST),
+    };
+
+    revision_model::list_revisions(state, disaster_id, item_id, query.page, query.per_page).await
+}
+
+pub async fn get_revision_service(
+    State(state): State<AppState>,
+    Path(revision_id): Path<String>,
+) -> Response {
+    match ObjectId::parse_str(&revision_id) {
+        Ok(revision_id) => revision_model::get_revision(state, revision_id).await,
+        Err(_) => error_response("Invalid revision ID format", StatusCode::BAD_REQUEST),
+    }
+}
+
+pub async fn revert_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(revision_id): Path<String>,
+) -> Response {
+    match ObjectId::parse_str(&revision_id) {
+        Ok(revision_id) => revision_model::revert(state, user, revision_id).await,
+        Err(_) => error_response("Invalid revision ID format", StatusCode::BAD_REQUEST),
+    }
+}
@@ -0,0 +1,124 @@
+use mongodb::bson::{oid::ObjectId, DateTime, Document};
+use serde::{Deserialize, Serialize};
+
+use crate::disaster::disaster_structure::GuideKind;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionEntity {
+    DisasterRecord,
+    GuideItem,
+}
+
+impl RevisionEntity {
+    /// Fields captured in snapshots and diffs
+    pub fn tracked_fields(&self) -> &'static [&'static str] {
+        match self {
+            RevisionEntity::DisasterRecord => &["name", "effects", "short_description", "youtube_link", "hazard", "severity"],
+            RevisionEntity::GuideItem => &["message", "status", "reason"],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionAction {
+    Create,
+    Update,
+    Moderate,
+    Revert,
+    Delete,
+    Flag, // Sent back to review after enough abuse reports
+}
+
+/// Immutable entry in the `revisions` collection, written on every change to a disaster record
+/// or guide item. `version` counts up per entity and matches the entity's `revision` field.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Revision {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub entity: RevisionEntity,
+    pub entity_id: ObjectId,
+    pub disaster_id: ObjectId,
+    #[serde(default)]
+    pub kind: Option<GuideKind>, // Set for guide items
+    pub version: u32,
+    pub action: RevisionAction,
+    pub author_id: ObjectId,
+    pub at: DateTime,
+    pub snapshot: Document, // Tracked fields after the change
+    pub diff: Document,     // `{ field: { from, to } }` for every tracked field that changed
+    #[serde(default)]
+    pub reverted_to: Option<u32>, // Version restored by a revert
+}
+
+/// What a revision is about
+#[derive(Debug, Clone, Copy)]
+pub struct RevisionTarget {
+    pub entity: RevisionEntity,
+    pub entity_id: ObjectId,
+    pub disaster_id: ObjectId,
+    pub kind: Option<GuideKind>,
+}
+
+/// The change being recorded; `before` is `None` on creation
+#[derive(Debug)]
+pub struct RevisionChange<'a> {
+    pub action: RevisionAction,
+    pub author_id: ObjectId,
+    pub before: Option<&'a Document>,
+    pub after: &'a Document,
+    pub reverted_to: Option<u32>,
+}
+
+/// Query of `GET /disaster/revisions`; newest first, pages start at 1
+#[derive(Debug, Deserialize)]
+pub struct RevisionQuery {
+    pub disaster_id: String,
+    pub item_id: Option<String>, // Only this guide item; otherwise the record and all its items
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevisionView {
+    pub id: String,
+    pub entity: RevisionEntity,
+    pub entity_id: String,
+    pub disaster_id: String,
+    pub kind: Option<GuideKind>,
+    pub version: u32,
+    pub action: RevisionAction,
+    pub author_id: String,
+    pub at: String,
+    pub snapshot: Document,
+    pub diff: Document,
+    pub reverted_to: Option<u32>,
+}
+
+impl From<Revision> for RevisionView {
+    fn from(revision: Revision) -> Self {
+        RevisionView {
+            id: revision.id.map(|id| id.to_hex()).unwrap_or_default(),
+            entity: revision.entity,
+            entity_id: revision.entity_id.to_hex(),
+            disaster_id: revision.disaster_id.to_hex(),
+            kind: revision.kind,
+            version: revision.version,
+            action: revision.action,
+            author_id: revision.author_id.to_hex(),
+            at: revision.at.try_to_rfc3339_string().unwrap_or_default(),
+            snapshot: revision.snapshot,
+            diff: revision.diff,
+            reverted_to: revision.reverted_to,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevisionPage {
+    pub revisions: Vec<RevisionView>,
+    pub page: u64,
+    pub per_page: i64,
+    pub total: u64,
+}
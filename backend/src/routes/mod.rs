@@ -3,28 +3,54 @@ use std::sync::Arc;
 use axum::middleware::from_fn;
 use axum::{Extension, Router};
 use tower::ServiceBuilder;
-use crate::{shelters, user, disaster};
+use crate::{admin, api_key, citizen, events, incident, jwt, organization, shelters, user, disaster};
 use crate::utils::db::AppState;
 use crate::middleware::log::log_request;
 use crate::resources;
-use tower_http::cors::{CorsLayer, Any};
-use http::{Request, Response, Method, header};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use http::{header, HeaderName, HeaderValue, Method};
+use crate::utils::mail::app_base_url;
 
 pub fn merge_routes(state: Arc<AppState>) -> Router  {
     let cors = CorsLayer::new()
-    // allow GET and POST when accessing the resource
-    .allow_methods([Method::GET, Method::POST])
-    // allow requests from any origin
-    .allow_origin(Any);
+    // allow the methods used by the API when accessing the resource
+    .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
+    .allow_headers([
+        header::AUTHORIZATION,
+        header::CONTENT_TYPE,
+        HeaderName::from_static("x-api-key"),
+        HeaderName::from_static("x-csrf-token"),
+    ])
+    // browsers only send the auth cookies to origins listed explicitly
+    .allow_origin(allowed_origins())
+    .allow_credentials(true);
 
 
     Router::new()
 
         .nest("/shelters", shelters::shelters_routes(state.clone())) 
         .nest("/user", user::user_routes(state.clone()))
+        .nest("/citizen", citizen::citizen_routes(state.clone()))
         .nest("/resources", resources::resources_routes((*state).clone()))
         .nest("/disaster", disaster::create_routes(state.clone())) 
+        .nest("/incidents", incident::incident_routes(state.clone()))
+        .nest("/events", events::events_routes(state.clone()))
+        .nest("/admin", admin::admin_routes(state.clone()))
+        .nest("/api_keys", api_key::api_key_routes(state.clone()))
+        .nest("/organizations", organization::organization_routes(state.clone()))
+        .nest("/.well-known", jwt::jwks_routes(state.clone()))
         .layer(Extension(state.clone())) 
         .layer(from_fn(log_request))
         .layer(ServiceBuilder::new().layer(cors))
-}
\ No newline at end of file
+}
+
+/// Origins allowed to call the API from a browser, from `CORS_ALLOWED_ORIGINS` (comma separated).
+/// Defaults to the frontend at `APP_BASE_URL`.
+fn allowed_origins() -> AllowOrigin {
+    let origins = std::env::var("CORS_ALLOWED_ORIGINS").unwrap_or_else(|_| app_base_url());
+    AllowOrigin::list(
+        origins
+            .split(',')
+            .filter_map(|origin| HeaderValue::from_str(origin.trim().trim_end_matches('/')).ok()),
+    )
+}
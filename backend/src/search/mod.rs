@@ -0,0 +1,18 @@
+use std::sync::Arc;
+
+use axum::{middleware::from_fn, routing::get, Router};
+use search_service::search_service;
+
+use crate::{middleware::auth::auth_middleware, utils::db::AppState};
+
+pub mod search_model;
+pub mod search_service;
+pub mod search_structure;
+
+/// Full-text search over disaster records and published guidance, nested under `/disaster/search`
+pub fn search_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(search_service))
+        .layer(from_fn(auth_middleware))
+        .with_state((*state).clone())
+}
@@ -0,0 +1,497 @@
+//! Full-text search over disaster records, published guide items and accepted translations,
+//! backed by MongoDB text indexes. Originals are indexed and queried with the stemming language
+//! of `DEFAULT_LOCALE`; translations are indexed in their own language (`search_language`) and
+//! queried in the caller's. Highlighting applies the same Snowball stemmers as MongoDB.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::{http::StatusCode, response::Response};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, from_document, oid::ObjectId, Bson, Document},
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use rust_stemmers::{Algorithm, Stemmer};
+use tokio::sync::OnceCell;
+
+use super::search_structure::{Highlight, SearchHit, SearchResults};
+use crate::{
+    disaster::{
+        disaster_model::{counter, is_published, published_items},
+        disaster_structure::{GuideKind, GuideStatus},
+    },
+    hazard::hazard_model,
+    revision::revision_structure::RevisionEntity,
+    translation::{
+        translation_model::{published_revision, translatable_fields},
+        translation_structure::Translation,
+    },
+    utils::{
+        db::AppState,
+        locale::default_locale,
+        response::{error_response, success_response},
+    },
+};
+
+/// Languages with stemming support in MongoDB text indexes, by base language tag
+const LANGUAGES: &[(&str, &str, Algorithm)] = &[
+    ("da", "danish", Algorithm::Danish),
+    ("de", "german", Algorithm::German),
+    ("en", "english", Algorithm::English),
+    ("es", "spanish", Algorithm::Spanish),
+    ("fi", "finnish", Algorithm::Finnish),
+    ("fr", "french", Algorithm::French),
+    ("hu", "hungarian", Algorithm::Hungarian),
+    ("it", "italian", Algorithm::Italian),
+    ("nb", "norwegian", Algorithm::Norwegian),
+    ("nl", "dutch", Algorithm::Dutch),
+    ("no", "norwegian", Algorithm::Norwegian),
+    ("pt", "portuguese", Algorithm::Portuguese),
+    ("ro", "romanian", Algorithm::Romanian),
+    ("ru", "russian", Algorithm::Russian),
+    ("sv", "swedish", Algorithm::Swedish),
+    ("tr", "turkish", Algorithm::Turkish),
+];
+
+/// Characters of context kept around the first match of a long field
+const FRAGMENT_CONTEXT: usize = 60;
+const FRAGMENT_LENGTH: usize = 200;
+
+/// Created on the first search rather than at startup, so the server starts without the database
+static TEXT_INDEXES: OnceCell<()> = OnceCell::const_new();
+
+fn language(locale: &str) -> Option<&'static (&'static str, &'static str, Algorithm)> {
+    let base = locale.split('-').next().unwrap_or_default();
+    LANGUAGES.iter().find(|(tag, _, _)| *tag == base)
+}
+
+/// MongoDB text search language for a locale; `none` disables stemming
+pub fn text_search_language(locale: &str) -> &'static str {
+    language(locale).map_or("none", |(_, name, _)| name)
+}
+
+async fn ensure_text_indexes(database: &Database) -> mongodb::error::Result<()> {
+    let language = text_search_language(&default_locale()).to_string();
+    let options = |default_language: String| {
+        IndexOptions::builder().name("search_text".to_string()).default_language(default_language)
+    };
+
+    database
+        .collection::<Document>("disaster_record")
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "name": "text", "short_description": "text", "effects": "text" })
+                .options(options(language.clone()).weights(doc! { "name": 10, "short_description": 5, "effects": 2 }).build())
+                .build(),
+        )
+        .await?;
+    database
+        .collection::<Document>("disaster_guide")
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "do_s.message": "text", "dont_s.message": "text" })
+                .options(options(language).build())
+                .build(),
+        )
+        .await?;
+    database
+        .collection::<Document>("translations")
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "text": "text" })
+                .options(options("none".to_string()).language_override("search_language".to_string()).build())
+                .build(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Lowercased words of `text` with their byte ranges
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(from)) => {
+                words.push((from, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+fn stem(stemmer: Option<&Stemmer>, word: &str) -> String {
+    let word = word.to_lowercase();
+    match stemmer {
+        Some(stemmer) => stemmer.stem(&word).into_owned(),
+        None => word,
+    }
+}
+
+/// Stems of the words a query looks for; negated terms (`-word`) are left out
+fn query_stems(query: &str, stemmer: Option<&Stemmer>) -> HashSet<String> {
+    let mut in_phrase = false;
+    let mut stems = HashSet::new();
+    for token in query.split_whitespace() {
+        let negated = !in_phrase && token.starts_with('-');
+        if !negated {
+            stems.extend(words(token).into_iter().map(|(start, end)| stem(stemmer, &token[start..end])));
+        }
+        if token.matches('"').count() % 2 == 1 {
+            in_phrase = !in_phrase;
+        }
+    }
+    stems
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Fragment of `text` around its first match, or `None` if no word matches
+fn highlight(text: &str, stems: &HashSet<String>, stemmer: Option<&Stemmer>) -> Option<String> {
+    let matches: Vec<(usize, usize)> = words(text)
+        .into_iter()
+        .filter(|(start, end)| stems.contains(&stem(stemmer, &text[*start..*end])))
+        .collect();
+    let (first, _) = *matches.first()?;
+
+    let mut start = text[..first].char_indices().rev().nth(FRAGMENT_CONTEXT).map_or(0, |(index, _)| index);
+    if start > 0 {
+        // Do not cut a word in half
+        start = text[start..first].find(char::is_whitespace).map_or(first, |offset| start + offset + 1);
+    }
+    let mut end = text[start..].char_indices().nth(FRAGMENT_LENGTH).map_or(text.len(), |(index, _)| start + index);
+    if end < text.len() {
+        end = text[first..end].rfind(char::is_whitespace).map_or(end, |offset| first + offset);
+    }
+
+    let mut fragment = String::from(if start > 0 { "…" } else { "" });
+    let mut position = start;
+    for (word_start, word_end) in matches.into_iter().filter(|(s, e)| *s >= start && *e <= end) {
+        fragment.push_str(&escape_html(&text[position..word_start]));
+        fragment.push_str(&format!("<mark>{}</mark>", escape_html(&text[word_start..word_end])));
+        position = word_end;
+    }
+    fragment.push_str(&escape_html(&text[position..end]));
+    if end < text.len() {
+        fragment.push('…');
+    }
+    Some(fragment)
+}
+
+fn kind_of(label: &str) -> GuideKind {
+    if label == "do_s" { GuideKind::Do } else { GuideKind::Dont }
+}
+
+/// Published item `item_id` of a stored guide with its kind
+fn published_item(guide: &Document, item_id: ObjectId) -> Option<(GuideKind, &Document)> {
+    [GuideKind::Do, GuideKind::Dont].into_iter().find_map(|kind| {
+        guide
+            .get_array(kind.field())
+            .ok()?
+            .iter()
+            .filter_map(Bson::as_document)
+            .find(|item| item.get_object_id("_id").ok() == Some(item_id) && is_published(item))
+            .map(|item| (kind, item))
+    })
+}
+
+/// Runs `query` against originals and, when the caller prefers another language than
+/// `DEFAULT_LOCALE`, against accepted translations that are still current. Hits are ranked by
+/// text score; an entity matched in several fields of one locale is returned once.
+/// With `hazard`, only disasters filed under that taxonomy node or its subtypes are searched.
+pub async fn search(state: AppState, query: String, chain: Vec<String>, hazard: Option<String>, limit: i64) -> Response {
+    let default = default_locale();
+    let preferred: Vec<&String> = chain.iter().take_while(|locale| **locale != default).collect();
+    let locale = preferred.first().map_or(default.clone(), |locale| locale.to_string());
+    let translated_language = text_search_language(&locale);
+    // Translations share one `$language` per query, so only locales stemmed alike are searched
+    let translation_locales: Vec<&String> = preferred
+        .into_iter()
+        .filter(|preferred| text_search_language(preferred) == translated_language)
+        .collect();
+
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    if let Err(e) = TEXT_INDEXES.get_or_try_init(|| ensure_text_indexes(&database)).await {
+        return error_response(&format!("Failed to prepare search indexes: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    // `$text` must come first in every filter, so the hazard narrows the rest of it
+    let mut record_filter = doc! { "$text": { "$search": &query, "$language": text_search_language(&default) } };
+    let mut guide_filter = record_filter.clone();
+    let mut translation_filter = doc! {
+        "$text": { "$search": &query, "$language": translated_language },
+        "status": GuideStatus::Accepted.as_str(),
+        "locale": { "$in": &translation_locales },
+    };
+    if let Some(hazard) = hazard.as_deref() {
+        let disaster_ids = match hazard_model::hazard_filter(&database, hazard).await {
+            Ok(Some(codes)) => {
+                record_filter.insert("hazard", doc! { "$in": &codes });
+                database
+                    .collection::<Document>("disaster_record")
+                    .distinct("_id", doc! { "hazard": { "$in": codes } })
+                    .await
+            }
+            Ok(None) => return error_response("Hazard not found", StatusCode::NOT_FOUND),
+            Err(e) => Err(e),
+        };
+        match disaster_ids {
+            Ok(disaster_ids) => {
+                guide_filter.insert("disaster_id", doc! { "$in": &disaster_ids });
+                translation_filter.insert("disaster_id", doc! { "$in": disaster_ids });
+            }
+            Err(e) => return error_response(&format!("Failed to search: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+
+    let scored = |filter: Document| {
+        vec![
+            doc! { "$match": filter },
+            doc! { "$addFields": { "score": { "$meta": "textScore" } } },
+            doc! { "$sort": { "score": -1 } },
+            doc! { "$limit": limit },
+        ]
+    };
+
+    let loaded = async {
+        let records: Vec<Document> = database
+            .collection::<Document>("disaster_record")
+            .aggregate(scored(record_filter))
+            .await?
+            .try_collect()
+            .await?;
+
+        let mut guide_pipeline = scored(guide_filter);
+        guide_pipeline.push(doc! {
+            "$project": { "disaster_id": 1, "score": 1, "do_s": published_items("$do_s"), "dont_s": published_items("$dont_s") }
+        });
+        let guides: Vec<Document> = database
+            .collection::<Document>("disaster_guide")
+            .aggregate(guide_pipeline)
+            .await?
+            .try_collect()
+            .await?;
+
+        let translations: Vec<Document> = if translation_locales.is_empty() {
+            Vec::new()
+        } else {
+            database
+                .collection::<Document>("translations")
+                .aggregate(scored(translation_filter))
+                .await?
+                .try_collect()
+                .await?
+        };
+
+        // Names of every disaster involved, and the current state of translated entities
+        let disaster_ids: Vec<ObjectId> = guides
+            .iter()
+            .chain(&translations)
+            .filter_map(|hit| hit.get_object_id("disaster_id").ok())
+            .collect();
+        let disasters: Vec<Document> = database
+            .collection::<Document>("disaster_record")
+            .find(doc! { "_id": { "$in": &disaster_ids } })
+            .projection(doc! { "name": 1, "revision": 1 })
+            .await?
+            .try_collect()
+            .await?;
+        let translated_guides: Vec<Document> = database
+            .collection::<Document>("disaster_guide")
+            .find(doc! { "disaster_id": { "$in": &disaster_ids } })
+            .await?
+            .try_collect()
+            .await?;
+
+        mongodb::error::Result::Ok((records, guides, translations, disasters, translated_guides))
+    };
+    let (records, guides, translations, disasters, translated_guides) = match loaded.await {
+        Ok(loaded) => loaded,
+        Err(e) => return error_response(&format!("Failed to search: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    drop(db);
+
+    let disasters: HashMap<ObjectId, &Document> = disasters
+        .iter()
+        .chain(&records)
+        .filter_map(|record| Some((record.get_object_id("_id").ok()?, record)))
+        .collect();
+    let name_of = |disaster_id: ObjectId| {
+        disasters.get(&disaster_id).and_then(|record| record.get_str("name").ok()).unwrap_or_default().to_string()
+    };
+    let translated_guides: HashMap<ObjectId, &Document> = translated_guides
+        .iter()
+        .filter_map(|guide| Some((guide.get_object_id("disaster_id").ok()?, guide)))
+        .collect();
+
+    let original_stemmer = language(&default).map(|(_, _, algorithm)| Stemmer::create(*algorithm));
+    let original_stems = query_stems(&query, original_stemmer.as_ref());
+    let mut hits: Vec<SearchHit> = Vec::new();
+
+    for record in &records {
+        let Ok(disaster_id) = record.get_object_id("_id") else { continue };
+        hits.push(SearchHit {
+            entity: RevisionEntity::DisasterRecord,
+            disaster_id: disaster_id.to_hex(),
+            disaster_name: name_of(disaster_id),
+            kind: None,
+            item_id: None,
+            locale: default.clone(),
+            score: record.get_f64("score").unwrap_or(0.0),
+            highlights: translatable_fields(RevisionEntity::DisasterRecord)
+                .iter()
+                .filter_map(|field| {
+                    let fragment = highlight(record.get_str(field).ok()?, &original_stems, original_stemmer.as_ref())?;
+                    Some(Highlight { field: field.to_string(), fragment })
+                })
+                .collect(),
+        });
+    }
+
+    // The text score covers a whole guide; each matching item gets the share of query words it contains
+    for guide in &guides {
+        let Ok(disaster_id) = guide.get_object_id("disaster_id") else { continue };
+        let score = guide.get_f64("score").unwrap_or(0.0);
+        for field in ["do_s", "dont_s"] {
+            let items = guide.get_array(field).map(|items| items.as_slice()).unwrap_or_default();
+            for item in items.iter().filter_map(Bson::as_document) {
+                let (Ok(item_id), Ok(message)) = (item.get_object_id("_id"), item.get_str("message")) else { continue };
+                let Some(fragment) = highlight(message, &original_stems, original_stemmer.as_ref()) else { continue };
+                let matched: HashSet<String> = words(message)
+                    .into_iter()
+                    .map(|(start, end)| stem(original_stemmer.as_ref(), &message[start..end]))
+                    .filter(|word| original_stems.contains(word))
+                    .collect();
+
+                hits.push(SearchHit {
+                    entity: RevisionEntity::GuideItem,
+                    disaster_id: disaster_id.to_hex(),
+                    disaster_name: name_of(disaster_id),
+                    kind: Some(kind_of(field)),
+                    item_id: Some(item_id.to_hex()),
+                    locale: default.clone(),
+                    score: score * matched.len() as f64 / original_stems.len().max(1) as f64,
+                    highlights: vec![Highlight { field: "message".to_string(), fragment }],
+                });
+            }
+        }
+    }
+
+    let translated_stemmer = language(&locale).map(|(_, _, algorithm)| Stemmer::create(*algorithm));
+    let translated_stems = query_stems(&query, translated_stemmer.as_ref());
+    let mut merged: HashMap<(ObjectId, String), usize> = HashMap::new();
+    for document in translations {
+        let score = document.get_f64("score").unwrap_or(0.0);
+        let Ok(translation) = from_document::<Translation>(document) else { continue };
+
+        // Stale translations are not served, so they are not found either
+        let current = match translation.entity {
+            RevisionEntity::DisasterRecord => disasters
+                .get(&translation.entity_id)
+                .map(|record| counter(record, "revision")),
+            RevisionEntity::GuideItem => translated_guides
+                .get(&translation.disaster_id)
+                .and_then(|guide| published_item(guide, translation.entity_id))
+                .map(|(_, item)| published_revision(item)),
+        };
+        if current != Some(translation.source_revision) {
+            continue;
+        }
+
+        let highlight = highlight(&translation.text, &translated_stems, translated_stemmer.as_ref())
+            .map(|fragment| Highlight { field: translation.field.clone(), fragment });
+        let key = (translation.entity_id, translation.locale.clone());
+        if let Some(hit) = merged.get(&key).map(|index| &mut hits[*index]) {
+            hit.score += score;
+            hit.highlights.extend(highlight);
+            continue;
+        }
+
+        merged.insert(key, hits.len());
+        hits.push(SearchHit {
+            entity: translation.entity,
+            disaster_id: translation.disaster_id.to_hex(),
+            disaster_name: name_of(translation.disaster_id),
+            kind: translation.kind,
+            item_id: (translation.entity == RevisionEntity::GuideItem).then(|| translation.entity_id.to_hex()),
+            locale: translation.locale,
+            score,
+            highlights: highlight.into_iter().collect(),
+        });
+    }
+
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(limit as usize);
+
+    success_response("Search completed successfully", SearchResults { query, locale, hits }, StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(words: &[&str]) -> HashSet<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn query_stems_skip_negated_terms_but_not_inside_phrases() {
+        assert_eq!(query_stems("Flood -evacuation \"boil water\"", None), set(&["flood", "boil", "water"]));
+        assert_eq!(query_stems("\"stay -calm\" -panic", None), set(&["stay", "calm"]));
+        assert_eq!(query_stems("-", None), set(&[]));
+
+        let english = Stemmer::create(Algorithm::English);
+        assert_eq!(query_stems("Flooding shelters", Some(&english)), set(&["flood", "shelter"]));
+    }
+
+    #[test]
+    fn highlight_marks_stemmed_matches_in_multibyte_text() {
+        let stems = set(&["inondation", "zürich"]);
+        assert_eq!(
+            highlight("Évacuez vers les abris — inondation à Zürich", &stems, None).as_deref(),
+            Some("Évacuez vers les abris — <mark>inondation</mark> à <mark>Zürich</mark>")
+        );
+
+        let english = Stemmer::create(Algorithm::English);
+        assert_eq!(
+            highlight("Rivers flooded overnight", &query_stems("floods", Some(&english)), Some(&english)).as_deref(),
+            Some("Rivers <mark>flooded</mark> overnight")
+        );
+        assert_eq!(highlight("Nothing relevant here", &stems, None), None);
+    }
+
+    #[test]
+    fn highlight_escapes_html_around_marks() {
+        assert_eq!(
+            highlight("Use <b>bleach</b> & water", &set(&["bleach"]), None).as_deref(),
+            Some("Use &lt;b&gt;<mark>bleach</mark>&lt;/b&gt; &amp; water")
+        );
+    }
+
+    #[test]
+    fn highlight_cuts_long_text_on_word_boundaries() {
+        let text = format!("{}séisme{}", "réfugié ".repeat(30), " après".repeat(60));
+        let fragment = highlight(&text, &set(&["séisme"]), None).unwrap();
+
+        assert!(fragment.starts_with("…réfugié "), "{}", fragment);
+        assert!(fragment.ends_with("après…"), "{}", fragment);
+        assert!(fragment.contains("<mark>séisme</mark>"));
+        assert!(fragment.chars().count() <= FRAGMENT_LENGTH + "<mark></mark>".len() + 2);
+    }
+
+    #[test]
+    fn text_search_language_uses_the_base_language() {
+        assert_eq!(text_search_language("pt-br"), "portuguese");
+        assert_eq!(text_search_language("en"), "english");
+        assert_eq!(text_search_language("sw"), "none");
+    }
+}
@@ -0,0 +1,41 @@
+use axum::{
+    extract::{Query, State},
+    http::{header::ACCEPT_LANGUAGE, HeaderMap, StatusCode},
+    response::Response,
+};
+
+use super::{search_model, search_structure::SearchQuery};
+use crate::utils::{
+    db::AppState,
+    locale::{negotiate, normalize_locale},
+    response::error_response,
+};
+
+const MAX_QUERY_LENGTH: usize = 200;
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 50;
+
+pub async fn search_service(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SearchQuery>,
+) -> Response {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return error_response("Search query must not be empty", StatusCode::BAD_REQUEST);
+    }
+    if q.chars().count() > MAX_QUERY_LENGTH {
+        return error_response("Search query is too long", StatusCode::BAD_REQUEST);
+    }
+
+    let chain = match query.lang.as_deref() {
+        Some(lang) => match normalize_locale(lang) {
+            Some(locale) => negotiate(Some(&locale)),
+            None => return error_response("Invalid locale", StatusCode::BAD_REQUEST),
+        },
+        None => negotiate(headers.get(ACCEPT_LANGUAGE).and_then(|value| value.to_str().ok())),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    search_model::search(state, q.to_string(), chain, query.hazard, limit).await
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{disaster::disaster_structure::GuideKind, revision::revision_structure::RevisionEntity};
+
+/// Query of `GET /disaster/search`. `q` uses MongoDB text search syntax (`"exact phrase"`,
+/// `-excluded`); `lang` overrides `Accept-Language` for searching translations.
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub lang: Option<String>,
+    pub hazard: Option<String>, // Taxonomy code; subtypes are included
+    pub limit: Option<i64>,
+}
+
+/// Matched fragment of one field, with matched words wrapped in `<mark>` and the rest HTML escaped
+#[derive(Debug, Serialize)]
+pub struct Highlight {
+    pub field: String,
+    pub fragment: String,
+}
+
+/// A disaster record or published guide item matching the query in one locale
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub entity: RevisionEntity,
+    pub disaster_id: String,
+    pub disaster_name: String,
+    pub kind: Option<GuideKind>,  // Set for guide items
+    pub item_id: Option<String>,  // Set for guide items
+    pub locale: String,           // Language of the matched text
+    pub score: f64,               // Text score; higher is more relevant
+    pub highlights: Vec<Highlight>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResults {
+    pub query: String,
+    pub locale: String,
+    pub hits: Vec<SearchHit>,
+}
@@ -0,0 +1,2 @@
+pub mod security_model;
+pub mod security_structure;
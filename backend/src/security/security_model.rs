@@ -0,0 +1,233 @@
+use axum::{http::StatusCode, response::Response};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::ReturnDocument,
+    Collection, Database,
+};
+
+use super::security_structure::{AuthEvent, AuthEventKind, AuthEventQuery, AuthEventView, LoginAttempt};
+use crate::{
+    session::session_structure::ClientInfo,
+    utils::{
+        db::AppState,
+        response::{error_response, success_response},
+    },
+};
+
+/// Failures before the exponential delay kicks in
+const BACKOFF_AFTER_FAILURES: i64 = 3;
+/// Upper bound of the exponential delay between attempts
+const MAX_BACKOFF_SECONDS: i64 = 300;
+/// Failures before the account is locked out
+const ACCOUNT_LOCKOUT_FAILURES: i64 = 10;
+/// Failures from one address before it is locked out, higher since many users may share a NAT
+const IP_LOCKOUT_FAILURES: i64 = 50;
+const LOCKOUT_MINUTES: i64 = 15;
+/// Counters are forgotten when there has been no failure for this long
+const FAILURE_WINDOW_MINUTES: i64 = 60;
+const DEFAULT_EVENT_LIMIT: i64 = 100;
+
+fn account_key(email: &str) -> String {
+    format!("account:{}", email.to_lowercase())
+}
+
+fn ip_key(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+fn minutes_from_now(minutes: i64) -> DateTime {
+    DateTime::from_millis(DateTime::now().timestamp_millis() + minutes * 60_000)
+}
+
+/// Seconds left before the account or address may try again, if either is locked
+pub async fn lockout_remaining(db: &Database, email: &str, ip: &str) -> mongodb::error::Result<Option<i64>> {
+    let attempts: Collection<LoginAttempt> = db.collection("login_attempts");
+    let now = DateTime::now();
+
+    let locked = attempts
+        .find(doc! {
+            "key": { "$in": [account_key(email), ip_key(ip)] },
+            "locked_until": { "$gt": now }
+        })
+        .await?
+        .try_collect::<Vec<LoginAttempt>>()
+        .await?;
+
+    Ok(locked
+        .iter()
+        .filter_map(|attempt| attempt.locked_until)
+        .map(|until| (until.timestamp_millis() - now.timestamp_millis()) / 1000 + 1)
+        .max())
+}
+
+/// Counts a failure for `key` and returns the updated counter. The count is bumped in one
+/// operation so concurrent failures, from this instance or another, are all counted.
+async fn register_failure(
+    attempts: &Collection<LoginAttempt>,
+    key: String,
+    lockout_failures: i64,
+) -> mongodb::error::Result<LoginAttempt> {
+    let now = DateTime::now();
+    let window_start = DateTime::from_millis(now.timestamp_millis() - FAILURE_WINDOW_MINUTES * 60_000);
+
+    // Counting restarts when the last failure fell outside the window (or there was none)
+    let count = vec![doc! {
+        "$set": {
+            "failures": {
+                "$cond": [
+                    { "$gt": ["$last_failure", window_start] },
+                    { "$add": [{ "$ifNull": ["$failures", 0] }, 1] },
+                    1
+                ]
+            },
+            "last_failure": now
+        }
+    }];
+    let mut attempt = attempts
+        .find_one_and_update(doc! { "key": &key }, count)
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .await?
+        .ok_or_else(|| mongodb::error::Error::custom("login attempt was not upserted"))?;
+    let failures = attempt.failures;
+
+    // Exponential delay after a few failures, then a full lockout
+    let locked_until = if failures >= lockout_failures {
+        Some(minutes_from_now(LOCKOUT_MINUTES))
+    } else if failures >= BACKOFF_AFTER_FAILURES {
+        let delay = 2i64
+            .saturating_pow((failures - BACKOFF_AFTER_FAILURES) as u32)
+            .min(MAX_BACKOFF_SECONDS);
+        Some(DateTime::from_millis(now.timestamp_millis() + delay * 1000))
+    } else {
+        None
+    };
+
+    // `$max` so a slower concurrent failure with a lower count cannot shorten the lock
+    if let Some(until) = locked_until {
+        attempts
+            .update_one(doc! { "key": &key }, doc! { "$max": { "locked_until": until } })
+            .await?;
+        attempt.locked_until = attempt.locked_until.max(locked_until);
+    }
+
+    Ok(attempt)
+}
+
+/// Records a failed login for both the account and the caller's address. `detail` says which
+/// step failed when it was not the password.
+pub async fn record_login_failure(
+    db: &Database,
+    email: &str,
+    user_id: Option<ObjectId>,
+    client: &ClientInfo,
+    detail: Option<&str>,
+) -> mongodb::error::Result<()> {
+    let attempts: Collection<LoginAttempt> = db.collection("login_attempts");
+
+    let account = register_failure(&attempts, account_key(email), ACCOUNT_LOCKOUT_FAILURES).await?;
+    let address = register_failure(&attempts, ip_key(&client.ip), IP_LOCKOUT_FAILURES).await?;
+
+    let mut event = AuthEvent::new(AuthEventKind::LoginFailed);
+    event.email = Some(email.to_string());
+    event.user_id = user_id;
+    event.ip = Some(client.ip.clone());
+    event.device = Some(client.device.clone());
+    event.detail = detail.map(str::to_string);
+    log_auth_event(db, event.clone()).await?;
+
+    for (attempt, threshold, what) in [
+        (account, ACCOUNT_LOCKOUT_FAILURES, "account"),
+        (address, IP_LOCKOUT_FAILURES, "address"),
+    ] {
+        if attempt.failures == threshold {
+            event.kind = AuthEventKind::AccountLocked;
+            event.detail = Some(format!("{} locked after {} failed attempts", what, attempt.failures));
+            event.at = DateTime::now();
+            log_auth_event(db, event.clone()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Clears the account counter after a successful login; the address counter is left
+/// to expire on its own so one valid account cannot reset an attacker's address
+pub async fn record_login_success(
+    db: &Database,
+    email: &str,
+    user_id: ObjectId,
+    client: &ClientInfo,
+) -> mongodb::error::Result<()> {
+    let attempts: Collection<LoginAttempt> = db.collection("login_attempts");
+    attempts.delete_one(doc! { "key": account_key(email) }).await?;
+
+    let mut event = AuthEvent::new(AuthEventKind::LoginSucceeded);
+    event.email = Some(email.to_string());
+    event.user_id = Some(user_id);
+    event.ip = Some(client.ip.clone());
+    event.device = Some(client.device.clone());
+    log_auth_event(db, event).await
+}
+
+pub async fn log_auth_event(db: &Database, event: AuthEvent) -> mongodb::error::Result<()> {
+    let events: Collection<AuthEvent> = db.collection("auth_events");
+    events.insert_one(event).await.map(|_| ())
+}
+
+pub async fn unlock_account(state: AppState, admin_id: ObjectId, user_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let users: Collection<Document> = database.collection("users");
+    let attempts: Collection<LoginAttempt> = database.collection("login_attempts");
+
+    let email = match users.find_one(doc! { "_id": user_id }).await {
+        Ok(Some(user_doc)) => user_doc.get_str("email").unwrap_or_default().to_string(),
+        Ok(None) => return error_response("User not found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    if let Err(e) = attempts.delete_one(doc! { "key": account_key(&email) }).await {
+        return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let mut event = AuthEvent::new(AuthEventKind::AccountUnlocked);
+    event.email = Some(email);
+    event.user_id = Some(user_id);
+    event.detail = Some(format!("unlocked by admin {}", admin_id.to_hex()));
+    if let Err(e) = log_auth_event(&database, event).await {
+        return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    success_response("Account unlocked successfully", user_id.to_hex(), StatusCode::OK)
+}
+
+pub async fn list_auth_events(state: AppState, query: AuthEventQuery) -> Response {
+    let db = state.db.lock().await;
+    let events: Collection<AuthEvent> = db.database("disaster").collection("auth_events");
+
+    let mut filter = doc! {};
+    if let Some(email) = query.email {
+        filter.insert("email", email);
+    }
+    if let Some(ip) = query.ip {
+        filter.insert("ip", ip);
+    }
+    if let Some(kind) = query.kind {
+        filter.insert("kind", mongodb::bson::to_bson(&kind).unwrap_or_default());
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_EVENT_LIMIT).clamp(1, 1000);
+
+    match events.find(filter).sort(doc! { "at": -1 }).limit(limit).await {
+        Ok(cursor) => match cursor.try_collect::<Vec<AuthEvent>>().await {
+            Ok(events) => {
+                let events: Vec<AuthEventView> = events.into_iter().map(AuthEventView::from).collect();
+                success_response("Auth events retrieved successfully", events, StatusCode::OK)
+            }
+            Err(e) => error_response(&format!("Failed to collect events: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+        },
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
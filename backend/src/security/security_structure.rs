@@ -0,0 +1,88 @@
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+/// Failed login counter for one key: `account:<email>` or `ip:<address>`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoginAttempt {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub key: String,
+    pub failures: i64,
+    pub last_failure: DateTime,
+    #[serde(default)]
+    pub locked_until: Option<DateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthEventKind {
+    LoginSucceeded,
+    LoginFailed,
+    LoginBlocked,
+    AccountLocked,
+    AccountUnlocked,
+}
+
+/// Security audit record in the `auth_events` collection
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthEvent {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub kind: AuthEventKind,
+    pub email: Option<String>,
+    pub user_id: Option<ObjectId>,
+    pub ip: Option<String>,
+    pub device: Option<String>,
+    pub detail: Option<String>,
+    pub at: DateTime,
+}
+
+impl AuthEvent {
+    pub fn new(kind: AuthEventKind) -> Self {
+        AuthEvent {
+            id: None,
+            kind,
+            email: None,
+            user_id: None,
+            ip: None,
+            device: None,
+            detail: None,
+            at: DateTime::now(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthEventView {
+    pub id: String,
+    pub kind: AuthEventKind,
+    pub email: Option<String>,
+    pub user_id: Option<String>,
+    pub ip: Option<String>,
+    pub device: Option<String>,
+    pub detail: Option<String>,
+    pub at: String,
+}
+
+impl From<AuthEvent> for AuthEventView {
+    fn from(event: AuthEvent) -> Self {
+        AuthEventView {
+            id: event.id.map(|id| id.to_hex()).unwrap_or_default(),
+            kind: event.kind,
+            email: event.email,
+            user_id: event.user_id.map(|id| id.to_hex()),
+            ip: event.ip,
+            device: event.device,
+            detail: event.detail,
+            at: event.at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthEventQuery {
+    pub email: Option<String>,
+    pub ip: Option<String>,
+    pub kind: Option<AuthEventKind>,
+    pub limit: Option<i64>,
+}
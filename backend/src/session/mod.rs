@@ -0,0 +1,22 @@
+use std::sync::Arc;
+
+use axum::{
+    middleware::from_fn,
+    routing::{delete, get},
+    Router,
+};
+use session_service::{list_sessions_service, revoke_all_sessions_service, revoke_session_service};
+
+use crate::{middleware::auth::auth_middleware, utils::db::AppState};
+
+pub mod session_model;
+pub mod session_service;
+pub mod session_structure;
+
+pub fn session_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_sessions_service).delete(revoke_all_sessions_service))
+        .route("/{session_id}", delete(revoke_session_service))
+        .layer(from_fn(auth_middleware))
+        .with_state((*state).clone())
+}
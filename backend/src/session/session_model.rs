@@ -0,0 +1,150 @@
+use axum::{http::StatusCode, response::Response};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Collection, Database,
+};
+
+use super::session_structure::{ClientInfo, Session, SessionView};
+use crate::{
+    user::user_structure::RefreshToken,
+    utils::{
+        db::AppState,
+        response::{error_response, success_response},
+    },
+};
+
+/// last_seen is only written when older than this, to avoid a write on every request
+const LAST_SEEN_RESOLUTION_SECONDS: i64 = 60;
+
+/// Opens a new session for `user_id` and returns its id
+pub async fn create_session(
+    db: &Database,
+    user_id: ObjectId,
+    client: ClientInfo,
+) -> mongodb::error::Result<ObjectId> {
+    let collection: Collection<Session> = db.collection("sessions");
+    let now = DateTime::now();
+
+    let session = Session {
+        id: None,
+        user_id,
+        device: client.device,
+        ip: client.ip,
+        created_at: now,
+        last_seen: now,
+        revoked: false,
+    };
+
+    let result = collection.insert_one(session).await?;
+    Ok(result.inserted_id.as_object_id().unwrap_or_default())
+}
+
+/// Returns the session if it is still active for the given user, refreshing last_seen
+pub async fn touch_session(
+    db: &Database,
+    session_id: ObjectId,
+    user_id: ObjectId,
+) -> mongodb::error::Result<Option<Session>> {
+    let collection: Collection<Session> = db.collection("sessions");
+
+    let session = collection
+        .find_one(doc! { "_id": session_id, "user_id": user_id, "revoked": false })
+        .await?;
+
+    if let Some(session) = &session {
+        let now = DateTime::now();
+        if now.timestamp_millis() - session.last_seen.timestamp_millis()
+            > LAST_SEEN_RESOLUTION_SECONDS * 1000
+        {
+            collection
+                .update_one(doc! { "_id": session_id }, doc! { "$set": { "last_seen": now } })
+                .await?;
+        }
+    }
+
+    Ok(session)
+}
+
+/// Revokes the sessions matching `filter` along with every refresh token issued for them
+pub async fn revoke_sessions(db: &Database, filter: mongodb::bson::Document) -> mongodb::error::Result<u64> {
+    let sessions: Collection<Session> = db.collection("sessions");
+    let refresh_tokens: Collection<RefreshToken> = db.collection("refresh_tokens");
+
+    let ids: Vec<ObjectId> = sessions
+        .find(filter)
+        .await?
+        .try_collect::<Vec<Session>>()
+        .await?
+        .into_iter()
+        .filter_map(|session| session.id)
+        .collect();
+
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let result = sessions
+        .update_many(doc! { "_id": { "$in": &ids } }, doc! { "$set": { "revoked": true } })
+        .await?;
+    refresh_tokens
+        .update_many(doc! { "session_id": { "$in": &ids } }, doc! { "$set": { "revoked": true } })
+        .await?;
+
+    Ok(result.modified_count)
+}
+
+pub async fn list_sessions(state: AppState, user_id: ObjectId, current: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let collection: Collection<Session> = db.database("disaster").collection("sessions");
+
+    let sessions = match collection
+        .find(doc! { "user_id": user_id, "revoked": false })
+        .sort(doc! { "last_seen": -1 })
+        .await
+    {
+        Ok(cursor) => cursor.try_collect::<Vec<Session>>().await.unwrap_or_else(|_| vec![]),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let sessions: Vec<SessionView> = sessions
+        .into_iter()
+        .map(|session| {
+            let id = session.id.unwrap_or_default();
+            SessionView {
+                id: id.to_hex(),
+                device: session.device,
+                ip: session.ip,
+                created_at: session.created_at.try_to_rfc3339_string().unwrap_or_default(),
+                last_seen: session.last_seen.try_to_rfc3339_string().unwrap_or_default(),
+                current: id == current,
+            }
+        })
+        .collect();
+
+    success_response("Sessions retrieved successfully", sessions, StatusCode::OK)
+}
+
+pub async fn revoke_session(state: AppState, user_id: ObjectId, session_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+
+    match revoke_sessions(
+        &db.database("disaster"),
+        doc! { "_id": session_id, "user_id": user_id, "revoked": false },
+    )
+    .await
+    {
+        Ok(0) => error_response("Session not found", StatusCode::NOT_FOUND),
+        Ok(_) => success_response("Session revoked successfully", session_id.to_hex(), StatusCode::OK),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn revoke_all_sessions(state: AppState, user_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+
+    match revoke_sessions(&db.database("disaster"), doc! { "user_id": user_id, "revoked": false }).await {
+        Ok(count) => success_response("All sessions revoked successfully", count, StatusCode::OK),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
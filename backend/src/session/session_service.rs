@@ -0,0 +1,127 @@
+use std::{
+    env,
+    net::{IpAddr, SocketAddr},
+};
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    Extension,
+};
+use mongodb::bson::oid::ObjectId;
+
+use super::{
+    session_model,
+    session_structure::{ClientInfo, CurrentSession},
+};
+use crate::{
+    middleware::permission::AuthUser,
+    utils::{db::AppState, response::error_response},
+};
+
+/// Addresses of the reverse proxies allowed to set `X-Forwarded-For` (`TRUSTED_PROXIES`,
+/// comma separated). Without it the header is ignored, since any client could forge it.
+fn trusted_proxies() -> Vec<IpAddr> {
+    env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|ip| ip.trim().parse().ok())
+        .collect()
+}
+
+/// Reads the device (User-Agent) and client address. Behind a trusted proxy the address is the
+/// last `X-Forwarded-For` entry that is not itself a trusted proxy; earlier entries come from
+/// the client and are not believed.
+pub fn client_info(headers: &HeaderMap, addr: SocketAddr) -> ClientInfo {
+    let device = headers
+        .get("User-Agent")
+        .and_then(|hv| hv.to_str().ok())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let proxies = trusted_proxies();
+    let mut ip = addr.ip();
+    if proxies.contains(&ip) {
+        let forwarded = headers
+            .get_all("X-Forwarded-For")
+            .iter()
+            .filter_map(|hv| hv.to_str().ok())
+            .flat_map(|hv| hv.split(','))
+            .map(str::trim)
+            .collect::<Vec<_>>();
+
+        for hop in forwarded.into_iter().rev() {
+            let Ok(hop) = hop.parse::<IpAddr>() else { break };
+            ip = hop;
+            if !proxies.contains(&hop) {
+                break;
+            }
+        }
+    }
+
+    ClientInfo { device, ip: ip.to_string() }
+}
+
+pub async fn list_sessions_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Extension(current): Extension<CurrentSession>,
+) -> Response {
+    session_model::list_sessions(state, user.id, current.id).await
+}
+
+pub async fn revoke_session_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(session_id): Path<String>,
+) -> Response {
+    let session_id = match ObjectId::parse_str(&session_id) {
+        Ok(id) => id,
+        Err(_) => return error_response("Invalid session ID format", StatusCode::BAD_REQUEST),
+    };
+
+    session_model::revoke_session(state, user.id, session_id).await
+}
+
+pub async fn revoke_all_sessions_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Response {
+    session_model::revoke_all_sessions(state, user.id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forwarded(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Forwarded-For", value.parse().unwrap());
+        headers.insert("User-Agent", "curl/8.0".parse().unwrap());
+        headers
+    }
+
+    // One test, since it changes a process-wide variable
+    #[test]
+    fn forwarded_address_is_only_believed_from_trusted_proxies() {
+        let proxy: SocketAddr = "10.0.0.2:443".parse().unwrap();
+        let direct: SocketAddr = "198.51.100.7:5000".parse().unwrap();
+
+        env::remove_var("TRUSTED_PROXIES");
+        let info = client_info(&forwarded("203.0.113.9"), proxy);
+        assert_eq!(info.ip, "10.0.0.2");
+        assert_eq!(info.device, "curl/8.0");
+
+        env::set_var("TRUSTED_PROXIES", "10.0.0.2, 10.0.0.3");
+        assert_eq!(client_info(&forwarded("203.0.113.9"), proxy).ip, "203.0.113.9");
+        // A client cannot spoof its address by sending the header straight to the server
+        assert_eq!(client_info(&forwarded("203.0.113.9"), direct).ip, "198.51.100.7");
+        // Entries prepended by the client are skipped; the proxy chain is read from the right
+        assert_eq!(client_info(&forwarded("1.2.3.4, 203.0.113.9, 10.0.0.3"), proxy).ip, "203.0.113.9");
+        // Garbage stops the walk at the last address a trusted proxy vouched for
+        assert_eq!(client_info(&forwarded("not-an-ip"), proxy).ip, "10.0.0.2");
+        assert_eq!(client_info(&HeaderMap::new(), proxy).ip, "10.0.0.2");
+        env::remove_var("TRUSTED_PROXIES");
+    }
+}
@@ -0,0 +1,40 @@
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+/// One logged-in device. The session id is embedded in the access token (`sid`)
+/// and doubles as the family id of the refresh tokens issued for it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Session {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub device: String, // User-Agent reported at login
+    pub ip: String,
+    pub created_at: DateTime,
+    pub last_seen: DateTime,
+    pub revoked: bool,
+}
+
+/// Session listing entry returned to the owner
+#[derive(Debug, Serialize)]
+pub struct SessionView {
+    pub id: String,
+    pub device: String,
+    pub ip: String,
+    pub created_at: String,
+    pub last_seen: String,
+    pub current: bool,
+}
+
+/// Session of the current request, inserted into request extensions by `auth_middleware`
+#[derive(Debug, Clone)]
+pub struct CurrentSession {
+    pub id: ObjectId,
+}
+
+/// Device and address of the caller, captured when a session is opened
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    pub device: String,
+    pub ip: String,
+}
@@ -2,11 +2,15 @@ use std::sync::Arc;
 use axum::{
     middleware::{from_fn, from_fn_with_state},
     routing::{delete, get, patch, post},
-    Router,
+    Extension, Router,
 };
 use shelters_service::{create_shelter_service, delete_shelter_service, get_shelter_service, update_shelter_service};
 use crate::{
-    middleware::{auth::auth_middleware, ngo::ngo_middleware},
+    api_key::api_key_structure::{ApiKeyRoute, ApiScope},
+    middleware::{
+        auth::auth_middleware,
+        permission::{require_permission, Permission},
+    },
     utils::db::AppState,
 };
 
@@ -20,7 +24,9 @@ pub fn shelters_routes(state: Arc<AppState>) -> Router {
     .route("/create_shelter", post(create_shelter_service))
     .route("/delete_shelter", delete(delete_shelter_service)) 
     .route("/update_shelter", patch(update_shelter_service))
-    .layer(from_fn_with_state(state.clone(), ngo_middleware))
-    .layer(from_fn(auth_middleware)).with_state(state)
+    .layer(from_fn_with_state(Permission::ManageShelters, require_permission))
+    .layer(from_fn(auth_middleware))
+    .layer(Extension(ApiKeyRoute { read: ApiScope::SheltersRead, write: ApiScope::SheltersWrite }))
+    .with_state(state)
 
 }
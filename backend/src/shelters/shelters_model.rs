@@ -2,17 +2,27 @@ use axum::{extract::State, http::StatusCode, response:: Response, Json};
 use futures::TryStreamExt;
 use mongodb::{bson::{self, doc, oid::ObjectId}, Collection};
 use std::sync::Arc;
+use crate::incident::{incident_model::forget_mobilised, incident_structure::Mobilised};
+use crate::middleware::permission::AuthUser;
+use crate::organization::organization_model::{can_write, resolve_owner_org, writable_org_ids};
 use crate::utils::db::AppState;
 use super::shelters_structure::Shelter;
 use crate::utils::response::{success_response, error_response};
 
 pub async fn create_shelters(
     State(state): State<Arc<AppState>>,
-    Json(shelter): Json<Shelter>,
+    user: AuthUser,
+    Json(mut shelter): Json<Shelter>,
 ) -> Response {
     let db = state.db.lock().await;
     let collection: Collection<Shelter> = db.database("disaster").collection("shelters");
 
+    shelter.id = None;
+    shelter.owner_org_id = match resolve_owner_org(&db.database("disaster"), &user, shelter.owner_org_id).await {
+        Ok(org_id) => org_id,
+        Err((status, message)) => return error_response(&message, status),
+    };
+
     // Check if a shelter with the same name already exists
     if collection.find_one(doc! { "name": &shelter.name }).await.unwrap_or(None).is_some() {
         return error_response("Shelter already present", StatusCode::BAD_REQUEST);
@@ -43,21 +53,54 @@ pub async fn get_shelters(State(state): State<Arc<AppState>>) -> Response {
     }
 }
 
+/// Fetches a shelter and checks that the caller may change it
+async fn find_writable(
+    state: &AppState,
+    user: &AuthUser,
+    obj_id: ObjectId,
+) -> Result<(Shelter, Option<Vec<ObjectId>>), (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let db_error = |e: mongodb::error::Error| (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {}", e));
+
+    let shelter = database
+        .collection::<Shelter>("shelters")
+        .find_one(doc! { "_id": obj_id })
+        .await
+        .map_err(db_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Shelter not found".to_string()))?;
+
+    let writable = writable_org_ids(&database, user).await.map_err(db_error)?;
+    if !can_write(&writable, shelter.owner_org_id) {
+        return Err((StatusCode::FORBIDDEN, "Shelter belongs to another organization".to_string()));
+    }
+
+    Ok((shelter, writable))
+}
+
 pub async fn delete_shelter(
     State(state): State<Arc<AppState>>,
+    user: AuthUser,
     id: String,
 ) -> Response {
-    let db = state.db.lock().await;
-    let collection: Collection<Shelter> = db.database("disaster").collection("shelters");
-
     let obj_id = match ObjectId::parse_str(&id) {
         Ok(oid) => oid,
         Err(_) => return error_response("Invalid ID format", StatusCode::BAD_REQUEST),
     };
 
+    if let Err((status, message)) = find_writable(&state, &user, obj_id).await {
+        return error_response(&message, status);
+    }
+
+    let db = state.db.lock().await;
+    let collection: Collection<Shelter> = db.database("disaster").collection("shelters");
+
     match collection.delete_one(doc! { "_id": obj_id }).await {
         Ok(result) => {
             if result.deleted_count == 1 {
+                if let Err(e) = forget_mobilised(&db.database("disaster"), Mobilised::Shelter, obj_id).await {
+                    return error_response(&format!("Shelter deleted but incidents still list it: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+                }
                 success_response("Shelter deleted successfully", id, StatusCode::OK)
             } else {
                 error_response("Shelter not found", StatusCode::NOT_FOUND)
@@ -69,17 +112,29 @@ pub async fn delete_shelter(
 
 pub async fn update_shelters(
     State(state): State<Arc<AppState>>,
-    Json(shelter): Json<Shelter>,
+    user: AuthUser,
+    Json(mut shelter): Json<Shelter>,
     id: String,
 ) -> Response {
-    let db = state.db.lock().await;
-    let collection: Collection<Shelter> = db.database("disaster").collection("shelters");
-
     let obj_id = match ObjectId::parse_str(&id) {
         Ok(oid) => oid,
         Err(_) => return error_response("Invalid ID format", StatusCode::BAD_REQUEST),
     };
 
+    let (existing, writable) = match find_writable(&state, &user, obj_id).await {
+        Ok(found) => found,
+        Err((status, message)) => return error_response(&message, status),
+    };
+
+    // Handing a shelter over needs write access to the receiving organization too
+    if shelter.owner_org_id.is_some() && shelter.owner_org_id != existing.owner_org_id && !can_write(&writable, shelter.owner_org_id) {
+        return error_response("You are not a member of that organization", StatusCode::FORBIDDEN);
+    }
+    shelter.id = None;
+
+    let db = state.db.lock().await;
+    let collection: Collection<Shelter> = db.database("disaster").collection("shelters");
+
     let update_doc = doc! { "$set": bson::to_document(&shelter).unwrap() };
 
     match collection.update_one(doc! { "_id": obj_id }, update_doc).await {
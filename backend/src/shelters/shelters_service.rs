@@ -1,6 +1,7 @@
 use axum::http::HeaderMap;
 use axum::{extract::State, Json, http::StatusCode, response::Response};
 use std::sync::Arc;
+use crate::middleware::permission::AuthUser;
 use crate::utils::db::AppState;
 use super::shelters_structure::Shelter;
 use super::shelters_model::{create_shelters, delete_shelter, get_shelters, update_shelters};
@@ -8,6 +9,7 @@ use crate::utils::response::error_response;
 
 pub async fn create_shelter_service(
     State(state): State<Arc<AppState>>,
+    user: AuthUser,
     Json(shelter): Json<Shelter>,
 ) -> Response {
     if shelter.name.trim().is_empty()
@@ -27,14 +29,14 @@ pub async fn create_shelter_service(
         return error_response("Available beds cannot exceed total capacity", StatusCode::BAD_REQUEST);
     }
 
-    create_shelters(State(state), Json(shelter)).await
+    create_shelters(State(state), user, Json(shelter)).await
 }
 
 pub async fn get_shelter_service(State(state): State<Arc<AppState>>) -> Response {
     get_shelters(State(state)).await
 }
 
-pub async fn delete_shelter_service(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+pub async fn delete_shelter_service(State(state): State<Arc<AppState>>, user: AuthUser, headers: HeaderMap) -> Response {
     let id = match headers.get("id") {
         Some(value) => match value.to_str() {
             Ok(id) => id.to_string(),
@@ -43,11 +45,12 @@ pub async fn delete_shelter_service(State(state): State<Arc<AppState>>, headers:
         None => return error_response("Missing id header", StatusCode::BAD_REQUEST),
     };
 
-    delete_shelter(State(state), id).await
+    delete_shelter(State(state), user, id).await
 }
 
 pub async fn update_shelter_service(
     State(state): State<Arc<AppState>>,
+    user: AuthUser,
     headers: HeaderMap,
     Json(shelter): Json<Shelter>,
 ) -> Response {
@@ -63,5 +66,5 @@ pub async fn update_shelter_service(
         None => return error_response("Missing id header", StatusCode::BAD_REQUEST),
     };
 
-    update_shelters(State(state), Json(shelter), id).await
+    update_shelters(State(state), user, Json(shelter), id).await
 }
@@ -1,7 +1,10 @@
+use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Shelter {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
     pub name: String,
     pub capacity: u32,
     pub available_beds: u32,
@@ -9,4 +12,6 @@ pub struct Shelter {
     pub district: String,
     pub state: String,
     pub country: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_org_id: Option<ObjectId>, // Organization managing the shelter, set from the caller
 }
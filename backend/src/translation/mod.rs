@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use axum::{middleware::{from_fn, from_fn_with_state}, routing::{get, patch, post}, Router};
+use translation_service::{list_pending_translations_service, missing_translations_service, moderate_translation_service, submit_translation_service};
+
+use crate::{middleware::{auth::auth_middleware, permission::{require_permission, Permission}}, utils::db::AppState};
+
+pub mod translation_model;
+pub mod translation_service;
+pub mod translation_structure;
+
+/// Translations of disaster records and guide items, nested under `/disaster/translations`.
+/// Anyone signed in can translate; moderators review submissions like guide items.
+pub fn translation_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", post(submit_translation_service))
+        .route("/pending", get(list_pending_translations_service)
+            .route_layer(from_fn_with_state(Permission::ModerateGuides, require_permission)))
+        .route("/missing", get(missing_translations_service)
+            .route_layer(from_fn_with_state(Permission::ModerateGuides, require_permission)))
+        .route("/{translation_id}", patch(moderate_translation_service)
+            .route_layer(from_fn_with_state(Permission::ModerateGuides, require_permission)))
+        .layer(from_fn(auth_middleware))
+        .with_state((*state).clone())
+}
@@ -0,0 +1,354 @@
+//! Per-field translations of disaster records and guide items. Translators submit text for one
+//! field in one locale; moderators review it like a guide item. Accepted translations are only
+//! served while they were made from the revision currently published, so a translation goes
+//! stale (and shows up as missing again) as soon as the original is edited.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use axum::http::StatusCode;
+use axum::response::Response;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, Bson, DateTime, Document},
+    Collection, Database,
+};
+
+use super::translation_structure::{
+    MissingTranslations, PendingTranslationQuery, SubmitTranslationRequest, Translation, TranslationPage,
+    TranslationView,
+};
+use crate::{
+    disaster::{
+        disaster_model::{counter, is_published},
+        disaster_structure::{DisasterGuide, DisasterRecord, GuideKind, GuideStatus, ModerationRequest},
+    },
+    middleware::permission::AuthUser,
+    revision::{revision_model::find_guide_item, revision_structure::RevisionEntity},
+    search::search_model::text_search_language,
+    utils::{
+        db::AppState,
+        locale::{default_locale, normalize_locale},
+        paging,
+        response::{error_response, success_response},
+    },
+};
+
+/// Fields translators may submit text for
+pub fn translatable_fields(entity: RevisionEntity) -> &'static [&'static str] {
+    match entity {
+        RevisionEntity::DisasterRecord => &["name", "effects", "short_description"],
+        RevisionEntity::GuideItem => &["message"],
+    }
+}
+
+/// Revision of the text the public currently sees for a stored guide item
+pub fn published_revision(item: &Document) -> u32 {
+    item.get_document("published")
+        .map(|published| counter(published, "revision"))
+        .unwrap_or_else(|_| counter(item, "revision"))
+}
+
+pub async fn submit_translation(state: AppState, translator: AuthUser, request: SubmitTranslationRequest) -> Response {
+    let Some(locale) = normalize_locale(&request.locale) else {
+        return error_response("Invalid locale", StatusCode::BAD_REQUEST);
+    };
+    if locale == default_locale() {
+        return error_response("Records are already written in the default locale", StatusCode::BAD_REQUEST);
+    }
+    let text = request.text.trim().to_string();
+    if text.is_empty() {
+        return error_response("Text must not be empty", StatusCode::BAD_REQUEST);
+    }
+    let Ok(disaster_id) = ObjectId::parse_str(&request.disaster_id) else {
+        return error_response("Invalid disaster record ID format", StatusCode::BAD_REQUEST);
+    };
+    let (entity, item) = match (request.kind, request.item_id.as_deref()) {
+        (None, None) => (RevisionEntity::DisasterRecord, None),
+        (Some(kind), Some(item_id)) => match ObjectId::parse_str(item_id) {
+            Ok(item_id) => (RevisionEntity::GuideItem, Some((kind, item_id))),
+            Err(_) => return error_response("Invalid guide item ID format", StatusCode::BAD_REQUEST),
+        },
+        _ => return error_response("kind and item_id must be given together", StatusCode::BAD_REQUEST),
+    };
+    if !translatable_fields(entity).contains(&request.field.as_str()) {
+        return error_response(
+            &format!("Field must be one of: {}", translatable_fields(entity).join(", ")),
+            StatusCode::BAD_REQUEST,
+        );
+    }
+
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let source = match item {
+        None => {
+            let records: Collection<Document> = database.collection("disaster_record");
+            records
+                .find_one(doc! { "_id": disaster_id })
+                .await
+                .map(|record| record.map(|record| (disaster_id, counter(&record, "revision"))))
+        }
+        Some((kind, item_id)) => find_guide_item(&database, disaster_id, kind, item_id)
+            .await
+            .map(|item| item.filter(is_published).map(|item| (item_id, published_revision(&item)))),
+    };
+    let (entity_id, source_revision) = match source {
+        Ok(Some(source)) => source,
+        Ok(None) if item.is_some() => return error_response("Published guide item not found", StatusCode::NOT_FOUND),
+        Ok(None) => return error_response("Disaster record not found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Failed to fetch the original text: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let translation = Translation {
+        id: None,
+        entity,
+        entity_id,
+        disaster_id,
+        kind: item.map(|(kind, _)| kind),
+        field: request.field,
+        search_language: Some(text_search_language(&locale).to_string()),
+        locale,
+        text,
+        source_revision,
+        status: GuideStatus::Pending.as_str().to_string(),
+        translator_id: translator.id,
+        submitted_at: DateTime::now(),
+        reason: None,
+        moderated_by: None,
+        moderated_at: None,
+    };
+
+    let translations: Collection<Translation> = database.collection("translations");
+    match translations.insert_one(&translation).await {
+        Ok(result) => success_response(
+            "Translation submitted for review",
+            TranslationView::from(Translation { id: result.inserted_id.as_object_id(), ..translation }),
+            StatusCode::CREATED,
+        ),
+        Err(e) => error_response(&format!("Failed to submit translation: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Pending translations, oldest first
+pub async fn list_pending_translations(state: AppState, query: PendingTranslationQuery, locale: Option<String>) -> Response {
+    let (page, per_page) = paging::page_window(query.page, query.per_page);
+
+    let mut filter = doc! { "status": GuideStatus::Pending.as_str() };
+    if let Some(locale) = locale {
+        filter.insert("locale", locale);
+    }
+
+    let db = state.db.lock().await;
+    let translations: Collection<Translation> = db.database("disaster").collection("translations");
+
+    let total = match translations.count_documents(filter.clone()).await {
+        Ok(total) => total,
+        Err(e) => return error_response(&format!("Failed to count translations: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    let found = match translations
+        .find(filter)
+        .sort(doc! { "submitted_at": 1, "_id": 1 })
+        .skip(paging::skip(page, per_page))
+        .limit(per_page)
+        .await
+    {
+        Ok(cursor) => cursor.try_collect::<Vec<Translation>>().await,
+        Err(e) => Err(e),
+    };
+
+    match found {
+        Ok(found) => success_response(
+            "Pending translations retrieved successfully",
+            TranslationPage { translations: found.into_iter().map(TranslationView::from).collect(), page, per_page, total },
+            StatusCode::OK,
+        ),
+        Err(e) => error_response(&format!("Failed to fetch translations: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Accepts or rejects a pending translation. Decided translations are final; a new
+/// submission replaces an accepted one once it is accepted itself.
+pub async fn moderate_translation(
+    state: AppState,
+    moderator: AuthUser,
+    translation_id: ObjectId,
+    decision: ModerationRequest,
+) -> Response {
+    let db = state.db.lock().await;
+    let translations: Collection<Translation> = db.database("disaster").collection("translations");
+
+    let reason = decision.reason.map(|reason| reason.trim().to_string()).filter(|reason| !reason.is_empty());
+    let update = doc! {
+        "$set": {
+            "status": decision.status.as_str(),
+            "reason": reason,
+            "moderated_by": moderator.id,
+            "moderated_at": DateTime::now(),
+        }
+    };
+
+    let filter = doc! { "_id": translation_id, "status": GuideStatus::Pending.as_str() };
+    match translations.find_one_and_update(filter, update).return_document(mongodb::options::ReturnDocument::After).await {
+        Ok(Some(translation)) => success_response(
+            &format!("Translation {}", decision.status.as_str().to_lowercase()),
+            TranslationView::from(translation),
+            StatusCode::OK,
+        ),
+        Ok(None) => error_response("Pending translation not found", StatusCode::NOT_FOUND),
+        Err(e) => error_response(&format!("Failed to moderate translation: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Replaces the original text of `records` and the published items of `guides` with the best
+/// accepted, current translation along `chain` (most preferred locale first). Fields without one
+/// keep the original. Returns the locales the response ended up in, in chain order.
+pub async fn localize(
+    database: &Database,
+    disaster_id: ObjectId,
+    chain: &[String],
+    records: &mut [DisasterRecord],
+    guides: &mut [DisasterGuide],
+) -> mongodb::error::Result<Vec<String>> {
+    let default = default_locale();
+    // Locales the caller prefers over the original text
+    let preferred: Vec<&String> = chain.iter().take_while(|locale| **locale != default).collect();
+
+    let mut best: HashMap<(ObjectId, String), (usize, u32, String)> = HashMap::new();
+    if !preferred.is_empty() {
+        let translations: Collection<Translation> = database.collection("translations");
+        let mut cursor = translations
+            .find(doc! {
+                "disaster_id": disaster_id,
+                "status": GuideStatus::Accepted.as_str(),
+                "locale": { "$in": &preferred },
+            })
+            .sort(doc! { "moderated_at": -1 })
+            .await?;
+
+        // Newest first, so the first translation seen wins among those for the same locale
+        while let Some(translation) = cursor.try_next().await? {
+            let rank = preferred.iter().position(|locale| **locale == translation.locale).unwrap_or(usize::MAX);
+            let key = (translation.entity_id, translation.field);
+            if best.get(&key).is_none_or(|(current, _, _)| rank < *current) {
+                best.insert(key, (rank, translation.source_revision, translation.text));
+            }
+        }
+    }
+
+    let mut used = vec![false; preferred.len() + 1];
+    let mut translate = |entity_id: Option<ObjectId>, field: &str, revision: u32, text: &mut String| {
+        let found = entity_id.and_then(|id| best.get(&(id, field.to_string())));
+        match found {
+            Some((rank, source_revision, translated)) if *source_revision == revision => {
+                *text = translated.clone();
+                used[*rank] = true;
+            }
+            _ => used[preferred.len()] = true,
+        }
+    };
+
+    for record in records.iter_mut() {
+        let (id, revision) = (record.id, record.revision);
+        translate(id, "name", revision, &mut record.name);
+        translate(id, "effects", revision, &mut record.effects);
+        translate(id, "short_description", revision, &mut record.short_description);
+    }
+    for guide in guides.iter_mut() {
+        for item in guide.do_s.iter_mut().chain(guide.dont_s.iter_mut()) {
+            translate(item.id, "message", item.revision, &mut item.message);
+        }
+    }
+
+    let mut locales: Vec<String> = preferred
+        .iter()
+        .zip(&used)
+        .filter(|(_, used)| **used)
+        .map(|(locale, _)| locale.to_string())
+        .collect();
+    if used[preferred.len()] || locales.is_empty() {
+        locales.push(default);
+    }
+    Ok(locales)
+}
+
+/// For every disaster record, the translatable fields (including its published guide items) that
+/// have no accepted translation of the current revision in each of `locales`
+pub async fn missing_translations(state: AppState, locales: Vec<String>) -> Response {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+
+    let loaded = async {
+        let records: Vec<Document> = database
+            .collection::<Document>("disaster_record")
+            .find(doc! {})
+            .sort(doc! { "name": 1 })
+            .await?
+            .try_collect()
+            .await?;
+        let guides: Vec<Document> = database.collection::<Document>("disaster_guide").find(doc! {}).await?.try_collect().await?;
+        let translated: HashSet<(ObjectId, String, String, u32)> = database
+            .collection::<Translation>("translations")
+            .find(doc! { "status": GuideStatus::Accepted.as_str(), "locale": { "$in": &locales } })
+            .await?
+            .map_ok(|t| (t.entity_id, t.field, t.locale, t.source_revision))
+            .try_collect()
+            .await?;
+        mongodb::error::Result::Ok((records, guides, translated))
+    };
+    let (records, guides, translated) = match loaded.await {
+        Ok(loaded) => loaded,
+        Err(e) => return error_response(&format!("Failed to fetch translations: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let guides: HashMap<ObjectId, &Document> = guides
+        .iter()
+        .filter_map(|guide| Some((guide.get_object_id("disaster_id").ok()?, guide)))
+        .collect();
+
+    let mut report = Vec::new();
+    for record in &records {
+        let Ok(record_id) = record.get_object_id("_id") else { continue };
+
+        // (entity id, field, current revision, label in the report)
+        let mut sources: Vec<(ObjectId, &str, u32, String)> = translatable_fields(RevisionEntity::DisasterRecord)
+            .iter()
+            .filter(|field| record.get_str(field).is_ok_and(|text| !text.trim().is_empty()))
+            .map(|field| (record_id, *field, counter(record, "revision"), field.to_string()))
+            .collect();
+        if let Some(guide) = guides.get(&record_id) {
+            for kind in [GuideKind::Do, GuideKind::Dont] {
+                let items = guide.get_array(kind.field()).map(|items| items.as_slice()).unwrap_or_default();
+                for item in items.iter().filter_map(Bson::as_document).filter(|item| is_published(item)) {
+                    let Ok(item_id) = item.get_object_id("_id") else { continue };
+                    let label = format!("{}:{}", if kind == GuideKind::Do { "do" } else { "dont" }, item_id.to_hex());
+                    sources.push((item_id, "message", published_revision(item), label));
+                }
+            }
+        }
+
+        let missing: BTreeMap<String, Vec<String>> = locales
+            .iter()
+            .map(|locale| {
+                let fields = sources
+                    .iter()
+                    .filter(|(id, field, revision, _)| {
+                        !translated.contains(&(*id, field.to_string(), locale.clone(), *revision))
+                    })
+                    .map(|(_, _, _, label)| label.clone())
+                    .collect::<Vec<_>>();
+                (locale.clone(), fields)
+            })
+            .filter(|(_, fields)| !fields.is_empty())
+            .collect();
+
+        if !missing.is_empty() {
+            report.push(MissingTranslations {
+                disaster_id: record_id.to_hex(),
+                name: record.get_str("name").unwrap_or_default().to_string(),
+                missing,
+            });
+        }
+    }
+
+    success_response("Missing translations retrieved successfully", report, StatusCode::OK)
+}
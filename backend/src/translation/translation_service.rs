@@ -0,0 +1,78 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+
+use super::{
+    translation_model,
+    translation_structure::{MissingTranslationQuery, PendingTranslationQuery, SubmitTranslationRequest},
+};
+use crate::{
+    disaster::{disaster_service::decision_error, disaster_structure::ModerationRequest},
+    middleware::permission::AuthUser,
+    utils::{
+        db::AppState,
+        locale::{default_locale, normalize_locale, supported_locales},
+        response::error_response,
+    },
+};
+
+pub async fn submit_translation_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<SubmitTranslationRequest>,
+) -> Response {
+    translation_model::submit_translation(state, user, payload).await
+}
+
+pub async fn list_pending_translations_service(
+    State(state): State<AppState>,
+    Query(query): Query<PendingTranslationQuery>,
+) -> Response {
+    let locale = match query.locale.as_deref().map(normalize_locale) {
+        Some(None) => return error_response("Invalid locale", StatusCode::BAD_REQUEST),
+        Some(locale) => locale,
+        None => None,
+    };
+
+    translation_model::list_pending_translations(state, query, locale).await
+}
+
+pub async fn moderate_translation_service(
+    State(state): State<AppState>,
+    moderator: AuthUser,
+    Path(translation_id): Path<String>,
+    Json(decision): Json<ModerationRequest>,
+) -> Response {
+    let Ok(translation_id) = ObjectId::parse_str(&translation_id) else {
+        return error_response("Invalid translation ID format", StatusCode::BAD_REQUEST);
+    };
+    if let Some(message) = decision_error(&decision) {
+        return error_response(message, StatusCode::BAD_REQUEST);
+    }
+
+    translation_model::moderate_translation(state, moderator, translation_id, decision).await
+}
+
+pub async fn missing_translations_service(
+    State(state): State<AppState>,
+    Query(query): Query<MissingTranslationQuery>,
+) -> Response {
+    let locales: Vec<String> = match query.locales {
+        Some(locales) => match locales.split(',').map(normalize_locale).collect::<Option<Vec<_>>>() {
+            Some(locales) => locales,
+            None => return error_response("Invalid locale", StatusCode::BAD_REQUEST),
+        },
+        None => supported_locales(),
+    };
+    let default = default_locale();
+    let locales: Vec<String> = locales.into_iter().filter(|locale| *locale != default).collect();
+    if locales.is_empty() {
+        return error_response("No locales given and SUPPORTED_LOCALES is not set", StatusCode::BAD_REQUEST);
+    }
+
+    translation_model::missing_translations(state, locales).await
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{disaster::disaster_structure::GuideKind, revision::revision_structure::RevisionEntity};
+
+/// Translation of one field of a disaster record or guide item, stored in the `translations`
+/// collection. Submissions go through moderation like guide items and are only served while
+/// `source_revision` matches the revision currently published, so outdated guidance is never shown.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Translation {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub entity: RevisionEntity,
+    pub entity_id: ObjectId,
+    pub disaster_id: ObjectId,
+    #[serde(default)]
+    pub kind: Option<GuideKind>, // Set for guide items
+    pub field: String,
+    pub locale: String, // Normalized BCP 47 tag, e.g. `pt-br`
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_language: Option<String>, // Stemming language of the text index, see `search::search_model`
+    pub source_revision: u32, // Revision of the original text that was translated
+    pub status: String,       // One of `GuideStatus`
+    pub translator_id: ObjectId,
+    pub submitted_at: DateTime,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub moderated_by: Option<ObjectId>,
+    #[serde(default)]
+    pub moderated_at: Option<DateTime>,
+}
+
+/// Body of `POST /disaster/translations`; `kind` and `item_id` select a guide item, otherwise
+/// the record itself is translated
+#[derive(Debug, Deserialize)]
+pub struct SubmitTranslationRequest {
+    pub disaster_id: String,
+    pub kind: Option<GuideKind>,
+    pub item_id: Option<String>,
+    pub locale: String,
+    pub field: String,
+    pub text: String,
+}
+
+/// Query of `GET /disaster/translations/pending`; oldest first, pages start at 1
+#[derive(Debug, Deserialize)]
+pub struct PendingTranslationQuery {
+    pub locale: Option<String>,
+    pub page: Option<u64>,
+    pub per_page: Option<i64>,
+}
+
+/// Query of `GET /disaster/translations/missing`; defaults to `SUPPORTED_LOCALES`
+#[derive(Debug, Deserialize)]
+pub struct MissingTranslationQuery {
+    pub locales: Option<String>, // Comma separated
+}
+
+#[derive(Debug, Serialize)]
+pub struct TranslationView {
+    pub id: String,
+    pub entity: RevisionEntity,
+    pub entity_id: String,
+    pub disaster_id: String,
+    pub kind: Option<GuideKind>,
+    pub field: String,
+    pub locale: String,
+    pub text: String,
+    pub source_revision: u32,
+    pub status: String,
+    pub translator_id: String,
+    pub submitted_at: String,
+    pub reason: Option<String>,
+}
+
+impl From<Translation> for TranslationView {
+    fn from(translation: Translation) -> Self {
+        TranslationView {
+            id: translation.id.map(|id| id.to_hex()).unwrap_or_default(),
+            entity: translation.entity,
+            entity_id: translation.entity_id.to_hex(),
+            disaster_id: translation.disaster_id.to_hex(),
+            kind: translation.kind,
+            field: translation.field,
+            locale: translation.locale,
+            text: translation.text,
+            source_revision: translation.source_revision,
+            status: translation.status,
+            translator_id: translation.translator_id.to_hex(),
+            submitted_at: translation.submitted_at.try_to_rfc3339_string().unwrap_or_default(),
+            reason: translation.reason,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TranslationPage {
+    pub translations: Vec<TranslationView>,
+    pub page: u64,
+    pub per_page: i64,
+    pub total: u64,
+}
+
+/// Fields of one disaster record still lacking a current translation, per locale.
+/// Guide items are listed as `do:<item_id>` / `dont:<item_id>`.
+#[derive(Debug, Serialize)]
+pub struct MissingTranslations {
+    pub disaster_id: String,
+    pub name: String,
+    pub missing: BTreeMap<String, Vec<String>>,
+}
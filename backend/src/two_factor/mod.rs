@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use axum::{middleware::from_fn, routing::post, Router};
+use two_factor_service::{confirm_service, disable_service, enroll_service, login_service, recovery_codes_service};
+
+use crate::{middleware::auth::auth_middleware, utils::db::AppState};
+
+pub mod two_factor_model;
+pub mod two_factor_service;
+pub mod two_factor_structure;
+
+pub fn two_factor_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/enroll", post(enroll_service))
+        .route("/confirm", post(confirm_service))
+        .route("/disable", post(disable_service))
+        .route("/recovery_codes", post(recovery_codes_service))
+        .layer(from_fn(auth_middleware))
+        .route("/login", post(login_service))
+        .with_state((*state).clone())
+}
@@ -0,0 +1,345 @@
+use std::env;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{http::StatusCode, response::Response};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    Collection, Database,
+};
+
+use super::two_factor_structure::{EnrollmentView, MfaChallenge, TwoFactorLoginRequest};
+use crate::{
+    security::{
+        security_model,
+        security_structure::{AuthEvent, AuthEventKind},
+    },
+    session::session_structure::ClientInfo,
+    user::{user_model::complete_login, user_structure::Role},
+    utils::{
+        crypto::{generate_token, hash_token},
+        db::AppState,
+        response::{error_response, success_response},
+        totp,
+    },
+};
+
+const CHALLENGE_TTL_MINUTES: i64 = 5;
+const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Roles that may only be exercised with two-factor enabled, from `TOTP_REQUIRED_ROLES` (e.g. `admin,ngo`)
+pub fn totp_required_roles() -> Vec<Role> {
+    env::var("TOTP_REQUIRED_ROLES")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|role| Role::parse(role.trim()))
+        .collect()
+}
+
+fn totp_issuer() -> String {
+    env::var("TOTP_ISSUER").unwrap_or_else(|_| "Disaster Management".to_string())
+}
+
+/// Ten single-use codes shaped `xxxxx-xxxxx`, returned in clear once and stored hashed
+fn generate_recovery_codes() -> (Vec<String>, Vec<String>) {
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 5];
+            OsRng.fill_bytes(&mut bytes);
+            let code = hex::encode(bytes);
+            format!("{}-{}", &code[..5], &code[5..])
+        })
+        .collect();
+    let hashes = codes.iter().map(|code| hash_token(code)).collect();
+    (codes, hashes)
+}
+
+/// Checks a TOTP code for the user, refusing a time step that was already used
+async fn verify_totp_code(
+    users: &Collection<Document>,
+    user_doc: &Document,
+    code: &str,
+) -> mongodb::error::Result<bool> {
+    let Ok(secret) = user_doc.get_str("totp_secret") else {
+        return Ok(false);
+    };
+    let Some(step) = totp::verify(secret, code, chrono::Utc::now().timestamp()) else {
+        return Ok(false);
+    };
+
+    // Only one login per time step, so an observed code cannot be replayed
+    let result = users
+        .update_one(
+            doc! {
+                "_id": user_doc.get_object_id("_id").unwrap_or_default(),
+                "$or": [
+                    { "totp_last_step": { "$exists": false } },
+                    { "totp_last_step": { "$lt": step } }
+                ]
+            },
+            doc! { "$set": { "totp_last_step": step } },
+        )
+        .await?;
+
+    Ok(result.modified_count == 1)
+}
+
+/// Consumes a recovery code if it belongs to the user
+async fn use_recovery_code(
+    users: &Collection<Document>,
+    user_id: ObjectId,
+    recovery_code: &str,
+) -> mongodb::error::Result<bool> {
+    let code_hash = hash_token(recovery_code.trim());
+    let result = users
+        .update_one(
+            doc! { "_id": user_id, "recovery_codes": &code_hash },
+            doc! { "$pull": { "recovery_codes": &code_hash } },
+        )
+        .await?;
+
+    Ok(result.modified_count == 1)
+}
+
+pub async fn create_challenge(db: &Database, user_id: ObjectId) -> mongodb::error::Result<String> {
+    let challenges: Collection<MfaChallenge> = db.collection("mfa_challenges");
+
+    let token = generate_token();
+    let now = DateTime::now();
+    let challenge = MfaChallenge {
+        id: None,
+        user_id,
+        token_hash: hash_token(&token),
+        created_at: now,
+        expires_at: DateTime::from_millis(now.timestamp_millis() + CHALLENGE_TTL_MINUTES * 60_000),
+        attempts: 0,
+        used: false,
+    };
+    challenges.insert_one(challenge).await?;
+
+    Ok(token)
+}
+
+pub async fn enroll(state: AppState, user_id: ObjectId) -> Response {
+    let db = state.db.lock().await;
+    let users: Collection<Document> = db.database("disaster").collection("users");
+
+    let user_doc = match users.find_one(doc! { "_id": user_id }).await {
+        Ok(Some(user_doc)) => user_doc,
+        Ok(None) => return error_response("User not found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    if matches!(user_doc.get_bool("totp_enabled"), Ok(true)) {
+        return error_response("Two-factor authentication is already enabled", StatusCode::BAD_REQUEST);
+    }
+
+    // Kept pending until the user proves the app is set up by confirming a code
+    let secret = totp::generate_secret();
+    if let Err(e) = users
+        .update_one(doc! { "_id": user_id }, doc! { "$set": { "totp_pending_secret": &secret } })
+        .await
+    {
+        return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let email = user_doc.get_str("email").unwrap_or_default();
+    let enrollment = EnrollmentView {
+        provisioning_uri: totp::provisioning_uri(&secret, email, &totp_issuer()),
+        secret,
+    };
+
+    success_response("Scan the provisioning URI, then confirm with a code", enrollment, StatusCode::OK)
+}
+
+pub async fn confirm(state: AppState, user_id: ObjectId, code: String) -> Response {
+    let db = state.db.lock().await;
+    let users: Collection<Document> = db.database("disaster").collection("users");
+
+    let secret = match users.find_one(doc! { "_id": user_id }).await {
+        Ok(Some(user_doc)) => match user_doc.get_str("totp_pending_secret") {
+            Ok(secret) => secret.to_string(),
+            Err(_) => return error_response("Start enrollment before confirming", StatusCode::BAD_REQUEST),
+        },
+        Ok(None) => return error_response("User not found", StatusCode::NOT_FOUND),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let Some(step) = totp::verify(&secret, &code, chrono::Utc::now().timestamp()) else {
+        return error_response("Invalid two-factor code", StatusCode::BAD_REQUEST);
+    };
+
+    let (codes, hashes) = generate_recovery_codes();
+    let update = doc! {
+        "$set": {
+            "totp_secret": &secret,
+            "totp_enabled": true,
+            "totp_last_step": step,
+            "recovery_codes": hashes
+        },
+        "$unset": { "totp_pending_secret": "" }
+    };
+
+    match users.update_one(doc! { "_id": user_id }, update).await {
+        Ok(_) => success_response(
+            "Two-factor authentication enabled. Store the recovery codes now, they cannot be shown again",
+            codes,
+            StatusCode::OK,
+        ),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn disable(state: AppState, user_id: ObjectId, roles: Vec<Role>, code: String) -> Response {
+    if roles.iter().any(|role| totp_required_roles().contains(role)) {
+        return error_response("Two-factor authentication is required for your role", StatusCode::FORBIDDEN);
+    }
+
+    let db = state.db.lock().await;
+    let users: Collection<Document> = db.database("disaster").collection("users");
+
+    let user_doc = match users.find_one(doc! { "_id": user_id, "totp_enabled": true }).await {
+        Ok(Some(user_doc)) => user_doc,
+        Ok(None) => return error_response("Two-factor authentication is not enabled", StatusCode::BAD_REQUEST),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match verify_totp_code(&users, &user_doc, &code).await {
+        Ok(true) => {}
+        Ok(false) => return error_response("Invalid two-factor code", StatusCode::BAD_REQUEST),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let update = doc! {
+        "$set": { "totp_enabled": false },
+        "$unset": { "totp_secret": "", "totp_last_step": "", "recovery_codes": "" }
+    };
+
+    match users.update_one(doc! { "_id": user_id }, update).await {
+        Ok(_) => success_response("Two-factor authentication disabled", user_id.to_hex(), StatusCode::OK),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+pub async fn regenerate_recovery_codes(state: AppState, user_id: ObjectId, code: String) -> Response {
+    let db = state.db.lock().await;
+    let users: Collection<Document> = db.database("disaster").collection("users");
+
+    let user_doc = match users.find_one(doc! { "_id": user_id, "totp_enabled": true }).await {
+        Ok(Some(user_doc)) => user_doc,
+        Ok(None) => return error_response("Two-factor authentication is not enabled", StatusCode::BAD_REQUEST),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match verify_totp_code(&users, &user_doc, &code).await {
+        Ok(true) => {}
+        Ok(false) => return error_response("Invalid two-factor code", StatusCode::BAD_REQUEST),
+        Err(e) => return error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let (codes, hashes) = generate_recovery_codes();
+    match users
+        .update_one(doc! { "_id": user_id }, doc! { "$set": { "recovery_codes": hashes } })
+        .await
+    {
+        Ok(_) => success_response("Recovery codes regenerated", codes, StatusCode::OK),
+        Err(e) => error_response(&format!("Database error: {}", e), StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Second login step: exchanges the challenge and a TOTP or recovery code for the token pair
+pub async fn login_second_step(
+    state: AppState,
+    client: ClientInfo,
+    payload: TwoFactorLoginRequest,
+) -> Result<Response, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let users: Collection<Document> = database.collection("users");
+    let challenges: Collection<MfaChallenge> = database.collection("mfa_challenges");
+    let db_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    };
+
+    // Count the attempt in the same operation that reads the challenge, so guesses cannot race the limit
+    let challenge = challenges
+        .find_one_and_update(
+            doc! {
+                "token_hash": hash_token(&payload.mfa_token),
+                "used": false,
+                "attempts": { "$lt": MAX_CHALLENGE_ATTEMPTS },
+                "expires_at": { "$gt": DateTime::now() }
+            },
+            doc! { "$inc": { "attempts": 1 } },
+        )
+        .await
+        .map_err(db_error)?
+        .ok_or((
+            StatusCode::UNAUTHORIZED,
+            "Invalid or expired two-factor challenge, please log in again".to_string(),
+        ))?;
+
+    let user_doc = users
+        .find_one(doc! { "_id": challenge.user_id })
+        .await
+        .map_err(db_error)?
+        .ok_or((StatusCode::UNAUTHORIZED, "User no longer exists".to_string()))?;
+    let email = user_doc.get_str("email").unwrap_or_default().to_string();
+
+    // A fresh challenge per password login must not buy fresh guesses: wrong codes count
+    // towards the same account and address lockout as wrong passwords
+    if let Some(retry_after) = security_model::lockout_remaining(&database, &email, &client.ip)
+        .await
+        .map_err(db_error)?
+    {
+        let mut event = AuthEvent::new(AuthEventKind::LoginBlocked);
+        event.email = Some(email);
+        event.user_id = Some(challenge.user_id);
+        event.ip = Some(client.ip.clone());
+        event.device = Some(client.device.clone());
+        security_model::log_auth_event(&database, event).await.map_err(db_error)?;
+
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Too many failed login attempts, try again in {} seconds", retry_after),
+        ));
+    }
+
+    let verified = match (&payload.code, &payload.recovery_code) {
+        (Some(code), _) => verify_totp_code(&users, &user_doc, code).await.map_err(db_error)?,
+        (None, Some(recovery_code)) => use_recovery_code(&users, challenge.user_id, recovery_code)
+            .await
+            .map_err(db_error)?,
+        (None, None) => false,
+    };
+
+    if !verified {
+        security_model::record_login_failure(
+            &database,
+            &email,
+            Some(challenge.user_id),
+            &client,
+            Some("invalid second factor"),
+        )
+        .await
+        .map_err(db_error)?;
+
+        return Err((StatusCode::UNAUTHORIZED, "Invalid two-factor code".to_string()));
+    }
+
+    let consumed = challenges
+        .update_one(doc! { "_id": challenge.id, "used": false }, doc! { "$set": { "used": true } })
+        .await
+        .map_err(db_error)?;
+    if consumed.modified_count == 0 {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "Invalid or expired two-factor challenge, please log in again".to_string(),
+        ));
+    }
+
+    complete_login(&database, &state.jwt_keys, challenge.user_id, &email, client).await
+}
@@ -0,0 +1,70 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use super::{
+    two_factor_model,
+    two_factor_structure::{TotpCodeRequest, TwoFactorLoginRequest},
+};
+use crate::{
+    middleware::permission::AuthUser,
+    session::session_service::client_info,
+    utils::{db::AppState, response::error_response},
+};
+
+pub async fn enroll_service(State(state): State<AppState>, user: AuthUser) -> Response {
+    two_factor_model::enroll(state, user.id).await
+}
+
+pub async fn confirm_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> Response {
+    if payload.code.trim().is_empty() {
+        return error_response("Code must not be empty", StatusCode::BAD_REQUEST);
+    }
+    two_factor_model::confirm(state, user.id, payload.code).await
+}
+
+pub async fn disable_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> Response {
+    if payload.code.trim().is_empty() {
+        return error_response("Code must not be empty", StatusCode::BAD_REQUEST);
+    }
+    two_factor_model::disable(state, user.id, user.roles, payload.code).await
+}
+
+pub async fn recovery_codes_service(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> Response {
+    if payload.code.trim().is_empty() {
+        return error_response("Code must not be empty", StatusCode::BAD_REQUEST);
+    }
+    two_factor_model::regenerate_recovery_codes(state, user.id, payload.code).await
+}
+
+pub async fn login_service(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<TwoFactorLoginRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if payload.mfa_token.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "mfa_token must not be empty".to_string()));
+    }
+    if payload.code.is_none() && payload.recovery_code.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "A code or recovery code is required".to_string()));
+    }
+    two_factor_model::login_second_step(state, client_info(&headers, addr), payload).await
+}
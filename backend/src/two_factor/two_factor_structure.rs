@@ -0,0 +1,35 @@
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+/// Pending second login step, created once the password has been verified
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MfaChallenge {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub token_hash: String, // SHA-256 of the `mfa_token` handed to the client
+    pub created_at: DateTime,
+    pub expires_at: DateTime,
+    pub attempts: i32,
+    pub used: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TotpCodeRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub mfa_token: String,
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub recovery_code: Option<String>, // Used instead of `code` when the device is lost
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnrollmentView {
+    pub secret: String,
+    pub provisioning_uri: String, // Render as a QR code for the authenticator app
+}
@@ -3,14 +3,32 @@ pub mod user_service;
 pub mod user_structure;
 use std::sync::Arc;
 
-use axum::routing::post;
+use axum::middleware::from_fn;
+use axum::routing::{get, post};
 use axum::Router;
 use user_service::login_service;
-use crate::utils::db::AppState; 
+use crate::{middleware::auth::auth_middleware, oidc, session, two_factor, utils::db::AppState}; 
 
 pub fn user_routes(state: Arc<AppState>) -> Router {
     Router::new()
+        .route(
+            "/me",
+            get(user_service::get_profile_service)
+                .patch(user_service::update_profile_service)
+                .delete(user_service::delete_account_service),
+        )
+        .route("/change_password", post(user_service::change_password_service))
+        .route("/logout", post(user_service::logout_service))
+        .layer(from_fn(auth_middleware))
         .route("/login", post(login_service))
         .route("/register", post(user_service::register_service)) 
+        .route("/refresh", post(user_service::refresh_service))
+        .route("/forgot_password", post(user_service::forgot_password_service))
+        .route("/reset_password", post(user_service::reset_password_service))
+        .route("/verify", post(user_service::verify_email_service))
+        .route("/resend_verification", post(user_service::resend_verification_service))
         .with_state((*state).clone())
+        .nest("/sessions", session::session_routes(state.clone()))
+        .nest("/2fa", two_factor::two_factor_routes(state.clone()))
+        .nest("/oidc", oidc::oidc_routes(state))
 }
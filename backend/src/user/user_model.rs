@@ -1,18 +1,46 @@
-use super::user_structure::{Claims, LoginRequest, RegisterRequest};
-use crate::utils::db::AppState;
+use super::user_structure::{
+    ChangePasswordRequest, DeleteAccountRequest, ForgotPasswordRequest, LoginRequest,
+    OneTimeToken, ProfileView, RefreshRequest, RefreshToken, RegisterRequest,
+    ResendVerificationRequest, ResetPasswordRequest, Role, RoleStatus, UpdateProfileRequest, User,
+    VerifyEmailRequest,
+};
+use crate::{
+    jwt::jwt_structure::JwtKeys,
+    organization::organization_structure::{OrgRole, Organization},
+    security::{
+        security_model,
+        security_structure::{AuthEvent, AuthEventKind},
+    },
+    session::{session_model, session_structure::ClientInfo},
+    two_factor::two_factor_model,
+    utils::{
+        crypto::{generate_token, hash_token},
+        db::AppState,
+        auth_cookie::{append_set_cookies, auth_cookies, cleared_auth_cookies},
+        mail::{app_base_url, MailMessage},
+    },
+};
 use argon2::password_hash::{rand_core::OsRng, PasswordHash, SaltString};
 use argon2::{Argon2, PasswordHasher, PasswordVerifier};
 use axum::{
     extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Json,
 };
-use jsonwebtoken::{encode, EncodingKey, Header};
-use mongodb::{bson::doc, Collection};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::ReturnDocument,
+    Collection, Database,
+};
 use serde_json::json;
+use std::env;
 
-const SECRET_KEY: &[u8] = b"disaster";
+const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+const PASSWORD_RESET_TTL_MINUTES: i64 = 30;
+const EMAIL_VERIFICATION_TTL_HOURS: i64 = 48;
 
 
 fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
@@ -35,22 +63,153 @@ fn verify_password(password: &str, hashed_password: &str) -> bool {
     }
 }
 
-pub fn generate_jwt(email: &str) -> Result<String, jsonwebtoken::errors::Error> {
-    let expiration = chrono::Utc::now()
-        .checked_add_signed(chrono::Duration::hours(24))
-        .expect("valid timestamp")
-        .timestamp() as usize;
+pub fn generate_jwt(keys: &JwtKeys, email: &str, session_id: &ObjectId) -> Result<String, jsonwebtoken::errors::Error> {
+    keys.sign(email, session_id, ACCESS_TOKEN_TTL_MINUTES * 60, None)
+}
+
+/// Stores a new refresh token for `session_id` and returns the raw value for the client.
+/// The expiry is pushed forward on every rotation, which gives a sliding session.
+async fn issue_refresh_token(
+    collection: &Collection<RefreshToken>,
+    user_id: ObjectId,
+    session_id: ObjectId,
+) -> Result<String, (StatusCode, String)> {
+    let refresh_token = generate_token();
+    let now = DateTime::now();
 
-    let claims = Claims {
-        sub: email.to_owned(),
-        exp: expiration,
+    let record = RefreshToken {
+        id: None,
+        token_hash: hash_token(&refresh_token),
+        session_id,
+        user_id,
+        created_at: now,
+        expires_at: DateTime::from_millis(
+            now.timestamp_millis() + chrono::Duration::days(REFRESH_TOKEN_TTL_DAYS).num_milliseconds(),
+        ),
+        used: false,
+        revoked: false,
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(SECRET_KEY),
+    collection.insert_one(record).await.map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to save refresh token".to_string(),
+        )
+    })?;
+
+    Ok(refresh_token)
+}
+
+/// Builds the response shared by login and refresh: bearer header, HttpOnly cookie and JSON body
+/// Returns the token pair in the body for API clients and as cookies for browsers.
+/// Browser clients echo `csrf_token` in the `X-CSRF-Token` header on state-changing requests.
+fn token_response(message: &str, token: String, refresh_token: String) -> impl IntoResponse {
+    let csrf_token = generate_token();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "Authorization",
+        HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+    );
+    append_set_cookies(
+        &mut headers,
+        auth_cookies(
+            &token,
+            &refresh_token,
+            &csrf_token,
+            ACCESS_TOKEN_TTL_MINUTES * 60,
+            REFRESH_TOKEN_TTL_DAYS * 86_400,
+        ),
+    );
+
+    (
+        StatusCode::OK,
+        headers,
+        Json(json!({
+            "message": message,
+            "token": token,
+            "refresh_token": refresh_token,
+            "csrf_token": csrf_token
+        })),
+    )
+}
+
+/// Issues a single-use token for `user_id`, invalidating any earlier unused one in the same collection
+async fn create_one_time_token(
+    collection: &Collection<OneTimeToken>,
+    user_id: ObjectId,
+    ttl: chrono::Duration,
+) -> mongodb::error::Result<String> {
+    collection
+        .update_many(doc! { "user_id": user_id, "used": false }, doc! { "$set": { "used": true } })
+        .await?;
+
+    let token = generate_token();
+    let now = DateTime::now();
+    let record = OneTimeToken {
+        id: None,
+        user_id,
+        token_hash: hash_token(&token),
+        created_at: now,
+        expires_at: DateTime::from_millis(now.timestamp_millis() + ttl.num_milliseconds()),
+        used: false,
+    };
+    collection.insert_one(record).await?;
+
+    Ok(token)
+}
+
+/// Marks the token as used in the same operation that checks it; `None` if unknown, used or expired
+async fn consume_one_time_token(
+    collection: &Collection<OneTimeToken>,
+    token: &str,
+) -> mongodb::error::Result<Option<OneTimeToken>> {
+    collection
+        .find_one_and_update(
+            doc! {
+                "token_hash": hash_token(token),
+                "used": false,
+                "expires_at": { "$gt": DateTime::now() }
+            },
+            doc! { "$set": { "used": true } },
+        )
+        .await
+}
+
+/// Mails an email verification link to a newly registered (or re-requesting) user
+async fn send_verification_mail(
+    state: &AppState,
+    collection: &Collection<OneTimeToken>,
+    user_id: ObjectId,
+    email: &str,
+) -> Result<(), (StatusCode, String)> {
+    let token = create_one_time_token(
+        collection,
+        user_id,
+        chrono::Duration::hours(EMAIL_VERIFICATION_TTL_HOURS),
     )
+    .await
+    .map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to create verification token".to_string(),
+        )
+    })?;
+
+    let body = format!(
+        "Welcome! Please confirm your email address before contributing.\n\n\
+         Verify your email: {}/verify-email?token={}\n\n\
+         The link expires in {} hours.",
+        app_base_url(),
+        token,
+        EMAIL_VERIFICATION_TTL_HOURS
+    );
+
+    state
+        .mailer
+        .send(MailMessage::new(email, "Verify your email address", body))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
 }
 
 
@@ -59,7 +218,7 @@ pub async fn register(
     Json(payload): Json<RegisterRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     let db = state.db.lock().await;
-    let collection: Collection<mongodb::bson::Document> =
+    let collection: Collection<Document> =
         db.database("disaster").collection("users");
 
     
@@ -85,99 +244,734 @@ pub async fn register(
         )
     })?;
 
-    let new_user = doc! {
+    let requested_role = payload.role.unwrap_or(Role::Community);
+
+    // BOOTSTRAP_ADMIN_EMAIL lets the very first admin in without someone to approve them
+    let is_bootstrap_admin = env::var("BOOTSTRAP_ADMIN_EMAIL")
+        .map(|email| email.eq_ignore_ascii_case(&payload.email))
+        .unwrap_or(false);
+
+    let mut new_user = doc! {
         "email": &payload.email,
         "password": hashed_password,
         "name": &payload.name,
-        "token": null
+        "requested_role": requested_role.as_str(),
+        "role_status": RoleStatus::Pending.as_str(),
+        "verified": false
     };
 
-    collection.insert_one(new_user).await.map_err(|_| {
+    if is_bootstrap_admin {
+        new_user.insert("roles", vec![Role::Admin.as_str()]);
+        new_user.insert("requested_role", Role::Admin.as_str());
+        new_user.insert("role_status", RoleStatus::Approved.as_str());
+    }
+
+    let user_id = collection
+        .insert_one(new_user)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to create user".to_string(),
+            )
+        })?
+        .inserted_id
+        .as_object_id()
+        .unwrap_or_default();
+
+    let verifications: Collection<OneTimeToken> =
+        db.database("disaster").collection("email_verifications");
+    send_verification_mail(&state, &verifications, user_id, &payload.email).await?;
+
+    Ok(Json(json!({
+        "message": "User registered successfully",
+        "role_status": if is_bootstrap_admin { RoleStatus::Approved.as_str() } else { RoleStatus::Pending.as_str() }
+    })))
+}
+
+
+/// Opens a session and issues the token pair once every authentication step has passed
+pub async fn complete_login(
+    database: &Database,
+    keys: &JwtKeys,
+    user_id: ObjectId,
+    email: &str,
+    client: ClientInfo,
+) -> Result<Response, (StatusCode, String)> {
+    security_model::record_login_success(database, email, user_id, &client)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database error".to_string(),
+            )
+        })?;
+
+    // Every login opens its own session, so other devices stay logged in
+    let session_id = session_model::create_session(database, user_id, client)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to create session".to_string(),
+            )
+        })?;
+
+    let token = generate_jwt(keys, email, &session_id).map_err(|_| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to create user".to_string(),
+            "Failed to generate token".to_string(),
         )
     })?;
 
-    Ok(Json(json!({"message": "User registered successfully"})))
+    let refresh_tokens: Collection<RefreshToken> = database.collection("refresh_tokens");
+    let refresh_token = issue_refresh_token(&refresh_tokens, user_id, session_id).await?;
+
+    Ok(token_response("Login successful", token, refresh_token).into_response())
 }
 
 
+/// Starts the second login step for an account with two-factor enabled: a short-lived challenge
+/// to exchange at `/user/2fa/login` instead of tokens. Shared by every first factor.
+pub async fn mfa_challenge_response(database: &Database, user_id: ObjectId) -> Result<Response, (StatusCode, String)> {
+    let mfa_token = two_factor_model::create_challenge(database, user_id).await.map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    })?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "message": "Two-factor code required",
+            "mfa_required": true,
+            "mfa_token": mfa_token
+        })),
+    )
+        .into_response())
+}
+
 pub async fn login(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(payload): Json<LoginRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
     let db = state.db.lock().await;
-    let collection: Collection<mongodb::bson::Document> =
-        db.database("disaster").collection("users");
+    let database = db.database("disaster");
+    let collection: Collection<Document> = database.collection("users");
+    let db_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    };
+
+    // Refuse early while the account or the address is backing off or locked out
+    if let Some(retry_after) = security_model::lockout_remaining(&database, &payload.email, &client.ip)
+        .await
+        .map_err(db_error)?
+    {
+        let mut event = AuthEvent::new(AuthEventKind::LoginBlocked);
+        event.email = Some(payload.email.clone());
+        event.ip = Some(client.ip.clone());
+        event.device = Some(client.device.clone());
+        security_model::log_auth_event(&database, event).await.map_err(db_error)?;
+
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Too many failed login attempts, try again in {} seconds", retry_after),
+        ));
+    }
 
-    
     let user = collection
         .find_one(doc! { "email": &payload.email })
         .await
-        .map_err(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database error".to_string(),
-            )
-        })?;
+        .map_err(db_error)?;
 
     match user {
         Some(user_doc) => {
             let stored_password = user_doc.get_str("password").unwrap_or_default();
 
             if !verify_password(&payload.password, stored_password) {
+                security_model::record_login_failure(
+                    &database,
+                    &payload.email,
+                    user_doc.get_object_id("_id").ok(),
+                    &client,
+                    None,
+                )
+                .await
+                .map_err(db_error)?;
+
                 return Err((
                     StatusCode::UNAUTHORIZED,
                     "Invalid email or password".to_string(),
                 ));
             }
 
-            let token = generate_jwt(&payload.email).map_err(|_| {
+            let user_id = user_doc.get_object_id("_id").map_err(|_| {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    "Failed to generate token".to_string(),
+                    "Invalid user record".to_string(),
                 )
             })?;
 
-            collection
-                .update_one(
-                    doc! { "email": &payload.email },
-                    doc! { "$set": { "token": token.clone() } },
-                    
+            if matches!(user_doc.get_bool("deactivated"), Ok(true)) {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    "This account has been deactivated".to_string(),
+                ));
+            }
+
+            // Accounts with two-factor authentication get a short-lived challenge instead of tokens
+            if matches!(user_doc.get_bool("totp_enabled"), Ok(true)) {
+                return mfa_challenge_response(&database, user_id).await;
+            }
+
+            complete_login(&database, &state.jwt_keys, user_id, &payload.email, client).await
+        }
+        None => {
+            // Unknown emails count too, so probing for accounts is throttled the same way
+            security_model::record_login_failure(&database, &payload.email, None, &client, None)
+                .await
+                .map_err(db_error)?;
+
+            Err((
+                StatusCode::UNAUTHORIZED,
+                "Invalid email or password".to_string(),
+            ))
+        }
+    }
+}
+
+
+pub async fn refresh(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let refresh_tokens: Collection<RefreshToken> =
+        db.database("disaster").collection("refresh_tokens");
+    let users: Collection<Document> = db.database("disaster").collection("users");
+
+    let token_hash = hash_token(&payload.refresh_token);
+    let db_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    };
+
+    // Mark the token as used in the same operation that reads it,
+    // so two concurrent refreshes with the same token cannot both succeed
+    let current = refresh_tokens
+        .find_one_and_update(
+            doc! { "token_hash": &token_hash, "used": false, "revoked": false },
+            doc! { "$set": { "used": true } },
+        )
+        .await
+        .map_err(db_error)?;
+
+    let current = match current {
+        Some(current) => current,
+        None => {
+            // A known token that was already rotated or revoked is being replayed:
+            // revoke the whole session so neither party can keep using it
+            if let Some(stale) = refresh_tokens
+                .find_one(doc! { "token_hash": &token_hash })
+                .await
+                .map_err(db_error)?
+            {
+                session_model::revoke_sessions(
+                    &db.database("disaster"),
+                    doc! { "_id": stale.session_id },
                 )
                 .await
-                .map_err(|_| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "Failed to save token to database".to_string(),
-                    )
-                })?;
-
-            
-            let mut headers = HeaderMap::new();
-            headers.insert(
-                "Authorization",
-                HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
-            );  
-
-            let cookie_value = format!(
-                "token={}; HttpOnly; Path=/; Max-Age=86400; SameSite=Strict",
-                token
-            );
-            headers.insert("Set-Cookie", HeaderValue::from_str(&cookie_value).unwrap());
-
-            let response = (
-                StatusCode::OK,
-                headers,
-                Json(json!({"message": "Login successful", "token": token})),
-            );
-
-            Ok(response)
+                .map_err(db_error)?;
+
+                return Err((
+                    StatusCode::UNAUTHORIZED,
+                    "Refresh token reuse detected, please log in again".to_string(),
+                ));
+            }
+
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                "Invalid refresh token".to_string(),
+            ));
         }
-        None => Err((
+    };
+
+    if current.expires_at < DateTime::now() {
+        return Err((
             StatusCode::UNAUTHORIZED,
-            "Invalid email or password".to_string(),
-        )),
+            "Refresh token expired, please log in again".to_string(),
+        ));
     }
+
+    session_model::touch_session(&db.database("disaster"), current.session_id, current.user_id)
+        .await
+        .map_err(db_error)?
+        .ok_or((StatusCode::UNAUTHORIZED, "Session has been revoked".to_string()))?;
+
+    let email = users
+        .find_one(doc! { "_id": current.user_id })
+        .await
+        .map_err(db_error)?
+        .and_then(|user_doc| user_doc.get_str("email").ok().map(str::to_string))
+        .ok_or((StatusCode::UNAUTHORIZED, "User no longer exists".to_string()))?;
+
+    let token = generate_jwt(&state.jwt_keys, &email, &current.session_id).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to generate token".to_string(),
+        )
+    })?;
+
+    let refresh_token =
+        issue_refresh_token(&refresh_tokens, current.user_id, current.session_id).await?;
+
+    Ok(token_response("Token refreshed successfully", token, refresh_token))
+}
+
+
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    // Same answer whether or not the account exists, so the endpoint cannot be used to probe emails
+    let response = Json(json!({"message": "If the account exists, a reset link has been sent"}));
+
+    let db = state.db.lock().await;
+    let users: Collection<Document> = db.database("disaster").collection("users");
+    let resets: Collection<OneTimeToken> = db.database("disaster").collection("password_resets");
+    let db_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    };
+
+    let user_id = match users
+        .find_one(doc! { "email": &payload.email })
+        .await
+        .map_err(db_error)?
+        .and_then(|user_doc| user_doc.get_object_id("_id").ok())
+    {
+        Some(user_id) => user_id,
+        None => return Ok(response),
+    };
+
+    // Only the most recent link stays valid
+    let token = create_one_time_token(
+        &resets,
+        user_id,
+        chrono::Duration::minutes(PASSWORD_RESET_TTL_MINUTES),
+    )
+    .await
+    .map_err(db_error)?;
+    drop(db);
+
+    let body = format!(
+        "A password reset was requested for your account.\n\n\
+         Reset your password: {}/reset-password?token={}\n\n\
+         The link expires in {} minutes. If you did not request this, you can ignore this email.",
+        app_base_url(),
+        token,
+        PASSWORD_RESET_TTL_MINUTES
+    );
+
+    state
+        .mailer
+        .send(MailMessage::new(&payload.email, "Reset your password", body))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    Ok(response)
+}
+
+
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let users: Collection<Document> = db.database("disaster").collection("users");
+    let resets: Collection<OneTimeToken> = db.database("disaster").collection("password_resets");
+    let db_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    };
+
+    let reset = consume_one_time_token(&resets, &payload.token)
+        .await
+        .map_err(db_error)?
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            "Invalid or expired reset token".to_string(),
+        ))?;
+
+    let hashed_password = hash_password(&payload.new_password).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to hash password".to_string(),
+        )
+    })?;
+
+    users
+        .update_one(
+            doc! { "_id": reset.user_id },
+            doc! { "$set": { "password": hashed_password } },
+        )
+        .await
+        .map_err(db_error)?;
+
+    // Whoever knew the old password must not stay logged in
+    session_model::revoke_sessions(
+        &db.database("disaster"),
+        doc! { "user_id": reset.user_id, "revoked": false },
+    )
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(json!({"message": "Password reset successfully"})))
+}
+
+
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let users: Collection<Document> = db.database("disaster").collection("users");
+    let verifications: Collection<OneTimeToken> =
+        db.database("disaster").collection("email_verifications");
+    let db_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    };
+
+    let verification = consume_one_time_token(&verifications, &payload.token)
+        .await
+        .map_err(db_error)?
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            "Invalid or expired verification token".to_string(),
+        ))?;
+
+    users
+        .update_one(
+            doc! { "_id": verification.user_id },
+            doc! { "$set": { "verified": true, "verified_at": DateTime::now() } },
+        )
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(json!({"message": "Email verified successfully"})))
+}
+
+
+pub async fn resend_verification(
+    State(state): State<AppState>,
+    Json(payload): Json<ResendVerificationRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let response = Json(json!({"message": "If the account is awaiting verification, a new link has been sent"}));
+
+    let db = state.db.lock().await;
+    let users: Collection<Document> = db.database("disaster").collection("users");
+    let verifications: Collection<OneTimeToken> =
+        db.database("disaster").collection("email_verifications");
+
+    let user_id = match users
+        .find_one(doc! { "email": &payload.email, "verified": false })
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database error".to_string(),
+            )
+        })?
+        .and_then(|user_doc| user_doc.get_object_id("_id").ok())
+    {
+        Some(user_id) => user_id,
+        None => return Ok(response),
+    };
+
+    send_verification_mail(&state, &verifications, user_id, &payload.email).await?;
+
+    Ok(response)
+}
+
+
+/// Stands in for the author of guide items whose account was deleted
+pub const DELETED_USER_ID: ObjectId = ObjectId::from_bytes([0; 12]);
+
+pub async fn get_profile(
+    State(state): State<AppState>,
+    user_id: ObjectId,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let users: Collection<User> = db.database("disaster").collection("users");
+
+    let user = users
+        .find_one(doc! { "_id": user_id })
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database error".to_string(),
+            )
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+    Ok(Json(json!({
+        "message": "Profile fetched successfully",
+        "user": ProfileView::from(user)
+    })))
+}
+
+
+pub async fn update_profile(
+    State(state): State<AppState>,
+    user_id: ObjectId,
+    Json(payload): Json<UpdateProfileRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let users: Collection<User> = db.database("disaster").collection("users");
+    let db_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    };
+
+    let mut set = Document::new();
+    let mut unset = Document::new();
+    if let Some(name) = &payload.name {
+        set.insert("name", name.trim());
+    }
+    match payload.phone.as_deref().map(str::trim) {
+        Some("") => {
+            unset.insert("phone", "");
+        }
+        Some(phone) => {
+            set.insert("phone", phone);
+        }
+        None => {}
+    }
+
+    let mut update = doc! {};
+    if !set.is_empty() {
+        update.insert("$set", set);
+    }
+    if !unset.is_empty() {
+        update.insert("$unset", unset);
+    }
+
+    let user = users
+        .find_one_and_update(doc! { "_id": user_id }, update)
+        .return_document(ReturnDocument::After)
+        .await
+        .map_err(db_error)?
+        .ok_or((StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+    Ok(Json(json!({
+        "message": "Profile updated successfully",
+        "user": ProfileView::from(user)
+    })))
+}
+
+
+pub async fn change_password(
+    State(state): State<AppState>,
+    user_id: ObjectId,
+    current_session: ObjectId,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let users: Collection<User> = db.database("disaster").collection("users");
+    let db_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    };
+
+    let user = users
+        .find_one(doc! { "_id": user_id })
+        .await
+        .map_err(db_error)?
+        .ok_or((StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+    if !verify_password(&payload.current_password, &user.password) {
+        return Err((StatusCode::UNAUTHORIZED, "Current password is incorrect".to_string()));
+    }
+
+    let hashed_password = hash_password(&payload.new_password).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to hash password".to_string(),
+        )
+    })?;
+
+    users
+        .update_one(
+            doc! { "_id": user_id },
+            doc! { "$set": { "password": hashed_password } },
+        )
+        .await
+        .map_err(db_error)?;
+
+    // Keep the device that made the change, log out every other one
+    let revoked = session_model::revoke_sessions(
+        &db.database("disaster"),
+        doc! { "user_id": user_id, "revoked": false, "_id": { "$ne": current_session } },
+    )
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(json!({
+        "message": "Password changed successfully",
+        "revoked_sessions": revoked
+    })))
+}
+
+
+/// Deletes the account and everything tied to it. Guide items the user wrote stay published,
+/// but are re-attributed to `DELETED_USER_ID`.
+pub async fn delete_account(
+    State(state): State<AppState>,
+    user_id: ObjectId,
+    Json(payload): Json<DeleteAccountRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let db = state.db.lock().await;
+    let database = db.database("disaster");
+    let users: Collection<User> = database.collection("users");
+    let db_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    };
+
+    let user = users
+        .find_one(doc! { "_id": user_id })
+        .await
+        .map_err(db_error)?
+        .ok_or((StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+    if !verify_password(&payload.password, &user.password) {
+        return Err((StatusCode::UNAUTHORIZED, "Password is incorrect".to_string()));
+    }
+
+    // Nobody would be left to approve applicants
+    if user.granted_roles().contains(&Role::Admin) {
+        let other_admins = users
+            .count_documents(doc! {
+                "_id": { "$ne": user_id },
+                "$or": [{ "roles": Role::Admin.as_str() }, { "role": Role::Admin.as_str() }]
+            })
+            .await
+            .map_err(db_error)?;
+        if other_admins == 0 {
+            return Err((StatusCode::CONFLICT, "The last admin account cannot be deleted".to_string()));
+        }
+    }
+
+    // The organization could no longer be managed by anyone
+    let organizations: Collection<Organization> = database.collection("organizations");
+    let owned: Vec<Organization> = organizations
+        .find(doc! { "members": { "$elemMatch": { "user_id": user_id, "role": OrgRole::Owner.as_str() } } })
+        .await
+        .map_err(db_error)?
+        .try_collect()
+        .await
+        .map_err(db_error)?;
+    if let Some(org) = owned.iter().find(|org| org.owner_count() == 1) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("You are the last owner of '{}', make another member owner first", org.name),
+        ));
+    }
+
+    let guides: Collection<Document> = database.collection("disaster_guide");
+    for field in ["do_s", "dont_s"] {
+        guides
+            .update_many(
+                doc! { format!("{}.user_id", field): user_id },
+                doc! { "$set": { format!("{}.$[item].user_id", field): DELETED_USER_ID } },
+            )
+            .array_filters(vec![doc! { "item.user_id": user_id }])
+            .await
+            .map_err(db_error)?;
+    }
+
+    organizations
+        .update_many(
+            doc! { "members.user_id": user_id },
+            doc! { "$pull": { "members": { "user_id": user_id } } },
+        )
+        .await
+        .map_err(db_error)?;
+
+    session_model::revoke_sessions(&database, doc! { "user_id": user_id })
+        .await
+        .map_err(db_error)?;
+
+    database
+        .collection::<Document>("api_keys")
+        .update_many(doc! { "owner_id": user_id }, doc! { "$set": { "revoked": true } })
+        .await
+        .map_err(db_error)?;
+
+    for collection in ["password_resets", "email_verifications", "mfa_challenges"] {
+        database
+            .collection::<Document>(collection)
+            .delete_many(doc! { "user_id": user_id })
+            .await
+            .map_err(db_error)?;
+    }
+
+    users
+        .delete_one(doc! { "_id": user_id })
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(json!({"message": "Account deleted successfully"})))
+}
+
+
+/// Ends the current session and clears the auth cookies
+pub async fn logout(
+    State(state): State<AppState>,
+    user_id: ObjectId,
+    session_id: ObjectId,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let db = state.db.lock().await;
+
+    session_model::revoke_sessions(
+        &db.database("disaster"),
+        doc! { "_id": session_id, "user_id": user_id },
+    )
+    .await
+    .map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database error".to_string(),
+        )
+    })?;
+
+    let mut headers = HeaderMap::new();
+    append_set_cookies(&mut headers, cleared_auth_cookies());
+
+    Ok((
+        StatusCode::OK,
+        headers,
+        Json(json!({"message": "Logged out successfully"})),
+    ))
 }
@@ -1,21 +1,39 @@
+use std::net::SocketAddr;
+
 use axum::{
-    extract::State, 
-    http::StatusCode, 
+    extract::{ConnectInfo, State}, 
+    http::{HeaderMap, StatusCode}, 
     response::IntoResponse, 
-    Json
+    Extension, Json
+};
+use crate::{
+    middleware::permission::AuthUser,
+    session::{session_service::client_info, session_structure::CurrentSession},
+    utils::{
+        auth_cookie::{csrf_token_matches, read_cookie, REFRESH_COOKIE},
+        db::AppState,
+    },
+};
+use super::{
+    user_model,
+    user_structure::{
+        ChangePasswordRequest, DeleteAccountRequest, ForgotPasswordRequest, LoginRequest,
+        RefreshRequest, RegisterRequest, ResendVerificationRequest, ResetPasswordRequest,
+        UpdateProfileRequest, VerifyEmailRequest,
+    },
 };
-use crate::utils::db::AppState;
-use super::{user_model, user_structure::{LoginRequest, RegisterRequest}};
 
 
 pub async fn login_service(
     State(_state): State<AppState>, 
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     if payload.email.trim().is_empty() || payload.password.trim().is_empty() {
         return Err((StatusCode::BAD_REQUEST, "Email and password must not be empty".to_string()));
     }
-    user_model::login(State(_state), Json(payload)).await
+    user_model::login(State(_state), client_info(&headers, addr), Json(payload)).await
 }
 
 pub async fn register_service(State(_state): State<AppState>, Json(payload): Json<RegisterRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
@@ -24,3 +42,94 @@ pub async fn register_service(State(_state): State<AppState>, Json(payload): Jso
     }
     user_model::register(State(_state), Json(payload)).await
 }
+
+pub async fn refresh_service(State(state): State<AppState>, headers: HeaderMap, Json(mut payload): Json<RefreshRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if payload.refresh_token.trim().is_empty() {
+        // Browser clients send the refresh cookie, which needs the CSRF header like any cookie-authenticated write
+        payload.refresh_token = read_cookie(&headers, REFRESH_COOKIE)
+            .ok_or((StatusCode::BAD_REQUEST, "Refresh token must not be empty".to_string()))?;
+        if !csrf_token_matches(&headers) {
+            return Err((StatusCode::FORBIDDEN, "Missing or invalid CSRF token".to_string()));
+        }
+    }
+    user_model::refresh(State(state), Json(payload)).await
+}
+
+pub async fn forgot_password_service(State(state): State<AppState>, Json(payload): Json<ForgotPasswordRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if payload.email.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Email must not be empty".to_string()));
+    }
+    user_model::forgot_password(State(state), Json(payload)).await
+}
+
+pub async fn reset_password_service(State(state): State<AppState>, Json(payload): Json<ResetPasswordRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if payload.token.trim().is_empty() || payload.new_password.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Token and new password must not be empty".to_string()));
+    }
+    user_model::reset_password(State(state), Json(payload)).await
+}
+
+pub async fn verify_email_service(State(state): State<AppState>, Json(payload): Json<VerifyEmailRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if payload.token.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Token must not be empty".to_string()));
+    }
+    user_model::verify_email(State(state), Json(payload)).await
+}
+
+pub async fn resend_verification_service(State(state): State<AppState>, Json(payload): Json<ResendVerificationRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if payload.email.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Email must not be empty".to_string()));
+    }
+    user_model::resend_verification(State(state), Json(payload)).await
+}
+
+/// Loose E.164 check: optional leading `+` and 7 to 15 digits, spaces and dashes ignored
+pub fn is_valid_phone(phone: &str) -> bool {
+    let digits: String = phone
+        .strip_prefix('+')
+        .unwrap_or(phone)
+        .chars()
+        .filter(|c| *c != ' ' && *c != '-')
+        .collect();
+    (7..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+pub async fn get_profile_service(State(state): State<AppState>, user: AuthUser) -> Result<impl IntoResponse, (StatusCode, String)> {
+    user_model::get_profile(State(state), user.id).await
+}
+
+pub async fn update_profile_service(State(state): State<AppState>, user: AuthUser, Json(payload): Json<UpdateProfileRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if payload.name.is_none() && payload.phone.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "Nothing to update".to_string()));
+    }
+    if payload.name.as_deref().is_some_and(|name| name.trim().is_empty()) {
+        return Err((StatusCode::BAD_REQUEST, "Name must not be empty".to_string()));
+    }
+    if let Some(phone) = payload.phone.as_deref().map(str::trim).filter(|phone| !phone.is_empty()) {
+        if !is_valid_phone(phone) {
+            return Err((StatusCode::BAD_REQUEST, "Invalid phone number".to_string()));
+        }
+    }
+    user_model::update_profile(State(state), user.id, Json(payload)).await
+}
+
+pub async fn change_password_service(State(state): State<AppState>, user: AuthUser, Extension(current): Extension<CurrentSession>, Json(payload): Json<ChangePasswordRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if payload.current_password.trim().is_empty() || payload.new_password.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Current and new password must not be empty".to_string()));
+    }
+    if payload.current_password == payload.new_password {
+        return Err((StatusCode::BAD_REQUEST, "New password must differ from the current one".to_string()));
+    }
+    user_model::change_password(State(state), user.id, current.id, Json(payload)).await
+}
+
+pub async fn delete_account_service(State(state): State<AppState>, user: AuthUser, Json(payload): Json<DeleteAccountRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if payload.password.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Password must not be empty".to_string()));
+    }
+    user_model::delete_account(State(state), user.id, Json(payload)).await
+}
+
+pub async fn logout_service(State(state): State<AppState>, user: AuthUser, Extension(current): Extension<CurrentSession>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    user_model::logout(State(state), user.id, current.id).await
+}
@@ -1,13 +1,102 @@
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{oid::ObjectId, DateTime};
 use serde::{Deserialize, Serialize};
 
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct User {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
     pub name: String,
     pub email: String,
     pub password: String,
+
+    #[serde(default)]
+    pub roles: Vec<Role>, // Granted roles, only added once an admin approves them
+
+    #[serde(default)]
+    pub role: Option<Role>, // Legacy single role, still honoured alongside `roles`
+
+    #[serde(default)]
+    pub requested_role: Option<Role>,
+
+    #[serde(default)]
+    pub role_status: Option<RoleStatus>,
+
+    #[serde(default)]
+    pub role_reason: Option<String>, // Reason given by the admin on approval/rejection
+
+    #[serde(default)]
+    pub phone: Option<String>,
+
+    #[serde(default)]
+    pub verified: Option<bool>, // Missing on accounts created before email verification, treated as verified
+
+    #[serde(default)]
+    pub totp_enabled: bool,
+
+    #[serde(default)]
+    pub deactivated: bool, // Set by an admin; the account can neither log in nor use existing tokens
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    Ngo,
+    Local,
+    Community,
+}
+
+impl User {
+    /// Granted roles including the legacy single `role` field
+    pub fn granted_roles(&self) -> Vec<Role> {
+        let mut roles = self.roles.clone();
+        if let Some(role) = self.role {
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
+        }
+        roles
+    }
+}
+
+impl Role {
+    pub fn parse(value: &str) -> Option<Role> {
+        match value {
+            "admin" => Some(Role::Admin),
+            "ngo" => Some(Role::Ngo),
+            "local" => Some(Role::Local),
+            "community" => Some(Role::Community),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Ngo => "ngo",
+            Role::Local => "local",
+            Role::Community => "community",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RoleStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl RoleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoleStatus::Pending => "pending",
+            RoleStatus::Approved => "approved",
+            RoleStatus::Rejected => "rejected",
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -21,10 +110,123 @@ pub struct RegisterRequest {
     pub email: String,
     pub password: String,
     pub name: String,
+    #[serde(default)]
+    pub role: Option<Role>, // Requested role, defaults to community
+}
+
+#[derive(Deserialize)]
+pub struct RefreshRequest {
+    #[serde(default)]
+    pub refresh_token: String, // Browsers may leave this empty and send the refresh cookie instead
+}
+
+#[derive(Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResendVerificationRequest {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateProfileRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>, // Empty string removes the phone number
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: String, // Re-entered to confirm the deletion
+}
+
+/// What the signed-in user sees of their own account, never exposing hashes or secrets
+#[derive(Debug, Serialize)]
+pub struct ProfileView {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub roles: Vec<Role>,
+    pub requested_role: Option<Role>,
+    pub role_status: Option<RoleStatus>,
+    pub verified: bool,
+    pub totp_enabled: bool,
+}
+
+impl From<User> for ProfileView {
+    fn from(user: User) -> Self {
+        ProfileView {
+            id: user.id.map(|id| id.to_hex()).unwrap_or_default(),
+            roles: user.granted_roles(),
+            name: user.name,
+            email: user.email,
+            phone: user.phone,
+            requested_role: user.requested_role,
+            role_status: user.role_status,
+            verified: user.verified.unwrap_or(true),
+            totp_enabled: user.totp_enabled,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Claims {
     pub sub: String,
     pub exp: usize,
-}
\ No newline at end of file
+    pub iat: usize,
+    pub iss: String,
+    pub aud: String,
+    pub jti: String, // Unique per token
+    pub sid: String, // Session the token was issued for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>, // Restricted token kind, e.g. `citizen`; absent on full user tokens
+}
+
+/// Opaque refresh token stored in the `refresh_tokens` collection.
+/// Every token issued by rotation shares the `session_id` of the login that started it,
+/// so a replayed token can revoke the whole chain.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefreshToken {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub token_hash: String, // SHA-256 of the token handed to the client
+    pub session_id: ObjectId,
+    pub user_id: ObjectId,
+    pub created_at: DateTime,
+    pub expires_at: DateTime,
+    pub used: bool,    // Set once the token has been exchanged for a new pair
+    pub revoked: bool, // Set when the session is revoked
+}
+
+/// Single-use token sent by mail, stored in `password_resets` or `email_verifications`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OneTimeToken {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub token_hash: String, // SHA-256 of the token sent by mail
+    pub created_at: DateTime,
+    pub expires_at: DateTime,
+    pub used: bool,
+}
@@ -0,0 +1,92 @@
+use std::env;
+
+use axum::http::{header, HeaderMap, HeaderValue};
+use tower_cookies::{
+    cookie::{time::Duration, SameSite},
+    Cookie,
+};
+
+pub const ACCESS_COOKIE: &str = "token";
+pub const REFRESH_COOKIE: &str = "refresh_token";
+/// Readable by the frontend, which echoes it in `CSRF_HEADER` (double-submit)
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+
+/// The refresh cookie is only sent to the endpoints that consume it
+const REFRESH_COOKIE_PATH: &str = "/user";
+
+/// `COOKIE_SAMESITE` is `strict` (default), `lax` or `none`. `COOKIE_SECURE=true` marks cookies
+/// HTTPS-only, which browsers require anyway for `SameSite=None`.
+fn cookie_attributes() -> (SameSite, bool) {
+    let same_site = match env::var("COOKIE_SAMESITE").unwrap_or_default().to_lowercase().as_str() {
+        "lax" => SameSite::Lax,
+        "none" => SameSite::None,
+        _ => SameSite::Strict,
+    };
+    let secure = same_site == SameSite::None
+        || env::var("COOKIE_SECURE").is_ok_and(|value| value.eq_ignore_ascii_case("true"));
+
+    (same_site, secure)
+}
+
+fn build_cookie(name: &'static str, value: String, path: &'static str, http_only: bool, max_age: Duration) -> Cookie<'static> {
+    let (same_site, secure) = cookie_attributes();
+    Cookie::build((name, value))
+        .path(path)
+        .http_only(http_only)
+        .secure(secure)
+        .same_site(same_site)
+        .max_age(max_age)
+        .build()
+}
+
+/// Cookies set on login and refresh: the access token, the refresh token and a fresh CSRF token
+pub fn auth_cookies(token: &str, refresh_token: &str, csrf_token: &str, access_ttl_secs: i64, refresh_ttl_secs: i64) -> Vec<Cookie<'static>> {
+    vec![
+        build_cookie(ACCESS_COOKIE, token.to_string(), "/", true, Duration::seconds(access_ttl_secs)),
+        build_cookie(REFRESH_COOKIE, refresh_token.to_string(), REFRESH_COOKIE_PATH, true, Duration::seconds(refresh_ttl_secs)),
+        build_cookie(CSRF_COOKIE, csrf_token.to_string(), "/", false, Duration::seconds(refresh_ttl_secs)),
+    ]
+}
+
+/// Expired copies of the auth cookies, so the browser drops them
+pub fn cleared_auth_cookies() -> Vec<Cookie<'static>> {
+    vec![
+        build_cookie(ACCESS_COOKIE, String::new(), "/", true, Duration::ZERO),
+        build_cookie(REFRESH_COOKIE, String::new(), REFRESH_COOKIE_PATH, true, Duration::ZERO),
+        build_cookie(CSRF_COOKIE, String::new(), "/", false, Duration::ZERO),
+    ]
+}
+
+pub fn append_set_cookies(headers: &mut HeaderMap, cookies: Vec<Cookie<'static>>) {
+    for cookie in cookies {
+        if let Ok(value) = HeaderValue::from_str(&cookie.to_string()) {
+            headers.append(header::SET_COOKIE, value);
+        }
+    }
+}
+
+/// Reads a cookie sent by the browser
+pub fn read_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(Cookie::split_parse)
+        .filter_map(Result::ok)
+        .find(|cookie| cookie.name() == name)
+        .map(|cookie| cookie.value().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Double-submit check: the `X-CSRF-Token` header must repeat the `csrf_token` cookie
+pub fn csrf_token_matches(headers: &HeaderMap) -> bool {
+    let header_token = headers.get(CSRF_HEADER).and_then(|value| value.to_str().ok());
+
+    match (read_cookie(headers, CSRF_COOKIE), header_token) {
+        (Some(cookie_token), Some(header_token)) => {
+            super::crypto::constant_time_eq(cookie_token.as_bytes(), header_token.as_bytes())
+        }
+        _ => false,
+    }
+}
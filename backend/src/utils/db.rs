@@ -4,9 +4,16 @@ use tokio::sync::Mutex;
 use std::{env, sync::Arc};
 use dotenv::dotenv;
 
+use super::mail::{mailer_from_env, Mailer};
+use super::sms::{sms_gateway_from_env, SmsGateway};
+use crate::jwt::{jwt_model::keys_from_env, jwt_structure::JwtKeys};
+
 #[derive(Clone)]
 pub struct AppState {
     pub db: Arc<Mutex<Client>>, 
+    pub mailer: Arc<dyn Mailer>,
+    pub sms: Arc<dyn SmsGateway>,
+    pub jwt_keys: Arc<JwtKeys>,
 }
 
 lazy_static! {
@@ -26,6 +33,9 @@ pub async fn initialize_db() -> AppState {
     *db_lock = Some(client.clone()); 
 
     AppState {
+        mailer: mailer_from_env(&client),
+        sms: sms_gateway_from_env(&client),
+        jwt_keys: Arc::new(keys_from_env()),
         db: Arc::new(Mutex::new(client)),
     }
 }
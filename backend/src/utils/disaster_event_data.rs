@@ -1,30 +1,40 @@
-use reqwest::{Client};
-use serde_json::{Value};
-use axum::{response::Json, http::StatusCode};
+use std::{env, time::Duration};
+
+use reqwest::Client;
+use serde_json::Value;
+use axum::http::StatusCode;
+
+const DEFAULT_GDACS_URL: &str = "https://www.gdacs.org/gdacsapi/api/events/geteventlist/EVENTS4APP";
+
+/// GDACS event list endpoint, from `GDACS_EVENTS_URL` so a local fixture server can stand in
+pub fn gdacs_events_url() -> String {
+    env::var("GDACS_EVENTS_URL").unwrap_or_else(|_| DEFAULT_GDACS_URL.to_string())
+}
 
 // Here Return as Result (ok, err) to handle the error for use of fetch_disaster_event_data call
-pub async fn disaster_event_data() -> Result<Json<Value>, (StatusCode, String)> {
+pub async fn disaster_event_data(url: &str) -> Result<Value, (StatusCode, String)> {
 
-    // GDACS API endpoint URL
-    let url = "https://www.gdacs.org/gdacsapi/api/events/geteventlist/EVENTS4APP";
+    // GDACS can be slow; do not let one poll hang forever
+    let client = Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Error building HTTP client: {}", e)))?;
 
     // Send GET request to GDACS API
-    let client = Client::new();
-    
-    match client.get(url).send().await {
+    match client.get(url).send().await.and_then(|response| response.error_for_status()) {
         Ok(response) => {
             // Parse the response as JSON
             match response.json::<Value>().await {
-                Ok(json_data) => Ok(Json(json_data)),
+                Ok(json_data) => Ok(json_data),
                 Err(e) => Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                    StatusCode::BAD_GATEWAY,
                     format!("Error parsing JSON: {}", e),
                 )),
             }
         }
         Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::BAD_GATEWAY,
             format!("Error fetching GDACS API: {}", e),
         )),
     }
-}
\ No newline at end of file
+}
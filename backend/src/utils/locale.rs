@@ -0,0 +1,99 @@
+use std::env;
+
+/// Language the records are written in, from `DEFAULT_LOCALE` (`en` by default)
+pub fn default_locale() -> String {
+    env::var("DEFAULT_LOCALE")
+        .ok()
+        .and_then(|locale| normalize_locale(&locale))
+        .unwrap_or_else(|| "en".to_string())
+}
+
+/// Locales translators are expected to cover, from `SUPPORTED_LOCALES` (comma separated)
+pub fn supported_locales() -> Vec<String> {
+    env::var("SUPPORTED_LOCALES")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(normalize_locale)
+        .collect()
+}
+
+/// Lowercased BCP 47 tag (`pt-BR` becomes `pt-br`), or `None` if it is not a plausible tag:
+/// a 2-3 letter language followed by alphanumeric subtags of up to 8 characters
+pub fn normalize_locale(tag: &str) -> Option<String> {
+    let tag = tag.trim().replace('_', "-").to_lowercase();
+    let mut parts = tag.split('-');
+
+    let language = parts.next()?;
+    let valid_language = (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase());
+    let valid_subtags = parts.all(|part| (1..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    (valid_language && valid_subtags).then_some(tag)
+}
+
+/// Fallback chain for an `Accept-Language` header, most preferred first. Each tag is followed by
+/// its shorter forms (`pt-br`, then `pt`) and the chain always ends with the default locale.
+pub fn negotiate(accept_language: Option<&str>) -> Vec<String> {
+    let mut weighted: Vec<(String, f32)> = accept_language
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = normalize_locale(parts.next()?)?;
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q=").map(|q| q.parse().unwrap_or(0.0)))
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    // Stable, so equally weighted tags keep the client's order
+    weighted.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut chain: Vec<String> = Vec::new();
+    let candidates = weighted.into_iter().flat_map(|(tag, _)| {
+        let parts: Vec<&str> = tag.split('-').collect();
+        (1..=parts.len()).rev().map(move |len| parts[..len].join("-")).collect::<Vec<_>>()
+    });
+    for locale in candidates.chain(std::iter::once(default_locale())) {
+        if !chain.contains(&locale) {
+            chain.push(locale);
+        }
+    }
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_plausible_tags_only() {
+        assert_eq!(normalize_locale(" pt_BR "), Some("pt-br".to_string()));
+        assert_eq!(normalize_locale("zh-Hant-TW"), Some("zh-hant-tw".to_string()));
+        assert_eq!(normalize_locale("fil"), Some("fil".to_string()));
+        assert_eq!(normalize_locale("*"), None);
+        assert_eq!(normalize_locale("e"), None);
+        assert_eq!(normalize_locale("english"), None);
+        assert_eq!(normalize_locale("en-"), None);
+        assert_eq!(normalize_locale("en-toolongsubtag"), None);
+        assert_eq!(normalize_locale("es-419"), Some("es-419".to_string()));
+    }
+
+    #[test]
+    fn orders_by_quality_and_falls_back_to_the_region_and_default() {
+        assert_eq!(
+            negotiate(Some("fr;q=0.5, pt-BR, es;q=0.8, de;q=0")),
+            ["pt-br", "pt", "es", "fr", "en"]
+        );
+        // Equal weights keep the client's order; `q=0` and junk entries are dropped
+        assert_eq!(negotiate(Some("nl, *;q=0.1, sw")), ["nl", "sw", "en"]);
+        assert_eq!(negotiate(Some("de;q=abc, it")), ["it", "en"]);
+        // The default locale is never repeated
+        assert_eq!(negotiate(Some("en-GB, en;q=0.9")), ["en-gb", "en"]);
+    }
+
+    #[test]
+    fn missing_header_serves_the_default_locale() {
+        assert_eq!(negotiate(None), ["en"]);
+        assert_eq!(negotiate(Some("")), ["en"]);
+    }
+}
@@ -0,0 +1,81 @@
+use std::{env, sync::Arc};
+
+use futures::future::BoxFuture;
+use mongodb::{
+    bson::{oid::ObjectId, DateTime},
+    Client, Collection,
+};
+use serde::{Deserialize, Serialize};
+
+/// An email waiting to be (or already) delivered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailMessage {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    pub created_at: DateTime,
+}
+
+impl MailMessage {
+    pub fn new(to: &str, subject: &str, body: String) -> Self {
+        MailMessage {
+            id: None,
+            to: to.to_string(),
+            subject: subject.to_string(),
+            body,
+            created_at: DateTime::now(),
+        }
+    }
+}
+
+/// Mail delivery backend. Selected at startup with `MAIL_BACKEND`.
+pub trait Mailer: Send + Sync {
+    fn send(&self, message: MailMessage) -> BoxFuture<'_, Result<(), String>>;
+}
+
+/// Default backend: stores every message in the `outbox` collection, so flows can be
+/// exercised offline and a separate worker (or a tester) can pick the messages up
+pub struct OutboxMailer {
+    collection: Collection<MailMessage>,
+}
+
+impl Mailer for OutboxMailer {
+    fn send(&self, message: MailMessage) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            self.collection
+                .insert_one(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Failed to queue mail: {}", e))
+        })
+    }
+}
+
+/// Prints messages to stdout instead of delivering them
+pub struct LogMailer;
+
+impl Mailer for LogMailer {
+    fn send(&self, message: MailMessage) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            println!("[mail] to: {} | subject: {}\n{}", message.to, message.subject, message.body);
+            Ok(())
+        })
+    }
+}
+
+/// Picks the backend from `MAIL_BACKEND` (`outbox` by default, or `log`)
+pub fn mailer_from_env(client: &Client) -> Arc<dyn Mailer> {
+    match env::var("MAIL_BACKEND").as_deref() {
+        Ok("log") => Arc::new(LogMailer),
+        _ => Arc::new(OutboxMailer {
+            collection: client.database("disaster").collection("outbox"),
+        }),
+    }
+}
+
+/// Base URL of the frontend, used to build links sent by mail
+pub fn app_base_url() -> String {
+    env::var("APP_BASE_URL").unwrap_or_else(|_| "http://localhost:4200".to_string())
+}
@@ -1,3 +1,10 @@
 pub mod db;
 pub mod response;
-pub mod disaster_event_data;    
\ No newline at end of file
+pub mod disaster_event_data;
+pub mod crypto;
+pub mod mail;
+pub mod sms;
+pub mod locale;
+pub mod totp;
+pub mod auth_cookie;
+pub mod paging;
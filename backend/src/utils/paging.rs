@@ -0,0 +1,20 @@
+//! Page arithmetic shared by the paginated listings. `page` and `per_page` come straight
+//! from the query string, so both are bounded before they reach `skip`.
+
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+pub const MAX_PAGE_SIZE: i64 = 100;
+/// Deepest page served; keeps the skip well inside `i64`
+pub const MAX_PAGE: u64 = 100_000;
+
+/// Page (from 1) and page size to serve for the requested values
+pub fn page_window(page: Option<u64>, per_page: Option<i64>) -> (u64, i64) {
+    (
+        page.unwrap_or(1).clamp(1, MAX_PAGE),
+        per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE),
+    )
+}
+
+/// Documents to skip for a window from `page_window`
+pub fn skip(page: u64, per_page: i64) -> u64 {
+    page.clamp(1, MAX_PAGE).saturating_sub(1).saturating_mul(per_page.clamp(1, MAX_PAGE_SIZE) as u64)
+}
@@ -0,0 +1,74 @@
+use std::{env, sync::Arc};
+
+use futures::future::BoxFuture;
+use mongodb::{
+    bson::{oid::ObjectId, DateTime},
+    Client, Collection,
+};
+use serde::{Deserialize, Serialize};
+
+/// A text message waiting to be (or already) delivered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmsMessage {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub to: String,
+    pub body: String,
+    pub created_at: DateTime,
+}
+
+impl SmsMessage {
+    pub fn new(to: &str, body: String) -> Self {
+        SmsMessage {
+            id: None,
+            to: to.to_string(),
+            body,
+            created_at: DateTime::now(),
+        }
+    }
+}
+
+/// SMS delivery backend. Selected at startup with `SMS_BACKEND`.
+pub trait SmsGateway: Send + Sync {
+    fn send(&self, message: SmsMessage) -> BoxFuture<'_, Result<(), String>>;
+}
+
+/// Default backend: records every message in the `sms_log` collection, so phone login can be
+/// tested without a real provider and a delivery worker can pick the messages up
+pub struct CollectionSmsGateway {
+    collection: Collection<SmsMessage>,
+}
+
+impl SmsGateway for CollectionSmsGateway {
+    fn send(&self, message: SmsMessage) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            self.collection
+                .insert_one(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Failed to queue SMS: {}", e))
+        })
+    }
+}
+
+/// Prints messages to stdout instead of delivering them
+pub struct LogSmsGateway;
+
+impl SmsGateway for LogSmsGateway {
+    fn send(&self, message: SmsMessage) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            println!("[sms] to: {}\n{}", message.to, message.body);
+            Ok(())
+        })
+    }
+}
+
+/// Picks the backend from `SMS_BACKEND` (`collection` by default, or `log`)
+pub fn sms_gateway_from_env(client: &Client) -> Arc<dyn SmsGateway> {
+    match env::var("SMS_BACKEND").as_deref() {
+        Ok("log") => Arc::new(LogSmsGateway),
+        _ => Arc::new(CollectionSmsGateway {
+            collection: client.database("disaster").collection("sms_log"),
+        }),
+    }
+}
@@ -0,0 +1,120 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+/// RFC 6238 defaults, which every authenticator app supports
+const TIME_STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+/// Accepted clock drift, in time steps on each side
+const ALLOWED_DRIFT_STEPS: i64 = 1;
+
+/// Generates a new 160-bit shared secret, base32 encoded as authenticator apps expect
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+    BASE32_NOPAD.encode(&bytes)
+}
+
+/// `otpauth://` URI to render as a QR code during enrollment
+pub fn provisioning_uri(secret: &str, account: &str, issuer: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={TIME_STEP_SECONDS}",
+        issuer = encode_uri_component(issuer),
+        account = encode_uri_component(account),
+    )
+}
+
+/// Code for one time step (RFC 4226 HOTP with the step as counter)
+fn code_at(secret: &[u8], step: i64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+
+    binary % 10u32.pow(DIGITS)
+}
+
+/// Checks `code` against the secret around `unix_time` and returns the matching time step,
+/// so callers can refuse a step that was already used
+pub fn verify(secret: &str, code: &str, unix_time: i64) -> Option<i64> {
+    let secret = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let code: u32 = code.trim().parse().ok()?;
+    let current = unix_time / TIME_STEP_SECONDS;
+
+    (current - ALLOWED_DRIFT_STEPS..=current + ALLOWED_DRIFT_STEPS).find(|step| code_at(&secret, *step) == code)
+}
+
+fn encode_uri_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'@' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The SHA-1 secret of the RFC 6238 test vectors, `12345678901234567890`
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn matches_the_rfc_6238_vectors() {
+        // The RFC lists 8 digits; 6-digit codes are the last six of them
+        for (unix_time, code) in [
+            (59, "287082"),
+            (1_111_111_109, "081804"),
+            (1_111_111_111, "050471"),
+            (1_234_567_890, "005924"),
+            (2_000_000_000, "279037"),
+            (20_000_000_000, "353130"),
+        ] {
+            assert_eq!(verify(RFC_SECRET, code, unix_time), Some(unix_time / TIME_STEP_SECONDS), "at {}", unix_time);
+        }
+    }
+
+    #[test]
+    fn accepts_one_step_of_drift_either_way() {
+        // 287082 is the code of step 1 (seconds 30 to 59)
+        assert_eq!(verify(RFC_SECRET, "287082", 29), Some(1));
+        assert_eq!(verify(RFC_SECRET, "287082", 89), Some(1));
+        assert_eq!(verify(RFC_SECRET, "287082", 90), None);
+        assert_eq!(verify(RFC_SECRET, "287082", 1_111_111_109), None);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(verify(RFC_SECRET, " 287082 ", 59), Some(1));
+        assert_eq!(verify(RFC_SECRET, "28708x", 59), None);
+        assert_eq!(verify(RFC_SECRET, "", 59), None);
+        assert_eq!(verify("not base32!", "287082", 59), None);
+    }
+
+    #[test]
+    fn generated_secrets_round_trip_through_base32() {
+        let secret = generate_secret();
+        assert_eq!(BASE32_NOPAD.decode(secret.as_bytes()).unwrap().len(), 20);
+        let code = format!("{:06}", code_at(&BASE32_NOPAD.decode(secret.as_bytes()).unwrap(), 1000));
+        assert_eq!(verify(&secret, &code, 1000 * TIME_STEP_SECONDS), Some(1000));
+    }
+
+    #[test]
+    fn provisioning_uri_encodes_the_account() {
+        let uri = provisioning_uri("ABC", "ana maria@example.org", "Disaster Management");
+        assert_eq!(
+            uri,
+            "otpauth://totp/Disaster%20Management:ana%20maria@example.org?secret=ABC&issuer=Disaster%20Management&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}